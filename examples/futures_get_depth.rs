@@ -0,0 +1,18 @@
+use mexc_rs::futures::v1::endpoints::get_depth::{GetDepth, GetDepthParams};
+use mexc_rs::futures::MexcFuturesApiClient;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    std::env::set_var("RUST_LOG", "mexc_rs=debug,futures_get_depth=trace");
+    tracing_subscriber::fmt::init();
+
+    let client = MexcFuturesApiClient::default();
+    let params = GetDepthParams {
+        symbol: "BTC_USDT",
+        limit: Some(5),
+    };
+    let output = client.get_depth(params).await?;
+    tracing::info!("Output: {:#?}", output);
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use dotenv::dotenv;
+use futures::StreamExt;
+use mexc_rs::futures::ws::auth::{PersonalChannel, PersonalFilter, WebsocketAuth};
+use mexc_rs::futures::ws::login::{Login, LoginParams};
+use mexc_rs::futures::ws::stream::Stream;
+use mexc_rs::futures::ws::MexcFuturesWebsocketClient;
+
+#[tokio::main]
+async fn main() {
+    std::env::set_var(
+        "RUST_LOG",
+        "mexc_rs=debug,futures_private_subscription=trace",
+    );
+    tracing_subscriber::fmt::init();
+
+    dotenv().ok();
+    let api_key = std::env::var("MEXC_API_KEY").expect("MEXC_API_KEY not set");
+    let secret_key = std::env::var("MEXC_SECRET_KEY").expect("MEXC_SECRET_KEY not set");
+
+    let ws_client = MexcFuturesWebsocketClient::default().into_arc();
+    let mut stream = ws_client.clone().stream();
+    ws_client
+        .clone()
+        .login(LoginParams::new(
+            WebsocketAuth::new(api_key, secret_key),
+            vec![
+                PersonalFilter::new(PersonalChannel::Position),
+                PersonalFilter::new(PersonalChannel::LiquidateRisk),
+            ],
+        ))
+        .await
+        .expect("Failed to login");
+
+    while let Some(message) = stream.next().await {
+        dbg!(&message);
+    }
+}
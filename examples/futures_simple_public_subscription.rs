@@ -0,0 +1,33 @@
+use futures::StreamExt;
+use mexc_rs::futures::v1::models::KlineInterval;
+use mexc_rs::futures::ws::stream::Stream;
+use mexc_rs::futures::ws::subscribe::{Subscribe, SubscribeParams};
+use mexc_rs::futures::ws::topic::{DealTopic, KlineTopic, Topic};
+use mexc_rs::futures::ws::MexcFuturesWebsocketClient;
+
+#[tokio::main]
+async fn main() {
+    std::env::set_var(
+        "RUST_LOG",
+        "mexc_rs=debug,futures_simple_public_subscription=trace",
+    );
+    tracing_subscriber::fmt::init();
+
+    let ws_client = MexcFuturesWebsocketClient::default().into_arc();
+    let mut stream = ws_client.clone().stream();
+    ws_client
+        .clone()
+        .subscribe(SubscribeParams::default().with_topics(vec![
+            Topic::Deal(DealTopic::new("BTC_USDT".to_string())),
+            Topic::Kline(KlineTopic::new(
+                "BTC_USDT".to_string(),
+                KlineInterval::OneMinute,
+            )),
+        ]))
+        .await
+        .expect("Failed to subscribe");
+
+    while let Some(message) = stream.next().await {
+        dbg!(&message);
+    }
+}
@@ -0,0 +1,39 @@
+use dotenv::dotenv;
+use futures::StreamExt;
+use mexc_rs::spot::ws::message::Message;
+use mexc_rs::spot::ws::stream::Stream;
+use mexc_rs::spot::ws::subscribe::{Subscribe, SubscribeParams};
+use mexc_rs::spot::ws::topic::{BookTickerTopic, Topic};
+use mexc_rs::spot::ws::MexcSpotWebsocketClient;
+
+#[tokio::main]
+async fn main() {
+    std::env::set_var("RUST_LOG", "mexc_rs=debug,spot_book_ticker=trace");
+    tracing_subscriber::fmt::init();
+
+    dotenv().ok();
+    let ws_client = MexcSpotWebsocketClient::default().into_arc();
+    ws_client
+        .clone()
+        .subscribe(
+            SubscribeParams::default().with_topics(vec![Topic::BookTicker(BookTickerTopic::new(
+                "BTCUSDT".to_string(),
+            ))]),
+        )
+        .await
+        .expect("Failed to subscribe");
+
+    let mut stream = ws_client.stream();
+    while let Some(message) = stream.next().await {
+        if let Message::BookTicker(book_ticker) = message.as_ref() {
+            tracing::info!(
+                "{}: bid {} @ {}, ask {} @ {}",
+                book_ticker.symbol,
+                book_ticker.bid_quantity,
+                book_ticker.bid_price,
+                book_ticker.ask_quantity,
+                book_ticker.ask_price
+            );
+        }
+    }
+}
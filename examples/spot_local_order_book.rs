@@ -0,0 +1,35 @@
+use dotenv::dotenv;
+use futures::StreamExt;
+use mexc_rs::spot::ws::local_order_book::{LocalOrderBook, LocalOrderBookParams};
+use mexc_rs::spot::ws::MexcSpotWebsocketClient;
+
+#[tokio::main]
+async fn main() {
+    std::env::set_var("RUST_LOG", "mexc_rs=debug,spot_local_order_book=trace");
+    tracing_subscriber::fmt::init();
+
+    dotenv().ok();
+    let ws_client = MexcSpotWebsocketClient::default().into_arc();
+    let local_order_book = LocalOrderBook::new(
+        ws_client,
+        LocalOrderBookParams::default().with_symbol("BTCUSDT".to_string()),
+    )
+    .await
+    .expect("Failed to start local order book");
+
+    let mut stream = local_order_book.stream().take(20);
+    while let Some(order_book) = stream.next().await {
+        tracing::info!(
+            "{} v{}: best bid {:?}, best ask {:?}",
+            order_book.symbol(),
+            order_book.version(),
+            order_book.best_bid(),
+            order_book.best_ask()
+        );
+    }
+
+    local_order_book
+        .stop()
+        .await
+        .expect("Failed to stop local order book");
+}
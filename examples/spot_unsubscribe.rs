@@ -0,0 +1,37 @@
+use dotenv::dotenv;
+use futures::StreamExt;
+use mexc_rs::spot::ws::stream::Stream;
+use mexc_rs::spot::ws::subscribe::{Subscribe, SubscribeParams};
+use mexc_rs::spot::ws::topic::{DealsTopic, Topic};
+use mexc_rs::spot::ws::unsubscribe::{Unsubscribe, UnsubscribeParams};
+use mexc_rs::spot::ws::MexcSpotWebsocketClient;
+
+#[tokio::main]
+async fn main() {
+    std::env::set_var("RUST_LOG", "mexc_rs=debug,spot_unsubscribe=trace");
+    tracing_subscriber::fmt::init();
+
+    dotenv().ok();
+    let ws_client = MexcSpotWebsocketClient::default().into_arc();
+    let topics = vec![
+        Topic::Deals(DealsTopic::new("BTCUSDT".to_string())),
+        Topic::Deals(DealsTopic::new("KASUSDT".to_string())),
+    ];
+    ws_client
+        .clone()
+        .subscribe(SubscribeParams::default().with_topics(topics.clone()))
+        .await
+        .expect("Failed to subscribe");
+
+    let mut stream = ws_client.clone().stream().take(10);
+    while let Some(message) = stream.next().await {
+        dbg!(&message);
+    }
+
+    ws_client
+        .clone()
+        .unsubscribe(UnsubscribeParams::default().with_topics(topics))
+        .await
+        .expect("Failed to unsubscribe");
+    tracing::info!("Unsubscribed from all topics");
+}
@@ -12,6 +12,8 @@ pub enum ApiError {
     ErrorResponse(#[from] ErrorApiResponse),
     #[error("Get auth header map error: {0:?}")]
     GetAuthHeaderMapError(#[from] GetAuthHeaderMapError),
+    #[error("Unable to parse response")]
+    UnableToParseResponse,
 }
 
 // 0 	Operate succeed
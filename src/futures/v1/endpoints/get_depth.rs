@@ -0,0 +1,78 @@
+use crate::futures::response::ApiResponse;
+use crate::futures::result::ApiResult;
+use crate::futures::v1::models::DepthLevel;
+use crate::futures::{
+    MexcFuturesApiClient, MexcFuturesApiClientWithAuthentication, MexcFuturesApiEndpoint,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+
+#[derive(Debug)]
+pub struct GetDepthParams<'a> {
+    pub symbol: &'a str,
+    /// Amount of levels per side, all levels when not given.
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct GetDepthQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl From<GetDepthParams<'_>> for GetDepthQuery {
+    fn from(params: GetDepthParams<'_>) -> Self {
+        Self {
+            limit: params.limit,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct GetDepthOutput {
+    pub asks: Vec<DepthLevel>,
+    pub bids: Vec<DepthLevel>,
+    /// Version of the last change included, matches the versions of the `depth` websocket
+    /// channel.
+    pub version: u64,
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+pub trait GetDepth {
+    async fn get_depth(&self, params: GetDepthParams<'_>) -> ApiResult<GetDepthOutput>;
+}
+
+async fn default_impl(
+    endpoint: &MexcFuturesApiEndpoint,
+    reqwest: &Client,
+    params: GetDepthParams<'_>,
+) -> ApiResult<GetDepthOutput> {
+    let url = format!(
+        "{}/api/v1/contract/depth/{}",
+        endpoint.as_ref(),
+        params.symbol
+    );
+    let query = GetDepthQuery::from(params);
+    let response = reqwest.get(&url).query(&query).send().await?;
+    let api_response = response.json::<ApiResponse<GetDepthOutput>>().await?;
+    let output = api_response.into_api_result()?;
+
+    Ok(output)
+}
+
+#[async_trait]
+impl GetDepth for MexcFuturesApiClient {
+    async fn get_depth(&self, params: GetDepthParams<'_>) -> ApiResult<GetDepthOutput> {
+        default_impl(&self.endpoint, &self.reqwest_client, params).await
+    }
+}
+
+#[async_trait]
+impl GetDepth for MexcFuturesApiClientWithAuthentication {
+    async fn get_depth(&self, params: GetDepthParams<'_>) -> ApiResult<GetDepthOutput> {
+        default_impl(&self.endpoint, &self.reqwest_client, params).await
+    }
+}
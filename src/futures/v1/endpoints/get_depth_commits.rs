@@ -0,0 +1,79 @@
+use crate::futures::response::ApiResponse;
+use crate::futures::result::ApiResult;
+use crate::futures::v1::models::DepthLevel;
+use crate::futures::{
+    MexcFuturesApiClient, MexcFuturesApiClientWithAuthentication, MexcFuturesApiEndpoint,
+};
+use async_trait::async_trait;
+use reqwest::Client;
+
+#[derive(Debug)]
+pub struct GetDepthCommitsParams<'a> {
+    pub symbol: &'a str,
+    /// Amount of most recent commits.
+    pub limit: u32,
+}
+
+/// Changes of a single version of the order book, like the `depth` websocket channel pushes
+/// them.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct DepthCommit {
+    #[serde(default)]
+    pub asks: Vec<DepthLevel>,
+    #[serde(default)]
+    pub bids: Vec<DepthLevel>,
+    pub version: u64,
+}
+
+#[derive(Debug)]
+pub struct GetDepthCommitsOutput {
+    /// Ordered by version, from old to new.
+    pub commits: Vec<DepthCommit>,
+}
+
+#[async_trait]
+pub trait GetDepthCommits {
+    async fn get_depth_commits(
+        &self,
+        params: GetDepthCommitsParams<'_>,
+    ) -> ApiResult<GetDepthCommitsOutput>;
+}
+
+async fn default_impl(
+    endpoint: &MexcFuturesApiEndpoint,
+    reqwest: &Client,
+    params: GetDepthCommitsParams<'_>,
+) -> ApiResult<GetDepthCommitsOutput> {
+    let url = format!(
+        "{}/api/v1/contract/depth_commits/{}/{}",
+        endpoint.as_ref(),
+        params.symbol,
+        params.limit
+    );
+    let response = reqwest.get(&url).send().await?;
+    let api_response = response.json::<ApiResponse<Vec<DepthCommit>>>().await?;
+    let mut commits = api_response.into_api_result()?;
+    commits.sort_by_key(|commit| commit.version);
+
+    Ok(GetDepthCommitsOutput { commits })
+}
+
+#[async_trait]
+impl GetDepthCommits for MexcFuturesApiClient {
+    async fn get_depth_commits(
+        &self,
+        params: GetDepthCommitsParams<'_>,
+    ) -> ApiResult<GetDepthCommitsOutput> {
+        default_impl(&self.endpoint, &self.reqwest_client, params).await
+    }
+}
+
+#[async_trait]
+impl GetDepthCommits for MexcFuturesApiClientWithAuthentication {
+    async fn get_depth_commits(
+        &self,
+        params: GetDepthCommitsParams<'_>,
+    ) -> ApiResult<GetDepthCommitsOutput> {
+        default_impl(&self.endpoint, &self.reqwest_client, params).await
+    }
+}
@@ -1,11 +1,15 @@
+use crate::futures::error::ApiError;
 use crate::futures::response::ApiResponse;
 use crate::futures::result::ApiResult;
 use crate::futures::v1::models::{Kline, KlineInterval};
 use crate::futures::{
     MexcFuturesApiClient, MexcFuturesApiClientWithAuthentication, MexcFuturesApiEndpoint,
 };
+use crate::kline_history::{KlineHistoryCursor, KlineHistoryItem};
 use async_trait::async_trait;
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use futures::stream::BoxStream;
+use futures::StreamExt;
 use reqwest::Client;
 use rust_decimal::Decimal;
 
@@ -47,14 +51,129 @@ pub struct KlineData {
     pub amount: Vec<Decimal>,
 }
 
+/// Converts the columns to klines, the columns must all be of the same length.
+fn klines_from_data(data: KlineData) -> ApiResult<Vec<Kline>> {
+    let amount_of_entries = data.time.len();
+    let columns = [
+        data.open.len(),
+        data.close.len(),
+        data.high.len(),
+        data.low.len(),
+        data.vol.len(),
+        data.amount.len(),
+    ];
+    if columns.iter().any(|len| *len != amount_of_entries) {
+        tracing::error!(
+            "Kline columns have mismatched lengths, {} times and {:?} values",
+            amount_of_entries,
+            columns
+        );
+        return Err(ApiError::UnableToParseResponse);
+    }
+
+    let mut klines = Vec::with_capacity(amount_of_entries);
+    for i in 0..amount_of_entries {
+        let time = Utc
+            .timestamp_opt(data.time[i], 0)
+            .single()
+            .ok_or(ApiError::UnableToParseResponse)?;
+        let kline = Kline {
+            time,
+            open: data.open[i],
+            close: data.close[i],
+            high: data.high[i],
+            low: data.low[i],
+            volume: data.vol[i],
+            amount: data.amount[i],
+        };
+        klines.push(kline);
+    }
+
+    Ok(klines)
+}
+
 #[derive(Debug)]
 pub struct GetKlineOutput {
     pub klines: Vec<Kline>,
 }
 
+/// Most klines that are returned by a single request.
+pub const KLINE_PAGE_SIZE: usize = 2000;
+
+#[derive(Debug, Clone)]
+pub struct GetKlineHistoryParams {
+    pub symbol: String,
+    pub interval: KlineInterval,
+    pub start: DateTime<Utc>,
+    /// Exclusive.
+    pub end: DateTime<Utc>,
+}
+
+impl GetKlineHistoryParams {
+    pub fn new(
+        symbol: String,
+        interval: KlineInterval,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            symbol,
+            interval,
+            start,
+            end,
+        }
+    }
+}
+
 #[async_trait]
-pub trait GetKline {
+pub trait GetKline: Sync {
     async fn get_kline(&self, params: GetKlineParams<'_>) -> ApiResult<GetKlineOutput>;
+
+    /// Klines of any range, oldest first, requested page by page. Missing candles are reported as
+    /// gaps.
+    ///
+    /// The stream ends after the first error.
+    fn get_kline_history<'a>(
+        &'a self,
+        params: GetKlineHistoryParams,
+    ) -> BoxStream<'a, ApiResult<KlineHistoryItem<Kline>>> {
+        let stream = async_stream::stream! {
+            let mut cursor = KlineHistoryCursor::new(params.start, params.end);
+            while let Some((start, end)) = cursor.next_range() {
+                // Longer ranges are cut off by the server.
+                let page_end = (0..KLINE_PAGE_SIZE)
+                    .fold(start, |time, _| params.interval.next_open_time(time))
+                    .min(end);
+                let result = self
+                    .get_kline(GetKlineParams {
+                        symbol: &params.symbol,
+                        interval: params.interval,
+                        start: Some(start),
+                        // The end is inclusive and in seconds.
+                        end: Some(page_end - Duration::seconds(1)),
+                    })
+                    .await;
+                let output = match result {
+                    Ok(output) => output,
+                    Err(err) => {
+                        yield Err(err);
+                        return;
+                    }
+                };
+                let items = cursor.advance(
+                    output.klines,
+                    page_end,
+                    |kline| kline.time,
+                    |time| params.interval.next_open_time(time),
+                );
+                for item in items {
+                    yield Ok(item);
+                }
+            }
+        };
+
+        stream.boxed()
+    }
 }
 
 async fn default_impl(
@@ -72,20 +191,7 @@ async fn default_impl(
     let api_response = response.json::<ApiResponse<KlineData>>().await?;
     let data = api_response.into_api_result()?;
 
-    let amount_of_entries = data.time.len();
-    let mut klines = Vec::with_capacity(amount_of_entries);
-    for i in 0..amount_of_entries {
-        let kline = Kline {
-            time: Utc.timestamp_opt(data.time[i], 0).unwrap(),
-            open: data.open[i],
-            close: data.close[i],
-            high: data.high[i],
-            low: data.low[i],
-            volume: data.vol[i],
-            amount: data.amount[i],
-        };
-        klines.push(kline);
-    }
+    let klines = klines_from_data(data)?;
 
     Ok(GetKlineOutput { klines })
 }
@@ -103,3 +209,38 @@ impl GetKline for MexcFuturesApiClientWithAuthentication {
         default_impl(&self.endpoint, &self.reqwest_client, params).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn data(times: Vec<i64>, values: usize) -> KlineData {
+        let column = vec![Decimal::from_str("1.5").unwrap(); values];
+        KlineData {
+            time: times,
+            open: column.clone(),
+            close: column.clone(),
+            high: column.clone(),
+            low: column.clone(),
+            vol: column.clone(),
+            amount: column,
+        }
+    }
+
+    #[test]
+    fn klines_from_columns() {
+        let klines = klines_from_data(data(vec![1609740600, 1609741500], 2)).unwrap();
+        assert_eq!(klines.len(), 2);
+        assert_eq!(klines[1].time.timestamp(), 1609741500);
+
+        assert!(matches!(
+            klines_from_data(data(vec![1609740600, 1609741500], 1)),
+            Err(ApiError::UnableToParseResponse)
+        ));
+        assert!(matches!(
+            klines_from_data(data(vec![i64::MAX], 1)),
+            Err(ApiError::UnableToParseResponse)
+        ));
+    }
+}
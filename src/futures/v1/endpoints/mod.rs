@@ -1,5 +1,7 @@
 pub mod get_account_asset;
 pub mod get_account_assets;
+pub mod get_depth;
+pub mod get_depth_commits;
 pub mod get_kline;
 pub mod get_open_orders;
 pub mod get_open_positions;
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Months, Utc};
 use rust_decimal::Decimal;
 
 #[derive(Debug, serde::Deserialize)]
@@ -293,6 +293,46 @@ pub enum KlineInterval {
     OneMonth,
 }
 
+impl KlineInterval {
+    /// Open time of the candle that follows the one opened at the given time.
+    pub fn next_open_time(&self, open_time: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            KlineInterval::OneMinute => open_time + Duration::minutes(1),
+            KlineInterval::FiveMinutes => open_time + Duration::minutes(5),
+            KlineInterval::FifteenMinutes => open_time + Duration::minutes(15),
+            KlineInterval::ThirtyMinutes => open_time + Duration::minutes(30),
+            KlineInterval::OneHour => open_time + Duration::hours(1),
+            KlineInterval::FourHours => open_time + Duration::hours(4),
+            KlineInterval::EightHours => open_time + Duration::hours(8),
+            KlineInterval::OneDay => open_time + Duration::days(1),
+            KlineInterval::OneWeek => open_time + Duration::weeks(1),
+            KlineInterval::OneMonth => open_time
+                .checked_add_months(Months::new(1))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+        }
+    }
+}
+
+/// Level of the order book, pushed and returned as `[price, volume, order count]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(from = "(Decimal, Decimal, u32)")]
+pub struct DepthLevel {
+    pub price: Decimal,
+    /// Amount of contracts.
+    pub volume: Decimal,
+    pub order_count: u32,
+}
+
+impl From<(Decimal, Decimal, u32)> for DepthLevel {
+    fn from((price, volume, order_count): (Decimal, Decimal, u32)) -> Self {
+        Self {
+            price,
+            volume,
+            order_count,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Kline {
     pub time: DateTime<Utc>,
@@ -0,0 +1,189 @@
+use crate::futures::auth::{
+    sign_request, SignRequestError, SignRequestParams, SignRequestParamsKind,
+};
+use crate::futures::ws::SendableMessage;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+pub struct WebsocketAuth {
+    pub api_key: String,
+    pub secret_key: String,
+}
+
+impl WebsocketAuth {
+    pub fn new(api_key: String, secret_key: String) -> Self {
+        Self {
+            api_key,
+            secret_key,
+        }
+    }
+}
+
+/// Personal channel that is pushed after logging in, see [`PersonalFilter`].
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum PersonalChannel {
+    Order,
+    Position,
+    Asset,
+    StopOrder,
+    StopPlanOrder,
+    AdlLevel,
+    LiquidateRisk,
+}
+
+impl PersonalChannel {
+    /// Name of the filter, the messages are pushed on `push.personal.` followed by this name.
+    pub fn filter_name(&self) -> &'static str {
+        match self {
+            PersonalChannel::Order => "order",
+            PersonalChannel::Position => "position",
+            PersonalChannel::Asset => "asset",
+            PersonalChannel::StopOrder => "stop.order",
+            PersonalChannel::StopPlanOrder => "stop.planorder",
+            PersonalChannel::AdlLevel => "adl.level",
+            PersonalChannel::LiquidateRisk => "liquidate.risk",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct PersonalFilter {
+    pub channel: PersonalChannel,
+    /// Only push messages of these symbols, all symbols when empty.
+    pub symbols: Vec<String>,
+}
+
+impl PersonalFilter {
+    pub fn new(channel: PersonalChannel) -> Self {
+        Self {
+            channel,
+            symbols: Vec::new(),
+        }
+    }
+
+    pub fn with_symbol(mut self, symbol: String) -> Self {
+        self.symbols.push(symbol);
+        self
+    }
+
+    pub fn with_symbols(mut self, symbols: Vec<String>) -> Self {
+        self.symbols.extend(symbols);
+        self
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LoginParam<'a> {
+    api_key: &'a str,
+    req_time: String,
+    signature: String,
+    /// Whether all personal channels are pushed, otherwise only those of the filters.
+    subscribe: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct FilterParam<'a> {
+    filters: Vec<FilterParamItem<'a>>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct FilterParamItem<'a> {
+    filter: &'static str,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    rules: &'a [String],
+}
+
+/// The signature is made like the one of a request without parameters.
+fn login_message(
+    auth: &WebsocketAuth,
+    subscribe: bool,
+    time: DateTime<Utc>,
+) -> Result<SendableMessage, SignRequestError> {
+    let sign_request_output = sign_request(SignRequestParams {
+        time,
+        api_key: &auth.api_key,
+        secret_key: &auth.secret_key,
+        params_kind: SignRequestParamsKind::Query,
+        params: &(),
+    })?;
+    let param = LoginParam {
+        api_key: &auth.api_key,
+        req_time: time.timestamp_millis().to_string(),
+        signature: sign_request_output.signature,
+        subscribe,
+    };
+
+    Ok(SendableMessage::new(
+        "login",
+        Some(serde_json::to_value(param)?),
+    ))
+}
+
+fn filter_message(filters: &[PersonalFilter]) -> SendableMessage {
+    let param = FilterParam {
+        filters: filters
+            .iter()
+            .map(|filter| FilterParamItem {
+                filter: filter.channel.filter_name(),
+                rules: &filter.symbols,
+            })
+            .collect(),
+    };
+
+    SendableMessage::new(
+        "personal.filter",
+        Some(serde_json::to_value(param).expect("Failed to serialize personal filter")),
+    )
+}
+
+/// Login of the client, which is sent again on every new connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WebsocketLogin {
+    pub auth: WebsocketAuth,
+    pub filters: Vec<PersonalFilter>,
+}
+
+impl WebsocketLogin {
+    /// Without filters all personal channels are pushed.
+    pub fn to_messages(&self) -> Result<Vec<SendableMessage>, SignRequestError> {
+        let subscribe = self.filters.is_empty();
+        let mut messages = vec![login_message(&self.auth, subscribe, Utc::now())?];
+        if !subscribe {
+            messages.push(filter_message(&self.filters));
+        }
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    #[test]
+    fn login_and_filter_messages() {
+        let auth = WebsocketAuth::new("key".to_string(), "secret".to_string());
+        let time = Utc.timestamp_millis_opt(1611038237237).unwrap();
+        let message = login_message(&auth, false, time).unwrap();
+        let param = message.param.unwrap();
+        assert_eq!(message.method, "login");
+        assert_eq!(param["apiKey"], "key");
+        assert_eq!(param["reqTime"], "1611038237237");
+        assert_eq!(param["subscribe"], false);
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(b"key1611038237237");
+        assert_eq!(param["signature"], hex::encode(mac.finalize().into_bytes()));
+
+        let message = filter_message(&[
+            PersonalFilter::new(PersonalChannel::Order).with_symbol("BTC_USDT".to_string()),
+            PersonalFilter::new(PersonalChannel::LiquidateRisk),
+        ]);
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            r#"{"method":"personal.filter","param":{"filters":[{"filter":"order","rules":["BTC_USDT"]},{"filter":"liquidate.risk"}]}}"#
+        );
+    }
+}
@@ -0,0 +1,524 @@
+use crate::futures::auth::SignRequestError;
+use crate::futures::ws::auth::WebsocketLogin;
+use crate::futures::ws::message::{Message, ParsedFrame};
+use crate::futures::ws::topic::Topic;
+use crate::futures::ws::{message, Inner, MexcFuturesWebsocketClient, SendableMessage};
+use futures::stream::{SplitSink, SplitStream};
+use futures::{SinkExt, StreamExt};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::MutexGuard;
+use tokio_tungstenite::tungstenite::{self, Error};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Time given to send the close frame when the connection is closed.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+/// The first subscription or login waits for the connection, so it must not take forever.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Closes the connection when dropped.
+#[derive(Debug)]
+pub(crate) struct Connection {
+    pub id: Uuid,
+    pub message_tx: async_channel::Sender<SendableMessage>,
+    pub cancellation_token: CancellationToken,
+}
+
+impl Connection {
+    pub fn is_connected(&self) -> bool {
+        !self.cancellation_token.is_cancelled()
+    }
+
+    /// Fails with the message when the connection is down. The reconnect logs in and subscribes
+    /// to all topics of the client again.
+    pub async fn send(
+        &self,
+        message: SendableMessage,
+    ) -> Result<(), async_channel::SendError<SendableMessage>> {
+        if !self.is_connected() {
+            return Err(async_channel::SendError(message));
+        }
+        self.message_tx.send(message).await
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.cancellation_token.cancel();
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConnectError {
+    #[error("Tungestenite error: {0}")]
+    TungesteniteError(#[from] Error),
+
+    #[error("Failed to sign login: {0}")]
+    SignRequestError(#[from] SignRequestError),
+
+    #[error("Failed to send message through channel: {0}")]
+    SendError(#[from] async_channel::SendError<SendableMessage>),
+}
+
+/// Connects to the endpoint and spawns the tasks of the connection, logging in first when the
+/// client has a login.
+pub(crate) async fn connect(
+    this: Arc<MexcFuturesWebsocketClient>,
+    login: Option<&WebsocketLogin>,
+) -> Result<Connection, ConnectError> {
+    let (ws_stream, _) = tokio::time::timeout(
+        CONNECT_TIMEOUT,
+        tokio_tungstenite::connect_async(this.endpoint.to_string()),
+    )
+    .await
+    .map_err(|_| {
+        Error::Io(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "Timed out connecting to websocket",
+        ))
+    })??;
+    let (ws_tx, ws_rx) = ws_stream.split();
+    let (tx, rx) = async_channel::unbounded();
+
+    let connection_id = Uuid::new_v4();
+    let cancellation_token = this.shutdown_token.child_token();
+
+    spawn_sender_task(
+        this.clone(),
+        ws_tx,
+        rx,
+        cancellation_token.clone(),
+        connection_id,
+    );
+    spawn_receiver_task(
+        this.clone(),
+        ws_rx,
+        cancellation_token.clone(),
+        connection_id,
+    );
+    spawn_ping_task(this.clone(), tx.clone(), cancellation_token.clone());
+
+    let connection = Connection {
+        id: connection_id,
+        message_tx: tx,
+        cancellation_token,
+    };
+    if let Some(login) = login {
+        send_login(&connection, login).await?;
+    }
+
+    Ok(connection)
+}
+
+/// Locks the client, connecting first when there is no connection. The lock is not held while
+/// connecting, so the topics can be read and the client shut down in the meantime.
+pub(crate) async fn lock_connected(
+    this: &Arc<MexcFuturesWebsocketClient>,
+) -> Result<MutexGuard<'_, Inner>, ConnectError> {
+    let login = {
+        let inner = this.inner.lock().await;
+        if inner.connection.is_some() {
+            return Ok(inner);
+        }
+        inner.login.clone()
+    };
+
+    let connection = connect(this.clone(), login.as_ref()).await?;
+
+    let mut inner = this.inner.lock().await;
+    if inner.connection.is_some() {
+        // Connected by someone else in the meantime, ours is closed when dropped.
+        return Ok(inner);
+    }
+    // Logged in while connecting.
+    if inner.login != login {
+        if let Some(login) = &inner.login {
+            send_login(&connection, login).await?;
+        }
+    }
+    // The topics are kept when the reconnect policy gave up.
+    resubscribe(&connection, &inner.topics).await?;
+    inner.connection = Some(connection);
+
+    Ok(inner)
+}
+
+fn cancel_connection(cancellation_token: &CancellationToken) -> bool {
+    let was_cancelled = cancellation_token.is_cancelled();
+    cancellation_token.cancel();
+    !was_cancelled
+}
+
+/// Keeps reconnecting until it succeeds or the reconnect policy gives up, and subscribes to
+/// all topics again.
+async fn handle_disconnect(this: Arc<MexcFuturesWebsocketClient>, connection_id: Uuid) {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        if !this.reconnect_policy.allows_attempt(attempt) {
+            tracing::error!(
+                "Giving up reconnecting futures websocket after {} attempts",
+                attempt - 1
+            );
+            let mut inner = this.inner.lock().await;
+            if is_current_connection(&inner, connection_id) {
+                // Subscribing connects again.
+                inner.connection = None;
+            }
+            return;
+        }
+
+        tokio::select! {
+            _ = this.shutdown_token.cancelled() => {
+                return;
+            }
+            _ = tokio::time::sleep(this.reconnect_policy.delay(attempt)) => {}
+        }
+
+        // The lock is not held while connecting, so subscribing and logging in don't wait for
+        // the reconnect.
+        let login = {
+            let inner = this.inner.lock().await;
+            if !is_current_connection(&inner, connection_id) {
+                // The connection was closed or replaced in the meantime.
+                return;
+            }
+            inner.login.clone()
+        };
+
+        tracing::debug!("Reconnecting futures websocket, attempt {}", attempt);
+        let connection = match connect(this.clone(), login.as_ref()).await {
+            Ok(connection) => connection,
+            Err(err) => {
+                tracing::error!("Failed to reconnect futures websocket: {}", err);
+                continue;
+            }
+        };
+
+        let mut inner = this.inner.lock().await;
+        if !is_current_connection(&inner, connection_id) {
+            return;
+        }
+        // Logged in again while connecting.
+        if inner.login != login {
+            if let Some(login) = &inner.login {
+                if let Err(err) = send_login(&connection, login).await {
+                    tracing::error!("Failed to log in on futures websocket: {}", err);
+                    continue;
+                }
+            }
+        }
+        // Topics that were subscribed to while connecting are included.
+        if let Err(err) = resubscribe(&connection, &inner.topics).await {
+            tracing::error!("Failed to resubscribe futures websocket: {}", err);
+            continue;
+        }
+        inner.connection = Some(connection);
+        return;
+    }
+}
+
+fn is_current_connection(inner: &Inner, connection_id: Uuid) -> bool {
+    inner
+        .connection
+        .as_ref()
+        .is_some_and(|connection| connection.id == connection_id)
+}
+
+async fn send_login(connection: &Connection, login: &WebsocketLogin) -> Result<(), ConnectError> {
+    for message in login.to_messages()? {
+        connection.send(message).await?;
+    }
+    Ok(())
+}
+
+async fn resubscribe(
+    connection: &Connection,
+    topics: &[Topic],
+) -> Result<(), async_channel::SendError<SendableMessage>> {
+    for topic in topics {
+        connection.send(topic.to_subscription_message()).await?;
+    }
+    Ok(())
+}
+
+fn spawn_sender_task(
+    this: Arc<MexcFuturesWebsocketClient>,
+    mut ws_tx: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, tungstenite::Message>,
+    rx: async_channel::Receiver<SendableMessage>,
+    cancellation_token: CancellationToken,
+    connection_id: Uuid,
+) {
+    this.clone().spawn_task(async move {
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    // Let the server know we are going, the connection might already be gone.
+                    let _ = tokio::time::timeout(CLOSE_TIMEOUT, ws_tx.close()).await;
+                    break;
+                }
+                message_result = rx.recv() => {
+                    let message = match message_result {
+                        Ok(x) => x,
+                        Err(err) => {
+                            cancellation_token.cancel();
+                            tracing::error!("Error receiving message from channel: {}", err);
+                            break;
+                        }
+                    };
+                    let json = serde_json::to_string(&message).expect("Failed to serialize message");
+
+                    if let Err(err) = ws_tx.send(tungstenite::Message::Text(json)).await {
+                        tracing::error!("Failed to send message to futures websocket: {}", err);
+                        if cancel_connection(&cancellation_token) {
+                            handle_disconnect(this.clone(), connection_id).await;
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+fn spawn_receiver_task(
+    this: Arc<MexcFuturesWebsocketClient>,
+    mut ws_rx: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    cancellation_token: CancellationToken,
+    connection_id: Uuid,
+) {
+    this.clone().spawn_task(async move {
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    break;
+                }
+                message_result_opt = ws_rx.next() => {
+                    let message = match message_result_opt {
+                        Some(Ok(message)) => message,
+                        Some(Err(err)) => {
+                            tracing::error!("Failed to receive message from futures websocket: {}", err);
+                            if cancel_connection(&cancellation_token) {
+                                handle_disconnect(this.clone(), connection_id).await;
+                            }
+                            break;
+                        }
+                        None => {
+                            if cancel_connection(&cancellation_token) {
+                                handle_disconnect(this.clone(), connection_id).await;
+                            }
+                            break;
+                        }
+                    };
+
+                    let text = match message {
+                        tungstenite::Message::Text(text) => text,
+                        tungstenite::Message::Binary(data) => match String::from_utf8(data) {
+                            Ok(text) => text,
+                            Err(err) => {
+                                tracing::debug!("Received binary message that is not utf-8: {}", err);
+                                continue;
+                            }
+                        },
+                        _ => {
+                            tracing::debug!("Received non-text message: {:?}", message);
+                            continue;
+                        }
+                    };
+
+                    let message = match message::parse_text_frame(text) {
+                        ParsedFrame::Pong => continue,
+                        ParsedFrame::Reply(reply) => {
+                            if reply.channel == "rs.error" {
+                                tracing::warn!("Futures websocket replied with an error: {}", reply.data);
+                            } else {
+                                tracing::debug!("Futures websocket replied on {}: {}", reply.channel, reply.data);
+                            }
+                            // Nobody might be waiting for the reply.
+                            let _ = this.reply_tx.send((connection_id, Arc::new(reply)));
+                            continue;
+                        }
+                        ParsedFrame::Message(message) => message,
+                        ParsedFrame::Unknown(text) => Message::Unknown(text),
+                    };
+
+                    // There might not be anyone listening
+                    let _ = this.broadcast_tx.send(Arc::new(message));
+                }
+            }
+        }
+    });
+}
+
+fn spawn_ping_task(
+    this: Arc<MexcFuturesWebsocketClient>,
+    sender: async_channel::Sender<SendableMessage>,
+    cancellation_token: CancellationToken,
+) {
+    let ping_interval = this.ping_interval;
+    this.spawn_task(async move {
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    break;
+                }
+                _ = tokio::time::sleep(ping_interval) => {
+                    if let Err(err) = sender.send(SendableMessage::ping()).await {
+                        cancellation_token.cancel();
+                        tracing::error!("Failed to send ping: {}", err);
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::futures::ws::endpoint::MexcFuturesWebsocketEndpoint;
+    use crate::futures::ws::subscribe::{Subscribe, SubscribeParams};
+    use crate::futures::ws::test_server::Server;
+    use crate::futures::ws::topic::{DealTopic, TickerTopic};
+    use crate::reconnect_policy::ReconnectPolicy;
+
+    fn deal_topic() -> Topic {
+        Topic::Deal(DealTopic::new("BTC_USDT".to_string()))
+    }
+
+    fn ticker_topic() -> Topic {
+        Topic::Ticker(TickerTopic::new("BTC_USDT".to_string()))
+    }
+
+    async fn wait_for_connection(
+        client: &MexcFuturesWebsocketClient,
+        predicate: impl Fn(Option<&Connection>) -> bool,
+    ) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !predicate(client.inner.lock().await.connection.as_ref()) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn subscribe_while_reconnecting() {
+        let mut server = Server::start().await;
+        let client = MexcFuturesWebsocketClient::new(server.endpoint())
+            .with_reconnect_policy(
+                ReconnectPolicy::default()
+                    .with_initial_delay(Duration::from_millis(200))
+                    .with_jitter(0.0),
+            )
+            .into_arc();
+
+        client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(deal_topic()))
+            .await
+            .unwrap();
+        assert_eq!(server.subscriptions(0, 1).await, vec!["sub.deal"]);
+
+        server.drop_connection(0);
+        wait_for_connection(&client, |connection| {
+            connection.is_some_and(|connection| !connection.is_connected())
+        })
+        .await;
+        client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(ticker_topic()))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.subscriptions(1, 2).await,
+            vec!["sub.deal", "sub.ticker"]
+        );
+        client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn subscribe_after_reconnect_gave_up() {
+        let mut server = Server::start().await;
+        let client = MexcFuturesWebsocketClient::new(server.endpoint())
+            .with_reconnect_policy(ReconnectPolicy::default().with_max_attempts(0))
+            .into_arc();
+
+        client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(deal_topic()))
+            .await
+            .unwrap();
+        assert_eq!(server.subscriptions(0, 1).await, vec!["sub.deal"]);
+
+        server.drop_connection(0);
+        wait_for_connection(&client, |connection| connection.is_none()).await;
+        client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(ticker_topic()))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            server.subscriptions(1, 2).await,
+            vec!["sub.deal", "sub.ticker"]
+        );
+        client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn topics_and_shutdown_do_not_wait_for_connecting() {
+        // Accepts connections but never completes the websocket handshake.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = MexcFuturesWebsocketEndpoint::Custom(format!(
+            "ws://{}",
+            listener.local_addr().unwrap()
+        ));
+        let client = MexcFuturesWebsocketClient::new(endpoint).into_arc();
+        let subscribe = tokio::spawn(
+            client
+                .clone()
+                .subscribe(SubscribeParams::default().with_topic(deal_topic())),
+        );
+        let _stream = listener.accept().await.unwrap();
+
+        let timeout = Duration::from_secs(1);
+        assert!(tokio::time::timeout(timeout, client.topics())
+            .await
+            .unwrap()
+            .is_empty());
+        tokio::time::timeout(timeout, client.shutdown())
+            .await
+            .unwrap();
+        subscribe.abort();
+    }
+
+    #[tokio::test]
+    async fn shutdown_waits_for_the_connection_tasks() {
+        let mut server = Server::start().await;
+        let client = MexcFuturesWebsocketClient::new(server.endpoint()).into_arc();
+        client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(deal_topic()))
+            .await
+            .unwrap();
+        assert_eq!(server.subscriptions(0, 1).await, vec!["sub.deal"]);
+
+        let tasks = client
+            .tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|task| task.abort_handle())
+            .collect::<Vec<_>>();
+        assert_eq!(tasks.len(), 3);
+        client.shutdown().await;
+        assert!(tasks.iter().all(|task| task.is_finished()));
+    }
+}
@@ -0,0 +1,433 @@
+use crate::futures::v1::endpoints::get_depth::{GetDepth, GetDepthOutput, GetDepthParams};
+use crate::futures::v1::endpoints::get_depth_commits::{
+    DepthCommit, GetDepthCommits, GetDepthCommitsParams,
+};
+use crate::futures::v1::models::DepthLevel;
+use crate::futures::ws::message::Message;
+use crate::futures::ws::stream::Stream;
+use crate::futures::ws::subscribe::{Subscribe, SubscribeError, SubscribeParams};
+use crate::futures::ws::topic::{DepthTopic, Topic};
+use crate::futures::ws::unsubscribe::{Unsubscribe, UnsubscribeError, UnsubscribeParams};
+use crate::futures::ws::MexcFuturesWebsocketClient;
+use crate::futures::MexcFuturesApiClient;
+use crate::order_book::{
+    spawn_fetch_task, DepthDiff, GapRecovery, MaintainedBooks, OrderBookLevel, SymbolSync,
+    SyncAction,
+};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+#[derive(Debug)]
+pub struct LocalOrderBookParams {
+    pub symbols: Vec<String>,
+    /// Amount of levels requested for the snapshot, all levels when not given.
+    pub depth_limit: Option<u32>,
+    /// Amount of recent depth commits requested to fill a version gap, a gap that goes back
+    /// further is resolved with a new snapshot.
+    pub commits_limit: u32,
+}
+
+impl Default for LocalOrderBookParams {
+    fn default() -> Self {
+        Self::new(Vec::new(), None, 100)
+    }
+}
+
+impl LocalOrderBookParams {
+    pub fn new(symbols: Vec<String>, depth_limit: Option<u32>, commits_limit: u32) -> Self {
+        Self {
+            symbols,
+            depth_limit,
+            commits_limit,
+        }
+    }
+
+    pub fn with_symbol(mut self, symbol: String) -> Self {
+        self.symbols.push(symbol);
+        self
+    }
+
+    pub fn with_symbols(mut self, symbols: Vec<String>) -> Self {
+        self.symbols.extend(symbols);
+        self
+    }
+
+    pub fn with_depth_limit(mut self, depth_limit: u32) -> Self {
+        self.depth_limit = Some(depth_limit);
+        self
+    }
+
+    pub fn with_commits_limit(mut self, commits_limit: u32) -> Self {
+        self.commits_limit = commits_limit;
+        self
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LocalOrderBookError {
+    #[error("Subscribe error: {0}")]
+    SubscribeError(#[from] SubscribeError),
+}
+
+/// L2 order book of a single contract.
+pub type OrderBook = crate::order_book::OrderBook<DepthLevel>;
+
+impl OrderBook {
+    pub fn from_snapshot(symbol: String, snapshot: &GetDepthOutput) -> Self {
+        Self::new(symbol, snapshot.version, &snapshot.bids, &snapshot.asks)
+    }
+}
+
+impl OrderBookLevel for DepthLevel {
+    fn price(&self) -> Decimal {
+        self.price
+    }
+
+    fn quantity(&self) -> Decimal {
+        self.volume
+    }
+}
+
+impl From<&DepthCommit> for DepthDiff<DepthLevel> {
+    fn from(commit: &DepthCommit) -> Self {
+        Self {
+            from_version: commit.version,
+            to_version: commit.version,
+            asks: commit.asks.clone(),
+            bids: commit.bids.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum FetchResult {
+    Snapshot(GetDepthOutput),
+    Commits(Vec<DepthCommit>),
+}
+
+/// Maintains L2 order books of contracts by combining depth snapshots of the REST api with the
+/// incremental depth of the websocket. Missed versions are filled in with depth commits, or with
+/// a new snapshot when the commits don't go back far enough.
+///
+/// Dropping it or shutting down the websocket client stops maintaining the order books, but
+/// only [`LocalOrderBook::stop`] unsubscribes from the depth topics.
+#[derive(Debug)]
+pub struct LocalOrderBook {
+    ws_client: Arc<MexcFuturesWebsocketClient>,
+    /// Depth topics that were not subscribed to before, the others are left alone when stopping.
+    topics: Vec<Topic>,
+    books: MaintainedBooks<DepthLevel>,
+}
+
+impl LocalOrderBook {
+    pub async fn new(
+        ws_client: Arc<MexcFuturesWebsocketClient>,
+        api_client: Arc<MexcFuturesApiClient>,
+        params: LocalOrderBookParams,
+    ) -> Result<Self, LocalOrderBookError> {
+        let topics = params
+            .symbols
+            .iter()
+            .map(|symbol| Topic::Depth(DepthTopic::new(symbol.clone())))
+            .collect::<Vec<_>>();
+
+        let subscribed_topics = ws_client.topics().await;
+        let added_topics = topics
+            .iter()
+            .filter(|topic| !subscribed_topics.contains(topic))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // Listen before subscribing so that no diff gets lost.
+        let messages = ws_client.clone().stream();
+        ws_client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topics(topics))
+            .await?;
+
+        let cancellation_token = ws_client.shutdown_token.child_token();
+        let local_order_book = Self {
+            ws_client,
+            topics: added_topics,
+            books: MaintainedBooks::new(cancellation_token),
+        };
+        spawn_local_order_book_task(&local_order_book, api_client, messages, params);
+
+        Ok(local_order_book)
+    }
+
+    /// The order book of the symbol, `None` while it is (re)syncing.
+    pub async fn order_book(&self, symbol: &str) -> Option<Arc<OrderBook>> {
+        self.books.get(symbol).await
+    }
+
+    /// Every order book after an update was applied. A consumer that falls behind skips the
+    /// pending updates and receives the current book of every contract instead.
+    pub fn stream(&self) -> BoxStream<'static, Arc<OrderBook>> {
+        self.books.stream()
+    }
+
+    /// Stops maintaining the order books and unsubscribes from the depth topics it subscribed
+    /// to, topics that were already subscribed to stay subscribed.
+    pub async fn stop(&self) -> Result<(), UnsubscribeError> {
+        self.books.stop().await;
+        self.ws_client
+            .clone()
+            .unsubscribe(UnsubscribeParams::default().with_topics(self.topics.clone()))
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn spawn_local_order_book_task(
+    local_order_book: &LocalOrderBook,
+    api_client: Arc<MexcFuturesApiClient>,
+    mut messages: BoxStream<'static, Arc<Message>>,
+    params: LocalOrderBookParams,
+) {
+    let publisher = local_order_book.books.publisher();
+    let cancellation_token = local_order_book.books.cancellation_token().clone();
+    let depth_limit = params.depth_limit;
+    let commits_limit = params.commits_limit;
+
+    let (fetch_tx, mut fetch_rx) = mpsc::unbounded_channel::<(String, FetchResult)>();
+    let fetch_snapshot = {
+        let api_client = api_client.clone();
+        let fetch_tx = fetch_tx.clone();
+        let cancellation_token = cancellation_token.clone();
+        move |symbol: String| {
+            let api_client = api_client.clone();
+            let fetch_symbol = symbol.clone();
+            spawn_fetch_task(
+                symbol,
+                "depth snapshot",
+                move || {
+                    let api_client = api_client.clone();
+                    let symbol = fetch_symbol.clone();
+                    async move {
+                        api_client
+                            .get_depth(GetDepthParams {
+                                symbol: &symbol,
+                                limit: depth_limit,
+                            })
+                            .await
+                            .map(FetchResult::Snapshot)
+                    }
+                },
+                fetch_tx.clone(),
+                cancellation_token.child_token(),
+            );
+        }
+    };
+    let fetch_commits = {
+        let cancellation_token = cancellation_token.clone();
+        move |symbol: String| {
+            let api_client = api_client.clone();
+            let fetch_symbol = symbol.clone();
+            spawn_fetch_task(
+                symbol,
+                "depth commits",
+                move || {
+                    let api_client = api_client.clone();
+                    let symbol = fetch_symbol.clone();
+                    async move {
+                        api_client
+                            .get_depth_commits(GetDepthCommitsParams {
+                                symbol: &symbol,
+                                limit: commits_limit,
+                            })
+                            .await
+                            .map(|output| FetchResult::Commits(output.commits))
+                    }
+                },
+                fetch_tx.clone(),
+                cancellation_token.child_token(),
+            );
+        }
+    };
+
+    tokio::spawn(async move {
+        let mut states = params
+            .symbols
+            .into_iter()
+            .map(|symbol| (symbol, SymbolSync::new(GapRecovery::MissedDiffs)))
+            .collect::<HashMap<_, _>>();
+        for symbol in states.keys() {
+            fetch_snapshot(symbol.clone());
+        }
+
+        loop {
+            let (symbol, action) = tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    break;
+                }
+                Some((symbol, result)) = fetch_rx.recv() => {
+                    let Some(state) = states.get_mut(&symbol) else {
+                        continue;
+                    };
+                    let action = match result {
+                        FetchResult::Snapshot(snapshot) => {
+                            state.on_snapshot(OrderBook::from_snapshot(symbol.clone(), &snapshot))
+                        }
+                        FetchResult::Commits(commits) => state.on_missed_diffs(
+                            &commits.iter().map(DepthDiff::from).collect::<Vec<_>>(),
+                        ),
+                    };
+                    (symbol, action)
+                }
+                message_opt = messages.next() => {
+                    let Some(message) = message_opt else {
+                        break;
+                    };
+                    let Message::Depth(depth) = message.as_ref() else {
+                        continue;
+                    };
+                    let Some(state) = states.get_mut(&depth.symbol) else {
+                        continue;
+                    };
+                    let action = state.on_diff(DepthDiff {
+                        from_version: depth.version,
+                        to_version: depth.version,
+                        asks: depth.asks.clone(),
+                        bids: depth.bids.clone(),
+                    });
+                    (depth.symbol.clone(), action)
+                }
+            };
+
+            match action {
+                SyncAction::None => {}
+                SyncAction::Publish => {
+                    if let Some(book) = states.get(&symbol).and_then(|state| state.book()) {
+                        publisher.publish(book).await;
+                    }
+                }
+                SyncAction::FetchMissedDiffs => {
+                    publisher.remove(&symbol).await;
+                    fetch_commits(symbol);
+                }
+                SyncAction::FetchSnapshot => {
+                    publisher.remove(&symbol).await;
+                    fetch_snapshot(symbol);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::futures::ws::test_server::Server;
+    use crate::futures::MexcFuturesApiEndpoint;
+
+    fn level(price: i64, volume: i64) -> DepthLevel {
+        DepthLevel {
+            price: Decimal::from(price),
+            volume: Decimal::from(volume),
+            order_count: 1,
+        }
+    }
+
+    fn snapshot(version: u64) -> GetDepthOutput {
+        GetDepthOutput {
+            bids: vec![level(99, 1), level(98, 2)],
+            asks: vec![level(101, 1), level(102, 2)],
+            version,
+            timestamp: None,
+        }
+    }
+
+    fn diff(version: u64) -> DepthDiff<DepthLevel> {
+        DepthDiff {
+            from_version: version,
+            to_version: version,
+            asks: vec![],
+            bids: vec![],
+        }
+    }
+
+    fn commit(version: u64, bids: Vec<DepthLevel>) -> DepthCommit {
+        DepthCommit {
+            asks: vec![],
+            bids,
+            version,
+        }
+    }
+
+    #[test]
+    fn repair_gap_with_commits() {
+        let mut state = SymbolSync::new(GapRecovery::MissedDiffs);
+        state.on_snapshot(OrderBook::from_snapshot(
+            "BTC_USDT".to_string(),
+            &snapshot(10),
+        ));
+        assert_eq!(state.on_diff(diff(12)), SyncAction::FetchMissedDiffs);
+
+        let commits = [commit(11, vec![level(100, 1)]), commit(12, vec![])];
+        assert_eq!(
+            state.on_missed_diffs(&commits.iter().map(DepthDiff::from).collect::<Vec<_>>()),
+            SyncAction::Publish
+        );
+        let book = state.book().unwrap();
+        assert_eq!(book.version(), 12);
+        assert_eq!(book.best_bid().unwrap().price, Decimal::from(100));
+        assert_eq!(book.best_bid().unwrap().order_count, 1);
+    }
+
+    fn api_client() -> Arc<MexcFuturesApiClient> {
+        Arc::new(MexcFuturesApiClient::new(MexcFuturesApiEndpoint::Custom(
+            "http://127.0.0.1:1".to_string(),
+        )))
+    }
+
+    #[tokio::test]
+    async fn stop_leaves_topics_that_were_already_subscribed() {
+        let server = Server::start().await;
+        let ws_client = MexcFuturesWebsocketClient::new(server.endpoint()).into_arc();
+        let btc_topic = Topic::Depth(DepthTopic::new("BTC_USDT".to_string()));
+        ws_client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(btc_topic.clone()))
+            .await
+            .unwrap();
+
+        let local_order_book = LocalOrderBook::new(
+            ws_client.clone(),
+            api_client(),
+            LocalOrderBookParams::default()
+                .with_symbols(vec!["BTC_USDT".to_string(), "ETH_USDT".to_string()]),
+        )
+        .await
+        .unwrap();
+        local_order_book.stop().await.unwrap();
+
+        assert_eq!(ws_client.topics().await, vec![btc_topic]);
+        ws_client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn drop_and_shutdown_stop_the_tasks() {
+        let server = Server::start().await;
+        let ws_client = MexcFuturesWebsocketClient::new(server.endpoint()).into_arc();
+        let params = || LocalOrderBookParams::default().with_symbol("BTC_USDT".to_string());
+
+        let local_order_book = LocalOrderBook::new(ws_client.clone(), api_client(), params())
+            .await
+            .unwrap();
+        let cancellation_token = local_order_book.books.cancellation_token().clone();
+        drop(local_order_book);
+        assert!(cancellation_token.is_cancelled());
+
+        let local_order_book = LocalOrderBook::new(ws_client.clone(), api_client(), params())
+            .await
+            .unwrap();
+        ws_client.shutdown().await;
+        assert!(local_order_book.books.cancellation_token().is_cancelled());
+    }
+}
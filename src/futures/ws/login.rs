@@ -0,0 +1,190 @@
+use crate::futures::auth::SignRequestError;
+use crate::futures::ws::auth::{PersonalFilter, WebsocketAuth, WebsocketLogin};
+use crate::futures::ws::connection::{lock_connected, ConnectError};
+use crate::futures::ws::{MexcFuturesWebsocketClient, SendableMessage};
+use async_channel::SendError;
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+/// How long to wait for the server to reply to the login.
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub struct LoginParams {
+    pub auth: WebsocketAuth,
+    /// Personal channels to push, all of them when empty.
+    pub filters: Vec<PersonalFilter>,
+}
+
+impl LoginParams {
+    pub fn new(auth: WebsocketAuth, filters: Vec<PersonalFilter>) -> Self {
+        Self { auth, filters }
+    }
+
+    pub fn with_filter(mut self, filter: PersonalFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn with_filters(mut self, filters: Vec<PersonalFilter>) -> Self {
+        self.filters.extend(filters);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoginOutput {}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoginError {
+    #[error("Failed to connect: {0}")]
+    ConnectError(#[from] ConnectError),
+
+    #[error("Failed to sign login: {0}")]
+    SignRequestError(#[from] SignRequestError),
+
+    #[error("Failed to send message through channel: {0}")]
+    SendError(#[from] SendError<SendableMessage>),
+
+    #[error("Login was rejected: {0}")]
+    Rejected(String),
+
+    /// The login is kept, it is sent again once the connection is reestablished. Errors the
+    /// server replied with in the meantime are logged, as they can't be told apart from errors
+    /// on other requests.
+    #[error("Server did not reply to the login in time")]
+    Unconfirmed,
+
+    #[error("Client was shut down")]
+    ClientShutDown,
+}
+
+#[async_trait]
+pub trait Login {
+    /// Logs in to receive the personal channels, the login is repeated whenever the connection is
+    /// reestablished. Logging in again replaces the previous login and filters.
+    async fn login(self: Arc<Self>, params: LoginParams) -> Result<LoginOutput, LoginError>;
+}
+
+#[async_trait]
+impl Login for MexcFuturesWebsocketClient {
+    async fn login(self: Arc<Self>, params: LoginParams) -> Result<LoginOutput, LoginError> {
+        let login = WebsocketLogin {
+            auth: params.auth,
+            filters: params.filters,
+        };
+        let mut reply_rx = self.reply_tx.subscribe();
+        let connection_id = {
+            let mut inner = self.inner.lock().await;
+            inner.login = Some(login.clone());
+            match &inner.connection {
+                Some(connection) => {
+                    for message in login.to_messages()? {
+                        // Fails while the connection is down, the reconnect still logs in.
+                        connection.send(message).await?;
+                    }
+                    Some(connection.id)
+                }
+                None => None,
+            }
+        };
+        let connection_id = match connection_id {
+            Some(connection_id) => connection_id,
+            // Connecting logs in with the stored login.
+            None => {
+                lock_connected(&self)
+                    .await?
+                    .connection
+                    .as_ref()
+                    .expect("Connected above")
+                    .id
+            }
+        };
+
+        let reply = tokio::time::timeout(CONFIRMATION_TIMEOUT, async {
+            loop {
+                match reply_rx.recv().await {
+                    Ok((reply_connection_id, reply)) => {
+                        // Errors on `rs.error` don't say which request they belong to, so only
+                        // the reply on `rs.login` decides the outcome.
+                        if reply_connection_id == connection_id && reply.channel == "rs.login" {
+                            return Ok(reply);
+                        }
+                    }
+                    Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return Err(LoginError::ClientShutDown),
+                }
+            }
+        })
+        .await
+        .map_err(|_| LoginError::Unconfirmed)??;
+
+        if reply.data == "success" {
+            return Ok(LoginOutput {});
+        }
+
+        // Don't keep logging in with a login that the server refuses.
+        let mut inner = self.inner.lock().await;
+        if inner
+            .login
+            .as_ref()
+            .is_some_and(|current_login| current_login.auth == login.auth)
+        {
+            inner.login = None;
+        }
+        let message = match reply.data.as_str() {
+            Some(message) => message.to_string(),
+            None => reply.data.to_string(),
+        };
+        Err(LoginError::Rejected(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::futures::ws::test_server::Server;
+
+    fn login_params() -> LoginParams {
+        LoginParams::new(
+            WebsocketAuth::new("key".to_string(), "secret".to_string()),
+            vec![],
+        )
+    }
+
+    #[tokio::test]
+    async fn login_ignores_error_replies() {
+        let server = Server::start().await;
+        server.reply_to(
+            "login",
+            vec![
+                r#"{"channel":"rs.error","data":"unknown method"}"#.to_string(),
+                r#"{"channel":"rs.login","data":"success"}"#.to_string(),
+            ],
+        );
+        let client = MexcFuturesWebsocketClient::new(server.endpoint()).into_arc();
+
+        client.clone().login(login_params()).await.unwrap();
+        assert!(client.inner.lock().await.login.is_some());
+        client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn rejected_login_is_not_repeated() {
+        let server = Server::start().await;
+        server.reply_to(
+            "login",
+            vec![r#"{"channel":"rs.login","data":"signature verification failed"}"#.to_string()],
+        );
+        let client = MexcFuturesWebsocketClient::new(server.endpoint()).into_arc();
+
+        let result = client.clone().login(login_params()).await;
+        assert!(
+            matches!(result, Err(LoginError::Rejected(message)) if message == "signature verification failed")
+        );
+        assert!(client.inner.lock().await.login.is_none());
+        client.shutdown().await;
+    }
+}
@@ -0,0 +1,294 @@
+use crate::futures::ws::message::deal::{channel_message_to_deals_message, DealsMessage};
+use crate::futures::ws::message::depth::{channel_message_to_depth_message, DepthMessage};
+use crate::futures::ws::message::kline::{channel_message_to_kline_message, KlineMessage};
+use crate::futures::ws::message::personal::{
+    PersonalAdlLevelMessage, PersonalAssetMessage, PersonalLiquidateRiskMessage,
+    PersonalOrderMessage, PersonalPositionMessage, PersonalStopOrderMessage,
+    PersonalStopPlanOrderMessage,
+};
+use crate::futures::ws::message::price::{FundingRateMessage, PriceMessage};
+use crate::futures::ws::message::ticker::{TickerMessage, TickersMessage};
+use chrono::{DateTime, Utc};
+
+pub mod deal;
+pub mod depth;
+pub mod kline;
+pub mod personal;
+pub mod price;
+pub mod ticker;
+
+#[derive(Debug)]
+pub enum Message {
+    Tickers(TickersMessage),
+    Ticker(TickerMessage),
+    Deals(DealsMessage),
+    Depth(DepthMessage),
+    DepthFull(DepthMessage),
+    Kline(KlineMessage),
+    FundingRate(FundingRateMessage),
+    IndexPrice(PriceMessage),
+    FairPrice(PriceMessage),
+    PersonalOrder(PersonalOrderMessage),
+    PersonalPosition(PersonalPositionMessage),
+    PersonalAsset(PersonalAssetMessage),
+    PersonalStopOrder(PersonalStopOrderMessage),
+    PersonalStopPlanOrder(PersonalStopPlanOrderMessage),
+    PersonalAdlLevel(PersonalAdlLevelMessage),
+    PersonalLiquidateRisk(PersonalLiquidateRiskMessage),
+    /// Frame that could not be parsed, e.g. because the exchange changed the shape of a channel.
+    Unknown(String),
+}
+
+/// Every frame of the contract websocket has this shape, pushes are sent on `push.*` channels
+/// and replies to requests on `rs.*` channels.
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct RawChannelMessage {
+    pub channel: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+    pub symbol: Option<String>,
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub ts: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChannelMessageError {
+    #[error("Unknown channel: {0}")]
+    UnknownChannel(String),
+
+    #[error("Message has no symbol")]
+    MissingSymbol,
+
+    #[error("Invalid message data: {0}")]
+    InvalidData(#[from] serde_json::Error),
+}
+
+impl TryFrom<RawChannelMessage> for Message {
+    type Error = ChannelMessageError;
+
+    fn try_from(channel_message: RawChannelMessage) -> Result<Self, Self::Error> {
+        let message = match channel_message.channel.as_str() {
+            "push.tickers" => Message::Tickers(TickersMessage {
+                tickers: serde_json::from_value(channel_message.data)?,
+            }),
+            "push.ticker" => Message::Ticker(serde_json::from_value(channel_message.data)?),
+            "push.deal" => Message::Deals(channel_message_to_deals_message(channel_message)?),
+            "push.depth" => Message::Depth(channel_message_to_depth_message(channel_message)?),
+            "push.depth.full" => {
+                Message::DepthFull(channel_message_to_depth_message(channel_message)?)
+            }
+            "push.kline" => Message::Kline(channel_message_to_kline_message(channel_message)?),
+            "push.funding.rate" => {
+                Message::FundingRate(serde_json::from_value(channel_message.data)?)
+            }
+            "push.index.price" => {
+                Message::IndexPrice(serde_json::from_value(channel_message.data)?)
+            }
+            "push.fair.price" => Message::FairPrice(serde_json::from_value(channel_message.data)?),
+            "push.personal.order" => {
+                Message::PersonalOrder(serde_json::from_value(channel_message.data)?)
+            }
+            "push.personal.position" => {
+                Message::PersonalPosition(serde_json::from_value(channel_message.data)?)
+            }
+            "push.personal.asset" => {
+                Message::PersonalAsset(serde_json::from_value(channel_message.data)?)
+            }
+            "push.personal.stop.order" => {
+                Message::PersonalStopOrder(serde_json::from_value(channel_message.data)?)
+            }
+            "push.personal.stop.planorder" => {
+                Message::PersonalStopPlanOrder(serde_json::from_value(channel_message.data)?)
+            }
+            "push.personal.adl.level" => {
+                Message::PersonalAdlLevel(serde_json::from_value(channel_message.data)?)
+            }
+            "push.personal.liquidate.risk" => {
+                Message::PersonalLiquidateRisk(serde_json::from_value(channel_message.data)?)
+            }
+            _ => return Err(ChannelMessageError::UnknownChannel(channel_message.channel)),
+        };
+
+        Ok(message)
+    }
+}
+
+/// Reply to one of our own requests, e.g. on `rs.sub.ticker` or `rs.login`.
+#[derive(Debug, Clone)]
+pub(crate) struct Reply {
+    pub channel: String,
+    pub data: serde_json::Value,
+}
+
+/// Frame received from the websocket after parsing.
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum ParsedFrame {
+    Pong,
+    Reply(Reply),
+    Message(Message),
+    Unknown(String),
+}
+
+pub(crate) fn parse_text_frame(text: String) -> ParsedFrame {
+    let channel_message = match serde_json::from_str::<RawChannelMessage>(&text) {
+        Ok(channel_message) => channel_message,
+        Err(err) => {
+            tracing::debug!("Failed to deserialize message: {}\njson: {}", err, &text);
+            return ParsedFrame::Unknown(text);
+        }
+    };
+
+    if channel_message.channel == "pong" {
+        return ParsedFrame::Pong;
+    }
+    if channel_message.channel.starts_with("rs.") {
+        return ParsedFrame::Reply(Reply {
+            channel: channel_message.channel,
+            data: channel_message.data,
+        });
+    }
+
+    match Message::try_from(channel_message) {
+        Ok(message) => ParsedFrame::Message(message),
+        Err(err) => {
+            tracing::debug!("Received unrecognized message: {}: {}", err, &text);
+            ParsedFrame::Unknown(text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::futures::v1::models::{KlineInterval, OrderSide, OrderState, PositionState};
+    use crate::futures::ws::message::deal::DealSide;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn parse_message(text: &str) -> Message {
+        match parse_text_frame(text.to_string()) {
+            ParsedFrame::Message(message) => message,
+            frame => panic!("Expected a message, got {:?}", frame),
+        }
+    }
+
+    #[test]
+    fn parse_replies_and_pong() {
+        assert!(matches!(
+            parse_text_frame(r#"{"channel":"pong","data":1587453241453}"#.to_string()),
+            ParsedFrame::Pong
+        ));
+        assert!(matches!(
+            parse_text_frame(r#"{"channel":"rs.sub.deal","data":"success","ts":1587442120158}"#.to_string()),
+            ParsedFrame::Reply(reply) if reply.channel == "rs.sub.deal"
+        ));
+        assert!(matches!(
+            parse_text_frame(r#"{"channel":"push.unknown","data":{}}"#.to_string()),
+            ParsedFrame::Unknown(_)
+        ));
+    }
+
+    #[test]
+    fn parse_deal() {
+        let message = parse_message(
+            r#"{"channel":"push.deal","data":{"M":1,"O":1,"T":2,"p":6866.5,"t":1587442049632,"v":2096},"symbol":"BTC_USDT","ts":1587442022003}"#,
+        );
+        let Message::Deals(deals_message) = message else {
+            panic!("Expected deals message");
+        };
+        assert_eq!(deals_message.symbol, "BTC_USDT");
+        assert_eq!(deals_message.deals.len(), 1);
+        assert_eq!(deals_message.deals[0].price, dec("6866.5"));
+        assert_eq!(deals_message.deals[0].side, DealSide::Sell);
+    }
+
+    #[test]
+    fn parse_depth() {
+        let message = parse_message(
+            r#"{"channel":"push.depth","data":{"asks":[[6859.5,3251,1]],"bids":[[6858,0,0]],"version":96801927},"symbol":"BTC_USDT","ts":1587442022003}"#,
+        );
+        let Message::Depth(depth_message) = message else {
+            panic!("Expected depth message");
+        };
+        assert_eq!(depth_message.version, 96801927);
+        assert_eq!(depth_message.asks[0].price, dec("6859.5"));
+        assert_eq!(depth_message.asks[0].volume, dec("3251"));
+        assert_eq!(depth_message.asks[0].order_count, 1);
+        assert_eq!(depth_message.bids[0].volume, dec("0"));
+    }
+
+    #[test]
+    fn parse_kline() {
+        let message = parse_message(
+            r#"{"channel":"push.kline","data":{"a":233.740269343644737245,"c":6885,"h":6910.5,"interval":"Min60","l":6885,"o":6894.5,"q":1611754,"symbol":"BTC_USDT","t":1587448800},"symbol":"BTC_USDT"}"#,
+        );
+        let Message::Kline(kline_message) = message else {
+            panic!("Expected kline message");
+        };
+        assert_eq!(kline_message.interval, KlineInterval::OneHour);
+        assert_eq!(kline_message.kline.time.timestamp(), 1587448800);
+        assert_eq!(kline_message.kline.open, dec("6894.5"));
+        assert_eq!(kline_message.kline.volume, dec("1611754"));
+    }
+
+    #[test]
+    fn parse_ticker_and_prices() {
+        let message = parse_message(
+            r#"{"channel":"push.ticker","data":{"ask1":6866.5,"bid1":6865,"contractId":1,"fairPrice":6867.4,"fundingRate":0.0008,"high24Price":7223.5,"indexPrice":6861.6,"lastPrice":6865.5,"lower24Price":6756,"maxBidPrice":7073.42,"minAskPrice":6661.37,"riseFallRate":-0.0424,"riseFallValue":-304.5,"symbol":"BTC_USDT","timestamp":1587442022003,"holdVol":2284742,"volume24":164586129},"symbol":"BTC_USDT","ts":1587442022003}"#,
+        );
+        let Message::Ticker(ticker_message) = message else {
+            panic!("Expected ticker message");
+        };
+        assert_eq!(ticker_message.last_price, dec("6865.5"));
+        assert_eq!(ticker_message.rise_fall_rate, dec("-0.0424"));
+
+        let message = parse_message(
+            r#"{"channel":"push.fair.price","data":{"price":6867.4,"symbol":"BTC_USDT"},"symbol":"BTC_USDT","ts":1587442022003}"#,
+        );
+        assert!(
+            matches!(message, Message::FairPrice(price_message) if price_message.price == dec("6867.4"))
+        );
+    }
+
+    #[test]
+    fn parse_personal_messages() {
+        let message = parse_message(
+            r#"{"channel":"push.personal.order","data":{"category":1,"createTime":1610005069976,"dealAvgPrice":0.731,"dealVol":1,"errorCode":0,"externalOid":"_m_95bc2b72d3784bce8f9efecbdab2fe7e","feeCurrency":"USDT","leverage":0,"makerFee":0,"openType":1,"orderId":"102067003631907840","orderMargin":0,"orderType":5,"positionId":1397818,"price":0.707,"profit":-0.0005,"side":4,"state":3,"symbol":"CRV_USDT","takerFee":0.00029239999999999997,"updateTime":1610005069983,"usedMargin":0,"version":2,"vol":1},"ts":1610005069989}"#,
+        );
+        let Message::PersonalOrder(order_message) = message else {
+            panic!("Expected personal order message");
+        };
+        assert_eq!(order_message.order_id, 102067003631907840);
+        assert_eq!(order_message.side, OrderSide::CloseLong);
+        assert_eq!(order_message.state, OrderState::Completed);
+
+        let message = parse_message(
+            r#"{"channel":"push.personal.position","data":{"autoAddIm":false,"closeAvgPrice":0.731,"closeVol":1,"frozenVol":0,"holdAvgPrice":0.736,"holdFee":0,"holdVol":0,"im":0,"leverage":15,"liquidatePrice":0,"oim":0,"openAvgPrice":0.736,"openType":1,"positionId":1397818,"positionType":1,"realised":-0.0005,"state":3,"symbol":"CRV_USDT"},"ts":1610005070157}"#,
+        );
+        assert!(matches!(
+            message,
+            Message::PersonalPosition(position_message) if position_message.state == PositionState::Closed
+        ));
+
+        let message = parse_message(
+            r#"{"channel":"push.personal.asset","data":{"availableBalance":0.7514236,"bonus":0,"currency":"USDT","frozenBalance":0,"positionMargin":0},"ts":1610005070083}"#,
+        );
+        assert!(matches!(
+            message,
+            Message::PersonalAsset(asset_message) if asset_message.available_balance == dec("0.7514236")
+        ));
+
+        let message = parse_message(
+            r#"{"channel":"push.personal.adl.level","data":{"adlLevel":3,"positionId":1397818},"ts":1610005032231}"#,
+        );
+        assert!(matches!(
+            message,
+            Message::PersonalAdlLevel(adl_level_message) if adl_level_message.adl_level == 3
+        ));
+    }
+}
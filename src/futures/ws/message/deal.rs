@@ -0,0 +1,59 @@
+use crate::futures::ws::message::{ChannelMessageError, RawChannelMessage};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+#[derive(Debug)]
+pub struct DealsMessage {
+    pub symbol: String,
+    pub deals: Vec<Deal>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct Deal {
+    #[serde(rename = "p")]
+    pub price: Decimal,
+    #[serde(rename = "v")]
+    pub volume: Decimal,
+    #[serde(rename = "T")]
+    pub side: DealSide,
+    #[serde(rename = "t", with = "chrono::serde::ts_milliseconds")]
+    pub time: DateTime<Utc>,
+}
+
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    serde_repr::Deserialize_repr,
+    serde_repr::Serialize_repr,
+    Copy,
+    Clone,
+)]
+#[repr(i8)]
+pub enum DealSide {
+    Buy = 1,
+    Sell = 2,
+}
+
+/// The exchange pushes a single deal, or a batch of them.
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum RawDeals {
+    One(Deal),
+    Many(Vec<Deal>),
+}
+
+pub(crate) fn channel_message_to_deals_message(
+    channel_message: RawChannelMessage,
+) -> Result<DealsMessage, ChannelMessageError> {
+    let symbol = channel_message
+        .symbol
+        .ok_or(ChannelMessageError::MissingSymbol)?;
+    let deals = match serde_json::from_value(channel_message.data)? {
+        RawDeals::One(deal) => vec![deal],
+        RawDeals::Many(deals) => deals,
+    };
+
+    Ok(DealsMessage { symbol, deals })
+}
@@ -0,0 +1,41 @@
+use crate::futures::v1::models::DepthLevel;
+use crate::futures::ws::message::{ChannelMessageError, RawChannelMessage};
+use chrono::{DateTime, Utc};
+
+/// On the `depth` channel these are the changes since the previous version, where a level with
+/// a volume of zero was removed. On the `depth.full` channel these are the top levels of the
+/// order book.
+#[derive(Debug)]
+pub struct DepthMessage {
+    pub symbol: String,
+    pub asks: Vec<DepthLevel>,
+    pub bids: Vec<DepthLevel>,
+    pub version: u64,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct RawDepthData {
+    #[serde(default)]
+    pub asks: Vec<DepthLevel>,
+    #[serde(default)]
+    pub bids: Vec<DepthLevel>,
+    pub version: u64,
+}
+
+pub(crate) fn channel_message_to_depth_message(
+    channel_message: RawChannelMessage,
+) -> Result<DepthMessage, ChannelMessageError> {
+    let symbol = channel_message
+        .symbol
+        .ok_or(ChannelMessageError::MissingSymbol)?;
+    let data: RawDepthData = serde_json::from_value(channel_message.data)?;
+
+    Ok(DepthMessage {
+        symbol,
+        asks: data.asks,
+        bids: data.bids,
+        version: data.version,
+        timestamp: channel_message.ts,
+    })
+}
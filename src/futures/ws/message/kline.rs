@@ -0,0 +1,51 @@
+use crate::futures::v1::models::{Kline, KlineInterval};
+use crate::futures::ws::message::{ChannelMessageError, RawChannelMessage};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+#[derive(Debug)]
+pub struct KlineMessage {
+    pub symbol: String,
+    pub interval: KlineInterval,
+    pub kline: Kline,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(crate) struct RawKlineData {
+    pub symbol: String,
+    pub interval: KlineInterval,
+    #[serde(rename = "t", with = "chrono::serde::ts_seconds")]
+    pub time: DateTime<Utc>,
+    #[serde(rename = "o")]
+    pub open: Decimal,
+    #[serde(rename = "h")]
+    pub high: Decimal,
+    #[serde(rename = "l")]
+    pub low: Decimal,
+    #[serde(rename = "c")]
+    pub close: Decimal,
+    #[serde(rename = "q")]
+    pub volume: Decimal,
+    #[serde(rename = "a")]
+    pub amount: Decimal,
+}
+
+pub(crate) fn channel_message_to_kline_message(
+    channel_message: RawChannelMessage,
+) -> Result<KlineMessage, ChannelMessageError> {
+    let data: RawKlineData = serde_json::from_value(channel_message.data)?;
+
+    Ok(KlineMessage {
+        symbol: data.symbol,
+        interval: data.interval,
+        kline: Kline {
+            time: data.time,
+            open: data.open,
+            high: data.high,
+            low: data.low,
+            close: data.close,
+            volume: data.volume,
+            amount: data.amount,
+        },
+    })
+}
@@ -0,0 +1,213 @@
+use crate::futures::v1::models::{
+    OpenType, OrderCategory, OrderErrorCode, OrderSide, OrderState, OrderType, PositionState,
+    PositionType,
+};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde_with::{serde_as, DisplayFromStr, PickFirst};
+
+/// Change to one of the orders, shaped like [`OpenOrder`](crate::futures::v1::models::OpenOrder).
+#[serde_as]
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalOrderMessage {
+    /// Pushed as a string.
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub order_id: i64,
+    pub symbol: String,
+    pub position_id: i64,
+    pub price: Decimal,
+    #[serde(rename = "vol")]
+    pub volume: Decimal,
+    pub leverage: i32,
+    pub side: OrderSide,
+    pub category: OrderCategory,
+    pub order_type: Option<OrderType>,
+    #[serde(rename = "dealAvgPrice")]
+    pub deal_average_price: Decimal,
+    #[serde(rename = "dealVol")]
+    pub deal_volume: Decimal,
+    pub order_margin: Decimal,
+    pub used_margin: Decimal,
+    pub taker_fee: Decimal,
+    pub maker_fee: Decimal,
+    pub profit: Decimal,
+    pub fee_currency: String,
+    pub open_type: OpenType,
+    pub state: OrderState,
+    pub error_code: OrderErrorCode,
+    #[serde(rename = "externalOid")]
+    pub external_order_id: Option<String>,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub create_time: DateTime<Utc>,
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub update_time: Option<DateTime<Utc>>,
+}
+
+/// Change to one of the positions, shaped like
+/// [`OpenPosition`](crate::futures::v1::models::OpenPosition).
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalPositionMessage {
+    pub position_id: i64,
+    pub symbol: String,
+    #[serde(rename = "holdVol")]
+    pub holding_volume: Decimal,
+    pub position_type: PositionType,
+    pub open_type: OpenType,
+    pub state: PositionState,
+    #[serde(rename = "frozenVol")]
+    pub frozen_volume: Decimal,
+    #[serde(rename = "closeVol")]
+    pub close_volume: Decimal,
+    #[serde(rename = "holdAvgPrice")]
+    pub holdings_average_price: Decimal,
+    #[serde(rename = "closeAvgPrice")]
+    pub close_average_price: Decimal,
+    #[serde(rename = "openAvgPrice")]
+    pub open_average_price: Decimal,
+    pub liquidate_price: Decimal,
+    #[serde(rename = "oim")]
+    pub original_initial_margin: Decimal,
+    #[serde(rename = "im")]
+    pub initial_margin: Decimal,
+    pub hold_fee: Decimal,
+    pub realised: Decimal,
+    pub leverage: Option<i32>,
+    pub adl_level: Option<i8>,
+    #[serde(rename = "autoAddIm")]
+    pub auto_add_initial_margin: Option<bool>,
+}
+
+/// Change to the balance of a currency, shaped like
+/// [`AccountAsset`](crate::futures::v1::models::AccountAsset).
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalAssetMessage {
+    pub currency: String,
+    pub position_margin: Decimal,
+    pub frozen_balance: Decimal,
+    pub available_balance: Decimal,
+    pub cash_balance: Option<Decimal>,
+    pub equity: Option<Decimal>,
+    pub unrealized: Option<Decimal>,
+    pub bonus: Option<Decimal>,
+}
+
+/// Take profit and stop loss prices of a position.
+#[serde_as]
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalStopOrderMessage {
+    pub symbol: String,
+    pub position_id: i64,
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
+    #[serde(default)]
+    pub order_id: Option<i64>,
+    #[serde(rename = "profitPrice")]
+    pub take_profit_price: Option<Decimal>,
+    #[serde(rename = "lossPrice")]
+    pub stop_loss_price: Option<Decimal>,
+}
+
+/// Change to one of the trigger orders.
+#[serde_as]
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalStopPlanOrderMessage {
+    #[serde_as(as = "PickFirst<(_, DisplayFromStr)>")]
+    pub id: i64,
+    pub symbol: String,
+    pub leverage: Option<i32>,
+    pub side: OrderSide,
+    pub trigger_price: Decimal,
+    pub price: Option<Decimal>,
+    #[serde(rename = "vol")]
+    pub volume: Decimal,
+    pub open_type: OpenType,
+    pub trigger_type: TriggerType,
+    pub state: PlanOrderState,
+    pub trend: TriggerPriceType,
+    pub order_type: Option<OrderType>,
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
+    #[serde(default)]
+    pub order_id: Option<i64>,
+    pub error_code: Option<i32>,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub create_time: DateTime<Utc>,
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub update_time: Option<DateTime<Utc>>,
+}
+
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    serde_repr::Deserialize_repr,
+    serde_repr::Serialize_repr,
+    Copy,
+    Clone,
+)]
+#[repr(i8)]
+pub enum TriggerType {
+    GreaterThanOrEqual = 1,
+    LessThanOrEqual = 2,
+}
+
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    serde_repr::Deserialize_repr,
+    serde_repr::Serialize_repr,
+    Copy,
+    Clone,
+)]
+#[repr(i8)]
+pub enum TriggerPriceType {
+    LatestPrice = 1,
+    FairPrice = 2,
+    IndexPrice = 3,
+}
+
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    serde_repr::Deserialize_repr,
+    serde_repr::Serialize_repr,
+    Copy,
+    Clone,
+)]
+#[repr(i8)]
+pub enum PlanOrderState {
+    Untriggered = 1,
+    Cancelled = 2,
+    Executed = 3,
+    Invalid = 4,
+    ExecutionFailed = 5,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalAdlLevelMessage {
+    pub position_id: i64,
+    /// From 1 to 5, the higher the level the sooner the position is reduced.
+    pub adl_level: i8,
+}
+
+/// Warning that a position is close to being liquidated.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersonalLiquidateRiskMessage {
+    pub symbol: String,
+    pub position_id: i64,
+    pub liquidate_price: Decimal,
+    pub margin_ratio: Decimal,
+    pub position_type: Option<PositionType>,
+    pub open_type: Option<OpenType>,
+    pub adl_level: Option<i8>,
+}
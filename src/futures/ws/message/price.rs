@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// Index or fair price of a contract.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceMessage {
+    pub symbol: String,
+    pub price: Decimal,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRateMessage {
+    pub symbol: String,
+    pub rate: Decimal,
+    #[serde(default, with = "chrono::serde::ts_milliseconds_option")]
+    pub next_settle_time: Option<DateTime<Utc>>,
+}
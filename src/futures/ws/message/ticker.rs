@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+#[derive(Debug)]
+pub struct TickersMessage {
+    pub tickers: Vec<TickersItem>,
+}
+
+/// Short ticker of a single contract, as pushed on the `tickers` channel.
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TickersItem {
+    pub symbol: String,
+    pub last_price: Decimal,
+    pub fair_price: Decimal,
+    pub rise_fall_rate: Decimal,
+    #[serde(rename = "volume24")]
+    pub volume_24h: Decimal,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TickerMessage {
+    pub symbol: String,
+    pub last_price: Decimal,
+    #[serde(rename = "ask1")]
+    pub best_ask_price: Decimal,
+    #[serde(rename = "bid1")]
+    pub best_bid_price: Decimal,
+    pub fair_price: Decimal,
+    pub index_price: Decimal,
+    pub funding_rate: Decimal,
+    #[serde(rename = "high24Price")]
+    pub high_24h_price: Decimal,
+    #[serde(rename = "lower24Price")]
+    pub low_24h_price: Decimal,
+    #[serde(rename = "volume24")]
+    pub volume_24h: Decimal,
+    #[serde(rename = "amount24")]
+    pub amount_24h: Option<Decimal>,
+    pub rise_fall_rate: Decimal,
+    pub rise_fall_value: Decimal,
+    #[serde(rename = "holdVol")]
+    pub holding_volume: Decimal,
+    pub max_bid_price: Option<Decimal>,
+    pub min_ask_price: Option<Decimal>,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub timestamp: DateTime<Utc>,
+}
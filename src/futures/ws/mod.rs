@@ -1 +1,159 @@
+use crate::futures::ws::auth::WebsocketLogin;
+use crate::futures::ws::endpoint::MexcFuturesWebsocketEndpoint;
+use crate::futures::ws::topic::Topic;
+use crate::reconnect_policy::ReconnectPolicy;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
+pub mod auth;
+mod connection;
+pub mod endpoint;
+pub mod local_order_book;
+pub mod login;
+pub mod message;
+pub mod stream;
+pub mod subscribe;
+#[cfg(test)]
+mod test_server;
+pub mod topic;
+pub mod unsubscribe;
+
+pub use connection::ConnectError;
+
+#[derive(Debug)]
+struct Inner {
+    /// Topics that are subscribed to, they are subscribed to again after reconnecting.
+    pub topics: Vec<Topic>,
+    /// Sent again after reconnecting.
+    pub login: Option<WebsocketLogin>,
+    /// All topics share a single connection, which is only opened once subscribing or logging
+    /// in.
+    pub connection: Option<connection::Connection>,
+}
+
+/// Client for the push api of the contract exchange.
+#[derive(Debug, Clone)]
+pub struct MexcFuturesWebsocketClient {
+    inner: Arc<Mutex<Inner>>,
+    endpoint: Arc<MexcFuturesWebsocketEndpoint>,
+    broadcast_tx: tokio::sync::broadcast::Sender<Arc<message::Message>>,
+    reply_tx: tokio::sync::broadcast::Sender<(Uuid, Arc<message::Reply>)>,
+    reconnect_policy: Arc<ReconnectPolicy>,
+    ping_interval: Duration,
+    /// Parent of the cancellation tokens of all connections.
+    shutdown_token: CancellationToken,
+    tasks: Arc<std::sync::Mutex<Vec<tokio::task::JoinHandle<()>>>>,
+}
+
+impl MexcFuturesWebsocketClient {
+    pub fn new(endpoint: MexcFuturesWebsocketEndpoint) -> Self {
+        let (broadcast_tx, _broadcast_rx) = tokio::sync::broadcast::channel(1024);
+        let (reply_tx, _reply_rx) = tokio::sync::broadcast::channel(1024);
+
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                topics: Vec::new(),
+                login: None,
+                connection: None,
+            })),
+            endpoint: Arc::new(endpoint),
+            broadcast_tx,
+            reply_tx,
+            reconnect_policy: Arc::new(ReconnectPolicy::default()),
+            // The server drops connections that did not ping for a minute.
+            ping_interval: Duration::from_secs(15),
+            shutdown_token: CancellationToken::new(),
+            tasks: Arc::new(std::sync::Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn with_broadcast_capacity(mut self, broadcast_capacity: usize) -> Self {
+        let (broadcast_tx, _broadcast_rx) = tokio::sync::broadcast::channel(broadcast_capacity);
+        self.broadcast_tx = broadcast_tx;
+        self
+    }
+
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = Arc::new(reconnect_policy);
+        self
+    }
+
+    pub fn with_ping_interval(mut self, ping_interval: Duration) -> Self {
+        self.ping_interval = ping_interval;
+        self
+    }
+
+    pub fn into_arc(self) -> Arc<Self> {
+        Arc::new(self)
+    }
+
+    /// Topics that are currently subscribed to.
+    pub async fn topics(&self) -> Vec<Topic> {
+        self.inner.lock().await.topics.clone()
+    }
+
+    /// Closes the connection and returns once all of its tasks have exited, the client can not
+    /// be used afterwards.
+    pub async fn shutdown(&self) {
+        self.shutdown_token.cancel();
+        {
+            let mut inner = self.inner.lock().await;
+            inner.topics.clear();
+            inner.login = None;
+            inner.connection = None;
+        }
+
+        // Tasks that are exiting might spawn new ones, e.g. while reconnecting.
+        loop {
+            let tasks = std::mem::take(&mut *self.tasks.lock().expect("Tasks lock poisoned"));
+            if tasks.is_empty() {
+                break;
+            }
+            for task in tasks {
+                if let Err(err) = task.await {
+                    tracing::error!("Websocket task failed: {}", err);
+                }
+            }
+        }
+    }
+
+    /// Spawns a task that is awaited on shutdown.
+    pub(crate) fn spawn_task<F>(&self, future: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(future);
+        let mut tasks = self.tasks.lock().expect("Tasks lock poisoned");
+        tasks.retain(|task| !task.is_finished());
+        tasks.push(handle);
+    }
+}
+
+impl Default for MexcFuturesWebsocketClient {
+    fn default() -> Self {
+        Self::new(MexcFuturesWebsocketEndpoint::Base)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SendableMessage {
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub param: Option<serde_json::Value>,
+}
+
+impl SendableMessage {
+    pub fn new(method: impl Into<String>, param: Option<serde_json::Value>) -> Self {
+        Self {
+            method: method.into(),
+            param,
+        }
+    }
+
+    pub fn ping() -> Self {
+        Self::new("ping", None)
+    }
+}
@@ -0,0 +1,29 @@
+use crate::futures::ws::message::Message;
+use crate::futures::ws::MexcFuturesWebsocketClient;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+pub trait Stream {
+    /// Stream of all messages, messages missed because the consumer fell behind are skipped.
+    fn stream<'a>(self: Arc<Self>) -> BoxStream<'a, Arc<Message>>;
+}
+
+impl Stream for MexcFuturesWebsocketClient {
+    fn stream<'a>(self: Arc<Self>) -> BoxStream<'a, Arc<Message>> {
+        let mut rx = self.broadcast_tx.subscribe();
+        let stream = async_stream::stream! {
+            loop {
+                match rx.recv().await {
+                    Ok(message) => yield message,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Stream lagged behind, skipped {} messages", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        };
+        stream.boxed()
+    }
+}
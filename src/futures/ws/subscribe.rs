@@ -0,0 +1,79 @@
+use crate::futures::ws::connection::{lock_connected, ConnectError};
+use crate::futures::ws::topic::Topic;
+use crate::futures::ws::{MexcFuturesWebsocketClient, SendableMessage};
+use async_channel::SendError;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+pub struct SubscribeParams {
+    pub topics: Vec<Topic>,
+}
+
+impl SubscribeParams {
+    pub fn new(topics: Vec<Topic>) -> Self {
+        Self { topics }
+    }
+
+    pub fn with_topic(mut self, topic: Topic) -> Self {
+        self.topics.push(topic);
+        self
+    }
+
+    pub fn with_topics(mut self, topics: Vec<Topic>) -> Self {
+        self.topics.extend(topics);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SubscribeOutput {}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SubscribeError {
+    #[error("Failed to connect: {0}")]
+    ConnectError(#[from] ConnectError),
+
+    #[error("Failed to send message through channel: {0}")]
+    SendError(#[from] SendError<SendableMessage>),
+}
+
+#[async_trait]
+pub trait Subscribe {
+    /// Subscribes to the topics, connecting first when there is no connection yet. Topics that
+    /// are already subscribed to are left out. While the connection is down the topics are
+    /// subscribed to once it is reestablished.
+    async fn subscribe(
+        self: Arc<Self>,
+        params: SubscribeParams,
+    ) -> Result<SubscribeOutput, SubscribeError>;
+}
+
+#[async_trait]
+impl Subscribe for MexcFuturesWebsocketClient {
+    async fn subscribe(
+        self: Arc<Self>,
+        params: SubscribeParams,
+    ) -> Result<SubscribeOutput, SubscribeError> {
+        let mut inner = lock_connected(&self).await?;
+        let inner = &mut *inner;
+        let connection = inner.connection.as_ref().expect("Connected above");
+
+        for topic in params.topics {
+            if inner.topics.contains(&topic) {
+                continue;
+            }
+            let message = topic.to_subscription_message();
+            inner.topics.push(topic);
+            if let Err(err) = connection.send(message).await {
+                if connection.is_connected() {
+                    inner.topics.pop();
+                    return Err(err.into());
+                }
+                // The reconnect subscribes to the topic.
+            }
+        }
+
+        Ok(SubscribeOutput {})
+    }
+}
@@ -0,0 +1,102 @@
+use crate::futures::ws::endpoint::MexcFuturesWebsocketEndpoint;
+use futures::{SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite;
+use tokio_util::sync::CancellationToken;
+
+/// Server that reports the methods it receives per connection, answers them with the replies
+/// set up for the method and closes a connection when its token is cancelled.
+pub(crate) struct Server {
+    url: String,
+    methods_rx: mpsc::UnboundedReceiver<(usize, String)>,
+    connection_tokens: Arc<std::sync::Mutex<Vec<CancellationToken>>>,
+    replies: Arc<std::sync::Mutex<HashMap<String, Vec<String>>>>,
+}
+
+impl Server {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (methods_tx, methods_rx) = mpsc::unbounded_channel();
+        let connection_tokens = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let tokens = connection_tokens.clone();
+        let replies = Arc::new(std::sync::Mutex::new(HashMap::<String, Vec<String>>::new()));
+        let method_replies = replies.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let token = CancellationToken::new();
+                let id = {
+                    let mut tokens = tokens.lock().unwrap();
+                    tokens.push(token.clone());
+                    tokens.len() - 1
+                };
+                let methods_tx = methods_tx.clone();
+                let replies = method_replies.clone();
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    loop {
+                        tokio::select! {
+                            _ = token.cancelled() => break,
+                            message = ws.next() => match message {
+                                Some(Ok(tungstenite::Message::Text(text))) => {
+                                    let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+                                    let method = value["method"].as_str().unwrap().to_string();
+                                    let frames = replies.lock().unwrap().get(&method).cloned().unwrap_or_default();
+                                    for frame in frames {
+                                        let _ = ws.send(tungstenite::Message::Text(frame)).await;
+                                    }
+                                    let _ = methods_tx.send((id, method));
+                                }
+                                Some(Ok(_)) => {}
+                                _ => break,
+                            },
+                        }
+                    }
+                });
+            }
+        });
+        Self {
+            url,
+            methods_rx,
+            connection_tokens,
+            replies,
+        }
+    }
+
+    /// Sends the frames whenever the method is received.
+    pub fn reply_to(&self, method: &str, frames: Vec<String>) {
+        self.replies
+            .lock()
+            .unwrap()
+            .insert(method.to_string(), frames);
+    }
+
+    pub fn endpoint(&self) -> MexcFuturesWebsocketEndpoint {
+        MexcFuturesWebsocketEndpoint::Custom(self.url.clone())
+    }
+
+    pub fn drop_connection(&self, id: usize) {
+        self.connection_tokens.lock().unwrap()[id].cancel();
+    }
+
+    /// Waits for the subscriptions on the connection, ignoring pings.
+    pub async fn subscriptions(&mut self, id: usize, count: usize) -> Vec<String> {
+        let mut methods = Vec::new();
+        while methods.len() < count {
+            let (connection_id, method) =
+                tokio::time::timeout(Duration::from_secs(5), self.methods_rx.recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+            if connection_id == id && method.starts_with("sub.") {
+                methods.push(method);
+            }
+        }
+        methods.sort();
+        methods
+    }
+}
@@ -0,0 +1,218 @@
+use crate::futures::v1::models::KlineInterval;
+use crate::futures::ws::SendableMessage;
+use serde_json::json;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum Topic {
+    /// Tickers of all contracts.
+    Tickers,
+    Ticker(TickerTopic),
+    Deal(DealTopic),
+    Depth(DepthTopic),
+    DepthFull(DepthFullTopic),
+    Kline(KlineTopic),
+    FundingRate(FundingRateTopic),
+    IndexPrice(IndexPriceTopic),
+    FairPrice(FairPriceTopic),
+}
+
+impl Topic {
+    /// Name of the channel, which is prefixed with `sub.` and `unsub.` for requests and with
+    /// `push.` for the messages.
+    pub fn channel_name(&self) -> &'static str {
+        match self {
+            Topic::Tickers => "tickers",
+            Topic::Ticker(_) => "ticker",
+            Topic::Deal(_) => "deal",
+            Topic::Depth(_) => "depth",
+            Topic::DepthFull(_) => "depth.full",
+            Topic::Kline(_) => "kline",
+            Topic::FundingRate(_) => "funding.rate",
+            Topic::IndexPrice(_) => "index.price",
+            Topic::FairPrice(_) => "fair.price",
+        }
+    }
+
+    pub fn symbol(&self) -> Option<&str> {
+        match self {
+            Topic::Tickers => None,
+            Topic::Ticker(topic) => Some(&topic.symbol),
+            Topic::Deal(topic) => Some(&topic.symbol),
+            Topic::Depth(topic) => Some(&topic.symbol),
+            Topic::DepthFull(topic) => Some(&topic.symbol),
+            Topic::Kline(topic) => Some(&topic.symbol),
+            Topic::FundingRate(topic) => Some(&topic.symbol),
+            Topic::IndexPrice(topic) => Some(&topic.symbol),
+            Topic::FairPrice(topic) => Some(&topic.symbol),
+        }
+    }
+
+    fn param(&self) -> serde_json::Value {
+        match self {
+            Topic::Tickers => json!({}),
+            Topic::DepthFull(topic) => json!({
+                "symbol": topic.symbol,
+                "limit": topic.levels.to_api_value(),
+            }),
+            Topic::Kline(topic) => json!({
+                "symbol": topic.symbol,
+                "interval": topic.interval,
+            }),
+            _ => json!({ "symbol": self.symbol() }),
+        }
+    }
+
+    pub fn to_subscription_message(&self) -> SendableMessage {
+        SendableMessage::new(format!("sub.{}", self.channel_name()), Some(self.param()))
+    }
+
+    pub fn to_unsubscription_message(&self) -> SendableMessage {
+        SendableMessage::new(format!("unsub.{}", self.channel_name()), Some(self.param()))
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct TickerTopic {
+    pub symbol: String,
+}
+
+impl TickerTopic {
+    pub fn new(symbol: String) -> Self {
+        Self { symbol }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DealTopic {
+    pub symbol: String,
+}
+
+impl DealTopic {
+    pub fn new(symbol: String) -> Self {
+        Self { symbol }
+    }
+}
+
+/// Every change of the order book, with a version per change.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DepthTopic {
+    pub symbol: String,
+}
+
+impl DepthTopic {
+    pub fn new(symbol: String) -> Self {
+        Self { symbol }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum DepthFullLevels {
+    Levels5,
+    Levels10,
+    Levels20,
+}
+
+impl DepthFullLevels {
+    pub fn to_api_value(&self) -> u32 {
+        match self {
+            DepthFullLevels::Levels5 => 5,
+            DepthFullLevels::Levels10 => 10,
+            DepthFullLevels::Levels20 => 20,
+        }
+    }
+}
+
+/// Top levels of the order book.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DepthFullTopic {
+    pub symbol: String,
+    pub levels: DepthFullLevels,
+}
+
+impl DepthFullTopic {
+    pub fn new(symbol: String, levels: DepthFullLevels) -> Self {
+        Self { symbol, levels }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct KlineTopic {
+    pub symbol: String,
+    pub interval: KlineInterval,
+}
+
+impl KlineTopic {
+    pub fn new(symbol: String, interval: KlineInterval) -> Self {
+        Self { symbol, interval }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct FundingRateTopic {
+    pub symbol: String,
+}
+
+impl FundingRateTopic {
+    pub fn new(symbol: String) -> Self {
+        Self { symbol }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct IndexPriceTopic {
+    pub symbol: String,
+}
+
+impl IndexPriceTopic {
+    pub fn new(symbol: String) -> Self {
+        Self { symbol }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct FairPriceTopic {
+    pub symbol: String,
+}
+
+impl FairPriceTopic {
+    pub fn new(symbol: String) -> Self {
+        Self { symbol }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscription_messages() {
+        let topic = Topic::Kline(KlineTopic::new(
+            "BTC_USDT".to_string(),
+            KlineInterval::OneMinute,
+        ));
+        assert_eq!(
+            serde_json::to_string(&topic.to_subscription_message()).unwrap(),
+            r#"{"method":"sub.kline","param":{"interval":"Min1","symbol":"BTC_USDT"}}"#
+        );
+
+        let topic = Topic::FundingRate(FundingRateTopic::new("BTC_USDT".to_string()));
+        assert_eq!(
+            serde_json::to_string(&topic.to_unsubscription_message()).unwrap(),
+            r#"{"method":"unsub.funding.rate","param":{"symbol":"BTC_USDT"}}"#
+        );
+
+        let topic = Topic::DepthFull(DepthFullTopic::new(
+            "BTC_USDT".to_string(),
+            DepthFullLevels::Levels20,
+        ));
+        assert_eq!(
+            serde_json::to_string(&topic.to_subscription_message()).unwrap(),
+            r#"{"method":"sub.depth.full","param":{"limit":20,"symbol":"BTC_USDT"}}"#
+        );
+
+        assert_eq!(
+            serde_json::to_string(&Topic::Tickers.to_subscription_message()).unwrap(),
+            r#"{"method":"sub.tickers","param":{}}"#
+        );
+    }
+}
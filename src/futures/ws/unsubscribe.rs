@@ -0,0 +1,76 @@
+use crate::futures::ws::topic::Topic;
+use crate::futures::ws::{MexcFuturesWebsocketClient, SendableMessage};
+use async_channel::SendError;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+pub struct UnsubscribeParams {
+    pub topics: Vec<Topic>,
+}
+
+impl UnsubscribeParams {
+    pub fn new(topics: Vec<Topic>) -> Self {
+        Self { topics }
+    }
+
+    pub fn with_topic(mut self, topic: Topic) -> Self {
+        self.topics.push(topic);
+        self
+    }
+
+    pub fn with_topics(mut self, topics: Vec<Topic>) -> Self {
+        self.topics.extend(topics);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnsubscribeOutput {}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UnsubscribeError {
+    #[error("Failed to send message through channel: {0}")]
+    SendError(#[from] SendError<SendableMessage>),
+}
+
+#[async_trait]
+pub trait Unsubscribe {
+    /// Unsubscribes from the topics, the connection is closed once no topics are left and the
+    /// client is not logged in.
+    async fn unsubscribe(
+        self: Arc<Self>,
+        params: UnsubscribeParams,
+    ) -> Result<UnsubscribeOutput, UnsubscribeError>;
+}
+
+#[async_trait]
+impl Unsubscribe for MexcFuturesWebsocketClient {
+    async fn unsubscribe(
+        self: Arc<Self>,
+        params: UnsubscribeParams,
+    ) -> Result<UnsubscribeOutput, UnsubscribeError> {
+        let mut inner = self.inner.lock().await;
+        for topic in params.topics {
+            let Some(index) = inner.topics.iter().position(|t| t == &topic) else {
+                continue;
+            };
+            inner.topics.remove(index);
+            if let Some(connection) = &inner.connection {
+                if let Err(err) = connection.send(topic.to_unsubscription_message()).await {
+                    if connection.is_connected() {
+                        return Err(err.into());
+                    }
+                    // The reconnect leaves the topic out.
+                }
+            }
+        }
+
+        if inner.topics.is_empty() && inner.login.is_none() {
+            // Dropping the connection closes it.
+            inner.connection = None;
+        }
+
+        Ok(UnsubscribeOutput {})
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+
+/// Item of a kline history stream.
+#[derive(Debug, Clone)]
+pub enum KlineHistoryItem<K> {
+    Kline(K),
+    /// Candles are missing before the next kline or up to the end of the range, for instance
+    /// because trading was halted.
+    Gap(KlineGap),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KlineGap {
+    /// Open time of the first missing candle.
+    pub from: DateTime<Utc>,
+    /// Open time of the next candle that is present, or the end of the range when the candles
+    /// are missing up to there.
+    pub to: DateTime<Utc>,
+}
+
+/// Position of a kline history stream within its `[start, end)` range.
+///
+/// Pages can overlap at their boundaries, candles that open before the next expected open time
+/// are dropped. Missing candles at the start and the end of the range are reported as gaps as
+/// well.
+#[derive(Debug)]
+pub(crate) struct KlineHistoryCursor {
+    start: DateTime<Utc>,
+    next_start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    /// Open time of the candle after the last returned one, `None` before the first one.
+    expected_open_time: Option<DateTime<Utc>>,
+}
+
+impl KlineHistoryCursor {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            next_start: start,
+            end,
+            expected_open_time: None,
+        }
+    }
+
+    /// Start and exclusive end of the next page, `None` when the whole range was covered.
+    pub fn next_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if self.next_start >= self.end {
+            return None;
+        }
+        Some((self.next_start, self.end))
+    }
+
+    /// Moves past a page of klines, sorted by open time, that was requested up to `page_end` and
+    /// returns the new klines and the gaps between them. After a page without new klines the
+    /// next page starts at `page_end`.
+    pub fn advance<K>(
+        &mut self,
+        klines: Vec<K>,
+        page_end: DateTime<Utc>,
+        open_time: impl Fn(&K) -> DateTime<Utc>,
+        next_open_time: impl Fn(DateTime<Utc>) -> DateTime<Utc>,
+    ) -> Vec<KlineHistoryItem<K>> {
+        let mut items = Vec::with_capacity(klines.len());
+        for kline in klines {
+            let time = open_time(&kline);
+            if time < self.next_start || time >= self.end {
+                continue;
+            }
+            let expected = match self.expected_open_time {
+                Some(expected) => expected,
+                // The start of the range might not be on a candle boundary, only a whole candle
+                // missing counts as a gap.
+                None if time >= next_open_time(self.start) => self.start,
+                None => time,
+            };
+            if time > expected {
+                items.push(KlineHistoryItem::Gap(KlineGap {
+                    from: expected,
+                    to: time,
+                }));
+            }
+            self.expected_open_time = Some(next_open_time(time));
+            self.next_start = next_open_time(time);
+            items.push(KlineHistoryItem::Kline(kline));
+        }
+
+        if items.is_empty() {
+            self.next_start = self.next_start.max(page_end);
+        }
+
+        if self.next_start >= self.end {
+            let from = self.expected_open_time.unwrap_or(self.start);
+            if from < self.end {
+                items.push(KlineHistoryItem::Gap(KlineGap { from, to: self.end }));
+                self.expected_open_time = Some(self.end);
+            }
+        }
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn cursor_drops_duplicates_and_reports_gaps() {
+        let minute = |minute: i64| Utc.timestamp_opt(minute * 60, 0).unwrap();
+        let next_open_time = |time: DateTime<Utc>| time + Duration::minutes(1);
+        let mut cursor = KlineHistoryCursor::new(minute(0), minute(10));
+
+        let items = cursor.advance(
+            vec![minute(0), minute(1), minute(2)],
+            minute(10),
+            |t| *t,
+            next_open_time,
+        );
+        assert_eq!(items.len(), 3);
+        assert_eq!(cursor.next_range(), Some((minute(3), minute(10))));
+
+        // The boundary candle is returned again and minute 4 and 5 are missing.
+        let items = cursor.advance(
+            vec![minute(2), minute(3), minute(6), minute(10)],
+            minute(10),
+            |t| *t,
+            next_open_time,
+        );
+        assert!(matches!(items[0], KlineHistoryItem::Kline(time) if time == minute(3)));
+        assert!(matches!(
+            items[1],
+            KlineHistoryItem::Gap(KlineGap { from, to }) if from == minute(4) && to == minute(6)
+        ));
+        assert!(matches!(items[2], KlineHistoryItem::Kline(time) if time == minute(6)));
+        assert_eq!(items.len(), 3);
+
+        // Nothing new up to minute 8, the next page starts there.
+        assert!(cursor
+            .advance(vec![minute(6)], minute(8), |t| *t, next_open_time)
+            .is_empty());
+        assert_eq!(cursor.next_range(), Some((minute(8), minute(10))));
+
+        // The range ends without minute 7 to 9.
+        let items = cursor.advance(Vec::new(), minute(10), |t| *t, next_open_time);
+        assert!(matches!(
+            items[..],
+            [KlineHistoryItem::Gap(KlineGap { from, to })] if from == minute(7) && to == minute(10)
+        ));
+        assert_eq!(cursor.next_range(), None);
+
+        // The range starts without minute 0 and 1.
+        let mut cursor = KlineHistoryCursor::new(minute(0), minute(3));
+        let items = cursor.advance(vec![minute(2)], minute(3), |t| *t, next_open_time);
+        assert!(matches!(
+            items[0],
+            KlineHistoryItem::Gap(KlineGap { from, to }) if from == minute(0) && to == minute(2)
+        ));
+        assert!(matches!(items[1], KlineHistoryItem::Kline(time) if time == minute(2)));
+        assert_eq!(items.len(), 2);
+        assert_eq!(cursor.next_range(), None);
+
+        // A start within a candle is not a gap.
+        let mut cursor = KlineHistoryCursor::new(minute(0) + Duration::seconds(30), minute(3));
+        let items = cursor.advance(vec![minute(1)], minute(2), |t| *t, next_open_time);
+        assert!(matches!(items[..], [KlineHistoryItem::Kline(time)] if time == minute(1)));
+    }
+}
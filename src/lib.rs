@@ -4,6 +4,19 @@ pub mod spot;
 #[cfg(feature = "futures")]
 pub mod futures;
 
+/// Shared by the spot and futures kline endpoints.
+#[cfg(any(feature = "spot", feature = "futures"))]
+pub mod kline_history;
+
+/// Shared by the spot and futures local order books.
+#[cfg(all(feature = "ws", any(feature = "spot", feature = "futures")))]
+pub mod order_book;
+
+/// Shared by the spot and futures websocket clients.
+#[cfg(feature = "ws")]
+pub mod reconnect_policy;
+
+#[allow(clippy::large_enum_variant)]
 pub mod proto {
     tonic::include_proto!("_");
 }
@@ -0,0 +1,640 @@
+// Gap recovery from missed diffs is only used by the futures books, resetting only by the spot
+// books.
+#![cfg_attr(not(all(feature = "spot", feature = "futures")), allow(dead_code))]
+
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_util::sync::CancellationToken;
+
+/// Amount of diffs kept around per symbol while waiting for a snapshot or missed diffs.
+const MAX_BUFFERED_DIFFS: usize = 1000;
+
+/// Delay before fetching a snapshot or missed diffs again after the request failed.
+const FETCH_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Price level of an order book side.
+pub trait OrderBookLevel: Clone {
+    fn price(&self) -> Decimal;
+
+    /// Quantity at the price, a quantity of zero removes the level.
+    fn quantity(&self) -> Decimal;
+}
+
+/// L2 order book of a single symbol.
+#[derive(Debug, Clone)]
+pub struct OrderBook<L> {
+    symbol: String,
+    version: u64,
+    bids: BTreeMap<Decimal, L>,
+    asks: BTreeMap<Decimal, L>,
+}
+
+impl<L: OrderBookLevel> OrderBook<L> {
+    pub(crate) fn new(symbol: String, version: u64, bids: &[L], asks: &[L]) -> Self {
+        let levels = |levels: &[L]| {
+            levels
+                .iter()
+                .filter(|level| !level.quantity().is_zero())
+                .map(|level| (level.price(), level.clone()))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        Self {
+            symbol,
+            version,
+            bids: levels(bids),
+            asks: levels(asks),
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Version of the last applied update.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn best_bid(&self) -> Option<L> {
+        self.bids().next()
+    }
+
+    pub fn best_ask(&self) -> Option<L> {
+        self.asks().next()
+    }
+
+    /// Bids ordered from the highest to the lowest price.
+    pub fn bids(&self) -> impl Iterator<Item = L> + '_ {
+        self.bids.values().rev().cloned()
+    }
+
+    /// Asks ordered from the lowest to the highest price.
+    pub fn asks(&self) -> impl Iterator<Item = L> + '_ {
+        self.asks.values().cloned()
+    }
+
+    /// Returns whether the diff changed the book, diffs that are already included are skipped.
+    pub(crate) fn apply(&mut self, diff: &DepthDiff<L>) -> Result<bool, VersionGap> {
+        if diff.to_version <= self.version {
+            return Ok(false);
+        }
+        if diff.from_version > self.version + 1 {
+            return Err(VersionGap {
+                expected: self.version + 1,
+                received: diff.from_version,
+            });
+        }
+
+        // Quantities are absolute, a quantity of zero removes the level.
+        let apply_levels = |book: &mut BTreeMap<Decimal, L>, levels: &[L]| {
+            for level in levels {
+                if level.quantity().is_zero() {
+                    book.remove(&level.price());
+                } else {
+                    book.insert(level.price(), level.clone());
+                }
+            }
+        };
+        apply_levels(&mut self.bids, &diff.bids);
+        apply_levels(&mut self.asks, &diff.asks);
+        self.version = diff.to_version;
+
+        Ok(true)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct VersionGap {
+    pub expected: u64,
+    pub received: u64,
+}
+
+/// Depth changes covering the versions `from_version..=to_version`.
+#[derive(Debug, Clone)]
+pub(crate) struct DepthDiff<L> {
+    pub from_version: u64,
+    pub to_version: u64,
+    pub asks: Vec<L>,
+    pub bids: Vec<L>,
+}
+
+/// How a synced book recovers from a version gap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GapRecovery {
+    /// Resync from a new snapshot.
+    Snapshot,
+    /// Fill in the missed diffs, resyncing from a new snapshot when they don't cover the gap.
+    MissedDiffs,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum SyncAction {
+    None,
+    Publish,
+    FetchMissedDiffs,
+    FetchSnapshot,
+}
+
+#[derive(Debug)]
+enum SyncState<L> {
+    Syncing {
+        buffer: VecDeque<DepthDiff<L>>,
+    },
+    /// The book missed versions, which are fetched as diffs.
+    Repairing {
+        book: OrderBook<L>,
+        buffer: VecDeque<DepthDiff<L>>,
+    },
+    Synced(OrderBook<L>),
+}
+
+/// Brings the book of a symbol in sync by applying the diffs in version order on top of a
+/// snapshot, buffering them while the snapshot or the missed diffs are being fetched.
+#[derive(Debug)]
+pub(crate) struct SymbolSync<L> {
+    gap_recovery: GapRecovery,
+    state: SyncState<L>,
+}
+
+impl<L: OrderBookLevel> SymbolSync<L> {
+    pub fn new(gap_recovery: GapRecovery) -> Self {
+        Self {
+            gap_recovery,
+            state: SyncState::Syncing {
+                buffer: VecDeque::new(),
+            },
+        }
+    }
+
+    pub fn book(&self) -> Option<&OrderBook<L>> {
+        match &self.state {
+            SyncState::Synced(book) => Some(book),
+            SyncState::Syncing { .. } | SyncState::Repairing { .. } => None,
+        }
+    }
+
+    pub fn on_diff(&mut self, diff: DepthDiff<L>) -> SyncAction {
+        match &mut self.state {
+            SyncState::Syncing { buffer } | SyncState::Repairing { buffer, .. } => {
+                if buffer.len() >= MAX_BUFFERED_DIFFS {
+                    buffer.pop_front();
+                }
+                buffer.push_back(diff);
+                SyncAction::None
+            }
+            SyncState::Synced(book) => match book.apply(&diff) {
+                Ok(true) => SyncAction::Publish,
+                Ok(false) => SyncAction::None,
+                Err(gap) => {
+                    tracing::warn!(
+                        "Version gap in order book of {}, expected {} but received {}, recovering from {:?}",
+                        book.symbol,
+                        gap.expected,
+                        gap.received,
+                        self.gap_recovery
+                    );
+                    let buffer = VecDeque::from([diff]);
+                    match self.gap_recovery {
+                        GapRecovery::Snapshot => {
+                            self.state = SyncState::Syncing { buffer };
+                            SyncAction::FetchSnapshot
+                        }
+                        GapRecovery::MissedDiffs => {
+                            self.state = SyncState::Repairing {
+                                book: book.clone(),
+                                buffer,
+                            };
+                            SyncAction::FetchMissedDiffs
+                        }
+                    }
+                }
+            },
+        }
+    }
+
+    pub fn on_snapshot(&mut self, mut book: OrderBook<L>) -> SyncAction {
+        let SyncState::Syncing { buffer } = &mut self.state else {
+            return SyncAction::None;
+        };
+
+        match apply_buffer(&mut book, buffer) {
+            Ok(()) => {
+                self.state = SyncState::Synced(book);
+                SyncAction::Publish
+            }
+            Err(gap) => {
+                // Either the snapshot is older than the buffered diffs, or diffs went missing in
+                // between. The buffered diffs from the gap on are still usable later on.
+                tracing::debug!(
+                    "Snapshot of {} does not line up with buffered diffs, expected {} but received {}",
+                    book.symbol,
+                    gap.expected,
+                    gap.received
+                );
+                match self.gap_recovery {
+                    GapRecovery::Snapshot => SyncAction::FetchSnapshot,
+                    GapRecovery::MissedDiffs => {
+                        let buffer = std::mem::take(buffer);
+                        self.state = SyncState::Repairing { book, buffer };
+                        SyncAction::FetchMissedDiffs
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fills in the gap of a book that is being repaired, the diffs are in version order.
+    pub fn on_missed_diffs(&mut self, diffs: &[DepthDiff<L>]) -> SyncAction {
+        let SyncState::Repairing { book, buffer } = &mut self.state else {
+            return SyncAction::None;
+        };
+
+        let mut book = book.clone();
+        let mut buffer = std::mem::take(buffer);
+        let result = diffs
+            .iter()
+            .try_for_each(|diff| book.apply(diff).map(|_| ()))
+            .and_then(|_| apply_buffer(&mut book, &mut buffer));
+        match result {
+            Ok(()) => {
+                self.state = SyncState::Synced(book);
+                SyncAction::Publish
+            }
+            Err(gap) => {
+                tracing::warn!(
+                    "Missed diffs of {} do not cover the gap, expected {} but received {}, fetching a snapshot",
+                    book.symbol,
+                    gap.expected,
+                    gap.received
+                );
+                self.state = SyncState::Syncing { buffer };
+                SyncAction::FetchSnapshot
+            }
+        }
+    }
+
+    /// Drops the book and the buffered diffs, a new snapshot is needed to sync again.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.gap_recovery);
+    }
+}
+
+/// Applies the buffered diffs in order, the diffs from the gap on are left in the buffer.
+fn apply_buffer<L: OrderBookLevel>(
+    book: &mut OrderBook<L>,
+    buffer: &mut VecDeque<DepthDiff<L>>,
+) -> Result<(), VersionGap> {
+    while let Some(diff) = buffer.front() {
+        book.apply(diff)?;
+        buffer.pop_front();
+    }
+    Ok(())
+}
+
+/// Latest synced books, shared by the task that maintains them and their consumers.
+#[derive(Debug)]
+pub(crate) struct BookPublisher<L> {
+    books: Arc<RwLock<HashMap<String, Arc<OrderBook<L>>>>>,
+    update_tx: broadcast::Sender<Arc<OrderBook<L>>>,
+}
+
+impl<L> Clone for BookPublisher<L> {
+    fn clone(&self) -> Self {
+        Self {
+            books: self.books.clone(),
+            update_tx: self.update_tx.clone(),
+        }
+    }
+}
+
+impl<L: OrderBookLevel + Send + Sync + 'static> BookPublisher<L> {
+    fn new() -> Self {
+        let (update_tx, _update_rx) = broadcast::channel(1024);
+        Self {
+            books: Arc::new(RwLock::new(HashMap::new())),
+            update_tx,
+        }
+    }
+
+    pub async fn publish(&self, book: &OrderBook<L>) {
+        let book = Arc::new(book.clone());
+        self.books
+            .write()
+            .await
+            .insert(book.symbol.clone(), book.clone());
+        // There might not be anyone listening
+        let _ = self.update_tx.send(book);
+    }
+
+    /// Hides the book of the symbol while it is (re)syncing.
+    pub async fn remove(&self, symbol: &str) {
+        self.books.write().await.remove(symbol);
+    }
+
+    pub async fn clear(&self) {
+        self.books.write().await.clear();
+    }
+}
+
+/// Order books that are maintained by a task, which is stopped when these are dropped.
+#[derive(Debug)]
+pub(crate) struct MaintainedBooks<L> {
+    publisher: BookPublisher<L>,
+    cancellation_token: CancellationToken,
+}
+
+impl<L: OrderBookLevel + Send + Sync + 'static> MaintainedBooks<L> {
+    pub fn new(cancellation_token: CancellationToken) -> Self {
+        Self {
+            publisher: BookPublisher::new(),
+            cancellation_token,
+        }
+    }
+
+    pub fn publisher(&self) -> BookPublisher<L> {
+        self.publisher.clone()
+    }
+
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    pub async fn get(&self, symbol: &str) -> Option<Arc<OrderBook<L>>> {
+        self.publisher.books.read().await.get(symbol).cloned()
+    }
+
+    /// Every book after an update was applied. A consumer that falls behind skips the pending
+    /// updates and receives the current book of every symbol instead.
+    pub fn stream(&self) -> BoxStream<'static, Arc<OrderBook<L>>> {
+        let mut rx = self.publisher.update_tx.subscribe();
+        let books = self.publisher.books.clone();
+        let stream = async_stream::stream! {
+            loop {
+                match rx.recv().await {
+                    Ok(order_book) => yield order_book,
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::debug!(
+                            "Order book stream skipped {} updates, yielding the current books",
+                            skipped
+                        );
+                        // The updates still in the channel are older than the current books.
+                        rx = rx.resubscribe();
+                        let current_books = books.read().await.values().cloned().collect::<Vec<_>>();
+                        for order_book in current_books {
+                            yield order_book;
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        };
+        stream.boxed()
+    }
+
+    /// Stops the task and drops the books.
+    pub async fn stop(&self) {
+        self.cancellation_token.cancel();
+        self.publisher.clear().await;
+    }
+}
+
+impl<L> Drop for MaintainedBooks<L> {
+    fn drop(&mut self) {
+        self.cancellation_token.cancel();
+    }
+}
+
+/// Fetches from the REST api until it succeeds and sends the result along with the symbol,
+/// retrying after a delay when the request failed.
+pub(crate) fn spawn_fetch_task<T, E, F, Fut>(
+    symbol: String,
+    description: &'static str,
+    fetch: F,
+    result_tx: mpsc::UnboundedSender<(String, T)>,
+    cancellation_token: CancellationToken,
+) where
+    T: Send + 'static,
+    E: Display,
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, E>> + Send,
+{
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    break;
+                }
+                result = fetch() => match result {
+                    Ok(result) => {
+                        let _ = result_tx.send((symbol, result));
+                        break;
+                    }
+                    Err(err) => {
+                        tracing::error!("Failed to fetch {} of {}: {}", description, symbol, err);
+                    }
+                }
+            }
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    break;
+                }
+                _ = tokio::time::sleep(FETCH_RETRY_DELAY) => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Level {
+        price: Decimal,
+        quantity: Decimal,
+    }
+
+    impl OrderBookLevel for Level {
+        fn price(&self) -> Decimal {
+            self.price
+        }
+
+        fn quantity(&self) -> Decimal {
+            self.quantity
+        }
+    }
+
+    fn level(price: i64, quantity: i64) -> Level {
+        Level {
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+        }
+    }
+
+    fn snapshot(version: u64) -> OrderBook<Level> {
+        OrderBook::new(
+            "BTCUSDT".to_string(),
+            version,
+            &[level(99, 1), level(98, 2)],
+            &[level(101, 1), level(102, 2)],
+        )
+    }
+
+    fn diff(from_version: u64, to_version: u64, bids: Vec<Level>) -> DepthDiff<Level> {
+        DepthDiff {
+            from_version,
+            to_version,
+            asks: vec![],
+            bids,
+        }
+    }
+
+    #[test]
+    fn apply_diff() {
+        let mut book = snapshot(10);
+        assert_eq!(
+            book.apply(&diff(11, 12, vec![level(100, 3), level(99, 0)])),
+            Ok(true)
+        );
+        assert_eq!(book.version(), 12);
+        assert_eq!(book.best_bid().unwrap().price, Decimal::from(100));
+        assert_eq!(book.bids().count(), 2);
+        assert_eq!(book.best_ask().unwrap().price, Decimal::from(101));
+    }
+
+    #[test]
+    fn skip_stale_diff() {
+        let mut book = snapshot(10);
+        assert_eq!(book.apply(&diff(8, 10, vec![level(100, 3)])), Ok(false));
+        assert_eq!(book.best_bid().unwrap().price, Decimal::from(99));
+    }
+
+    #[test]
+    fn detect_version_gap() {
+        let mut book = snapshot(10);
+        assert_eq!(
+            book.apply(&diff(12, 13, vec![])),
+            Err(VersionGap {
+                expected: 11,
+                received: 12
+            })
+        );
+    }
+
+    #[test]
+    fn sync_with_buffered_diffs() {
+        let mut state = SymbolSync::new(GapRecovery::Snapshot);
+        assert_eq!(state.on_diff(diff(9, 10, vec![])), SyncAction::None);
+        assert_eq!(
+            state.on_diff(diff(11, 11, vec![level(100, 1)])),
+            SyncAction::None
+        );
+        assert_eq!(state.on_snapshot(snapshot(10)), SyncAction::Publish);
+        let book = state.book().unwrap();
+        assert_eq!(book.version(), 11);
+        assert_eq!(book.best_bid().unwrap().price, Decimal::from(100));
+    }
+
+    #[test]
+    fn refetch_snapshot_that_is_too_old() {
+        let mut state = SymbolSync::new(GapRecovery::Snapshot);
+        state.on_diff(diff(15, 16, vec![]));
+        assert_eq!(state.on_snapshot(snapshot(10)), SyncAction::FetchSnapshot);
+        assert!(state.book().is_none());
+        assert_eq!(state.on_snapshot(snapshot(15)), SyncAction::Publish);
+        assert_eq!(state.book().unwrap().version(), 16);
+    }
+
+    #[test]
+    fn resync_on_gap() {
+        let mut state = SymbolSync::new(GapRecovery::Snapshot);
+        state.on_snapshot(snapshot(10));
+        assert_eq!(state.on_diff(diff(11, 11, vec![])), SyncAction::Publish);
+        assert_eq!(
+            state.on_diff(diff(13, 13, vec![])),
+            SyncAction::FetchSnapshot
+        );
+        assert!(state.book().is_none());
+        assert_eq!(state.on_snapshot(snapshot(12)), SyncAction::Publish);
+        assert_eq!(state.book().unwrap().version(), 13);
+    }
+
+    #[test]
+    fn repair_gap_with_missed_diffs() {
+        let mut state = SymbolSync::new(GapRecovery::MissedDiffs);
+        state.on_snapshot(snapshot(10));
+        assert_eq!(state.on_diff(diff(11, 11, vec![])), SyncAction::Publish);
+        assert_eq!(
+            state.on_diff(diff(14, 14, vec![])),
+            SyncAction::FetchMissedDiffs
+        );
+        assert!(state.book().is_none());
+        assert_eq!(state.on_diff(diff(15, 15, vec![])), SyncAction::None);
+
+        assert_eq!(
+            state.on_missed_diffs(&[
+                diff(11, 11, vec![]),
+                diff(12, 12, vec![level(100, 1)]),
+                diff(13, 13, vec![]),
+            ]),
+            SyncAction::Publish
+        );
+        let book = state.book().unwrap();
+        assert_eq!(book.version(), 15);
+        assert_eq!(book.best_bid().unwrap().price, Decimal::from(100));
+    }
+
+    #[test]
+    fn fetch_snapshot_when_missed_diffs_do_not_cover_gap() {
+        let mut state = SymbolSync::new(GapRecovery::MissedDiffs);
+        state.on_snapshot(snapshot(10));
+        assert_eq!(
+            state.on_diff(diff(20, 20, vec![])),
+            SyncAction::FetchMissedDiffs
+        );
+        assert_eq!(
+            state.on_missed_diffs(&[diff(15, 15, vec![]), diff(16, 16, vec![])]),
+            SyncAction::FetchSnapshot
+        );
+        assert!(state.book().is_none());
+        assert_eq!(state.on_snapshot(snapshot(19)), SyncAction::Publish);
+        assert_eq!(state.book().unwrap().version(), 20);
+    }
+
+    #[tokio::test]
+    async fn stream_skips_to_the_current_books_when_lagging() {
+        let books = MaintainedBooks::new(CancellationToken::new());
+        let publisher = books.publisher();
+        let mut stream = books.stream();
+
+        // Overfill the channel without consuming, the last published book is the current one.
+        for version in 0..1100 {
+            publisher.publish(&snapshot(version)).await;
+        }
+        let order_book = stream.next().await.unwrap();
+        assert_eq!(order_book.version(), 1099);
+
+        // The stale updates that are still in the channel are skipped.
+        publisher.publish(&snapshot(1100)).await;
+        let order_book = stream.next().await.unwrap();
+        assert_eq!(order_book.version(), 1100);
+    }
+
+    #[tokio::test]
+    async fn drop_stops_the_task() {
+        let books = MaintainedBooks::<Level>::new(CancellationToken::new());
+        let cancellation_token = books.cancellation_token().clone();
+
+        drop(books);
+        assert!(cancellation_token.is_cancelled());
+    }
+}
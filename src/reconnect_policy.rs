@@ -0,0 +1,141 @@
+use rand::Rng;
+use std::time::Duration;
+
+/// Determines how often and how fast a lost websocket connection is reestablished.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Delay before the first attempt.
+    pub initial_delay: Duration,
+    /// Factor the delay grows by after every failed attempt.
+    pub multiplier: f64,
+    /// Fraction of the delay that is randomly added or subtracted, between 0 and 1.
+    pub jitter: f64,
+    pub max_delay: Duration,
+    /// Amount of attempts before giving up, `None` to keep retrying forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new(
+            Duration::from_millis(500),
+            2.0,
+            0.2,
+            Duration::from_secs(60),
+            None,
+        )
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn new(
+        initial_delay: Duration,
+        multiplier: f64,
+        jitter: f64,
+        max_delay: Duration,
+        max_attempts: Option<u32>,
+    ) -> Self {
+        Self {
+            initial_delay,
+            multiplier,
+            jitter,
+            max_delay,
+            max_attempts,
+        }
+    }
+
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    pub fn with_infinite_attempts(mut self) -> Self {
+        self.max_attempts = None;
+        self
+    }
+
+    /// Whether the given attempt, starting at 1, may be made.
+    pub fn allows_attempt(&self, attempt: u32) -> bool {
+        match self.max_attempts {
+            Some(max_attempts) => attempt <= max_attempts,
+            None => true,
+        }
+    }
+
+    /// Delay before the given attempt, starting at 1, without jitter.
+    pub fn base_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay_secs = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        if !delay_secs.is_finite() || delay_secs >= self.max_delay.as_secs_f64() {
+            return self.max_delay;
+        }
+        Duration::from_secs_f64(delay_secs.max(0.0))
+    }
+
+    /// Delay before the given attempt, starting at 1, with jitter applied.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base_delay = self.base_delay(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return base_delay;
+        }
+        let factor = rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter);
+        Duration::from_secs_f64(base_delay.as_secs_f64() * factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential_delay_up_to_max() {
+        let policy = ReconnectPolicy::default()
+            .with_initial_delay(Duration::from_secs(1))
+            .with_max_delay(Duration::from_secs(10));
+        assert_eq!(policy.base_delay(1), Duration::from_secs(1));
+        assert_eq!(policy.base_delay(2), Duration::from_secs(2));
+        assert_eq!(policy.base_delay(4), Duration::from_secs(8));
+        assert_eq!(policy.base_delay(5), Duration::from_secs(10));
+        assert_eq!(policy.base_delay(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = ReconnectPolicy::default()
+            .with_initial_delay(Duration::from_secs(10))
+            .with_jitter(0.5);
+        for _ in 0..100 {
+            let delay = policy.delay(1);
+            assert!(delay >= Duration::from_secs(5) && delay <= Duration::from_secs(15));
+        }
+    }
+
+    #[test]
+    fn max_attempts() {
+        let policy = ReconnectPolicy::default().with_max_attempts(3);
+        assert!(policy.allows_attempt(3));
+        assert!(!policy.allows_attempt(4));
+        assert!(ReconnectPolicy::default().allows_attempt(u32::MAX));
+    }
+}
@@ -0,0 +1,287 @@
+use crate::spot::v3::{ApiResponse, ApiResult};
+use crate::spot::MexcSpotApiTrait;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use rust_decimal::Decimal;
+
+/// Most trades that are returned by a single request.
+pub const AGG_TRADES_LIMIT: u32 = 1000;
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggTradesParams<'a> {
+    pub symbol: &'a str,
+    /// Start time, the range between start and end time can be at most an hour.
+    #[serde(rename = "startTime", with = "chrono::serde::ts_milliseconds_option")]
+    pub start_time: Option<DateTime<Utc>>,
+    /// End time
+    #[serde(rename = "endTime", with = "chrono::serde::ts_milliseconds_option")]
+    pub end_time: Option<DateTime<Utc>>,
+    /// Default 500; max 1000.
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AggTradesOutput {
+    pub trades: Vec<AggTrade>,
+}
+
+#[derive(Debug, serde::Deserialize, Clone, PartialEq)]
+pub struct AggTrade {
+    /// Currently always filled with null
+    #[serde(rename = "a")]
+    pub aggregate_trade_id: Option<u64>,
+    /// Currently always filled with null
+    #[serde(rename = "f")]
+    pub first_trade_id: Option<u64>,
+    /// Currently always filled with null
+    #[serde(rename = "l")]
+    pub last_trade_id: Option<u64>,
+    #[serde(rename = "p")]
+    pub price: Decimal,
+    #[serde(rename = "q")]
+    pub quantity: Decimal,
+    #[serde(rename = "T", with = "chrono::serde::ts_milliseconds")]
+    pub time: DateTime<Utc>,
+    #[serde(rename = "m")]
+    pub is_buyer_maker: bool,
+    #[serde(rename = "M")]
+    pub is_best_match: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct AggTradesStreamParams {
+    pub symbol: String,
+    pub start_time: DateTime<Utc>,
+    /// Exclusive.
+    pub end_time: DateTime<Utc>,
+    /// Range of a single request, at most an hour.
+    pub window: Duration,
+}
+
+impl AggTradesStreamParams {
+    pub fn new(symbol: String, start_time: DateTime<Utc>, end_time: DateTime<Utc>) -> Self {
+        Self {
+            symbol,
+            start_time,
+            end_time,
+            window: Duration::hours(1),
+        }
+    }
+
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+}
+
+/// Position of an [`AggTradesEndpoint::agg_trades_stream`] within its range.
+///
+/// Trades carry no ids, so when a window holds more trades than fit in a single request the next
+/// request starts at the time of the last trade and the trades at that time that were already
+/// returned are skipped.
+#[derive(Debug)]
+struct AggTradesCursor {
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    window: Duration,
+    /// Time the next request starts at and the amount of trades at that time that were already
+    /// returned.
+    boundary: Option<(DateTime<Utc>, usize)>,
+}
+
+impl AggTradesCursor {
+    fn new(params: &AggTradesStreamParams) -> Self {
+        Self {
+            start_time: params.start_time,
+            end_time: params.end_time,
+            window: params.window.max(Duration::milliseconds(1)),
+            boundary: None,
+        }
+    }
+
+    /// Inclusive range of the next request, `None` when the whole range was covered.
+    fn window(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if self.start_time >= self.end_time {
+            return None;
+        }
+        let window_end = (self.start_time + self.window).min(self.end_time);
+        Some((self.start_time, window_end - Duration::milliseconds(1)))
+    }
+
+    /// Moves past the trades of the current window and returns those that were not returned yet.
+    fn advance(&mut self, trades: Vec<AggTrade>, limit: usize) -> Vec<AggTrade> {
+        let Some((window_start, window_end)) = self.window() else {
+            return Vec::new();
+        };
+        let is_full = trades.len() >= limit;
+        let skip = match self.boundary.take() {
+            Some((time, count)) if time == window_start => trades
+                .iter()
+                .take(count)
+                .take_while(|trade| trade.time == time)
+                .count(),
+            _ => 0,
+        };
+        let trades = trades.into_iter().skip(skip).collect::<Vec<_>>();
+
+        if !is_full {
+            self.start_time = window_end + Duration::milliseconds(1);
+            return trades;
+        }
+
+        match trades.last() {
+            Some(last) => {
+                let returned_at_last = trades
+                    .iter()
+                    .rev()
+                    .take_while(|trade| trade.time == last.time)
+                    .count();
+                let returned_before = if last.time == window_start { skip } else { 0 };
+                self.start_time = last.time;
+                self.boundary = Some((last.time, returned_before + returned_at_last));
+            }
+            None => {
+                tracing::warn!(
+                    "More than {} aggregate trades at {}, skipping the remainder",
+                    limit,
+                    window_start
+                );
+                self.start_time = window_start + Duration::milliseconds(1);
+            }
+        }
+
+        trades
+    }
+}
+
+#[async_trait]
+pub trait AggTradesEndpoint {
+    /// Compressed/Aggregate trades list
+    async fn agg_trades(&self, params: AggTradesParams<'_>) -> ApiResult<AggTradesOutput>;
+
+    /// Aggregate trades of any time range, oldest first, requested window by window.
+    ///
+    /// The stream ends after the first error.
+    fn agg_trades_stream<'a>(
+        &'a self,
+        params: AggTradesStreamParams,
+    ) -> BoxStream<'a, ApiResult<AggTrade>>;
+}
+
+#[async_trait]
+impl<T: MexcSpotApiTrait + Sync> AggTradesEndpoint for T {
+    async fn agg_trades(&self, params: AggTradesParams<'_>) -> ApiResult<AggTradesOutput> {
+        let endpoint = format!("{}/api/v3/aggTrades", self.endpoint().as_ref());
+        let response = self
+            .reqwest_client()
+            .get(&endpoint)
+            .query(&params)
+            .send()
+            .await?;
+        let api_response = response.json::<ApiResponse<Vec<AggTrade>>>().await?;
+        let trades = api_response.into_api_result()?;
+
+        Ok(AggTradesOutput { trades })
+    }
+
+    fn agg_trades_stream<'a>(
+        &'a self,
+        params: AggTradesStreamParams,
+    ) -> BoxStream<'a, ApiResult<AggTrade>> {
+        let stream = async_stream::stream! {
+            let mut cursor = AggTradesCursor::new(&params);
+            while let Some((start_time, end_time)) = cursor.window() {
+                let result = self
+                    .agg_trades(AggTradesParams {
+                        symbol: &params.symbol,
+                        start_time: Some(start_time),
+                        end_time: Some(end_time),
+                        limit: Some(AGG_TRADES_LIMIT),
+                    })
+                    .await;
+                let output = match result {
+                    Ok(output) => output,
+                    Err(err) => {
+                        yield Err(err);
+                        return;
+                    }
+                };
+                for trade in cursor.advance(output.trades, AGG_TRADES_LIMIT as usize) {
+                    yield Ok(trade);
+                }
+            }
+        };
+
+        stream.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::spot::MexcSpotApiClient;
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn trade(time_millis: i64, price: i64) -> AggTrade {
+        AggTrade {
+            aggregate_trade_id: None,
+            first_trade_id: None,
+            last_trade_id: None,
+            price: Decimal::from(price),
+            quantity: Decimal::ONE,
+            time: Utc.timestamp_millis_opt(time_millis).unwrap(),
+            is_buyer_maker: false,
+            is_best_match: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agg_trades() {
+        let client = MexcSpotApiClient::default();
+        let params = AggTradesParams {
+            symbol: "BTCUSDT",
+            start_time: None,
+            end_time: None,
+            limit: Some(10),
+        };
+        let result = client.agg_trades(params).await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn cursor_steps_through_windows_and_skips_returned_trades() {
+        let params = AggTradesStreamParams::new(
+            "BTCUSDT".to_string(),
+            Utc.timestamp_millis_opt(0).unwrap(),
+            Utc.timestamp_millis_opt(100).unwrap(),
+        )
+        .with_window(Duration::milliseconds(60));
+        let mut cursor = AggTradesCursor::new(&params);
+        let millis = |time: DateTime<Utc>| time.timestamp_millis();
+
+        let (start, end) = cursor.window().unwrap();
+        assert_eq!((millis(start), millis(end)), (0, 59));
+        // Full page, the last two trades share a time with trades of the next page.
+        let returned = cursor.advance(vec![trade(1, 1), trade(5, 2), trade(5, 3)], 3);
+        assert_eq!(returned.len(), 3);
+
+        let (start, end) = cursor.window().unwrap();
+        assert_eq!((millis(start), millis(end)), (5, 64));
+        let returned = cursor.advance(vec![trade(5, 2), trade(5, 3), trade(5, 4)], 3);
+        assert_eq!(returned, vec![trade(5, 4)]);
+
+        let (start, _) = cursor.window().unwrap();
+        assert_eq!(millis(start), 5);
+        let returned = cursor.advance(vec![trade(5, 2), trade(5, 3), trade(5, 4), trade(7, 5)], 5);
+        assert_eq!(returned, vec![trade(7, 5)]);
+
+        let (start, end) = cursor.window().unwrap();
+        assert_eq!((millis(start), millis(end)), (65, 99));
+        assert!(cursor.advance(Vec::new(), 3).is_empty());
+        assert!(cursor.window().is_none());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::spot::v3::{ApiResponse, ApiResult};
+use crate::spot::MexcSpotApiTrait;
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde_with::{serde_as, NoneAsEmptyString, PickFirst};
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct BookTickerParams<'a> {
+    /// All symbols when not given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BookTickerOutput {
+    pub tickers: Vec<BookTicker>,
+}
+
+/// Best bid and ask of a symbol, a side without orders is `None`.
+#[serde_as]
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BookTicker {
+    pub symbol: String,
+    #[serde_as(as = "PickFirst<(NoneAsEmptyString, _)>")]
+    #[serde(default)]
+    pub bid_price: Option<Decimal>,
+    #[serde_as(as = "PickFirst<(NoneAsEmptyString, _)>")]
+    #[serde(default, rename = "bidQty")]
+    pub bid_quantity: Option<Decimal>,
+    #[serde_as(as = "PickFirst<(NoneAsEmptyString, _)>")]
+    #[serde(default)]
+    pub ask_price: Option<Decimal>,
+    #[serde_as(as = "PickFirst<(NoneAsEmptyString, _)>")]
+    #[serde(default, rename = "askQty")]
+    pub ask_quantity: Option<Decimal>,
+}
+
+/// A single ticker is returned when a symbol was given, all of them otherwise.
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum BookTickerResponse {
+    One(BookTicker),
+    Many(Vec<BookTicker>),
+}
+
+#[async_trait]
+pub trait BookTickerEndpoint {
+    /// Symbol order book ticker
+    async fn book_ticker(&self, params: BookTickerParams<'_>) -> ApiResult<BookTickerOutput>;
+}
+
+#[async_trait]
+impl<T: MexcSpotApiTrait + Sync> BookTickerEndpoint for T {
+    async fn book_ticker(&self, params: BookTickerParams<'_>) -> ApiResult<BookTickerOutput> {
+        let endpoint = format!("{}/api/v3/ticker/bookTicker", self.endpoint().as_ref());
+        let response = self
+            .reqwest_client()
+            .get(&endpoint)
+            .query(&params)
+            .send()
+            .await?;
+        let api_response = response.json::<ApiResponse<BookTickerResponse>>().await?;
+        let tickers = match api_response.into_api_result()? {
+            BookTickerResponse::One(ticker) => vec![ticker],
+            BookTickerResponse::Many(tickers) => tickers,
+        };
+
+        Ok(BookTickerOutput { tickers })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::spot::MexcSpotApiClient;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_book_ticker() {
+        let client = MexcSpotApiClient::default();
+        let params = BookTickerParams {
+            symbol: Some("BTCUSDT"),
+        };
+        let result = client.book_ticker(params).await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn deserialize() {
+        let json = r#"[{"symbol":"BTCUSDT","bidPrice":"46263.71","bidQty":"1.5","askPrice":"46263.72","askQty":"0.2"},{"symbol":"NEWUSDT","bidPrice":"","bidQty":"","askPrice":null,"askQty":null}]"#;
+        let BookTickerResponse::Many(tickers) =
+            serde_json::from_str::<BookTickerResponse>(json).unwrap()
+        else {
+            panic!("Expected all tickers");
+        };
+        assert_eq!(tickers[0].bid_quantity, Some(Decimal::new(15, 1)));
+        assert_eq!(tickers[1].bid_price, None);
+        assert_eq!(tickers[1].ask_quantity, None);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::spot::v3::{ApiResponse, ApiResult};
+use crate::spot::MexcSpotApiClientWithAuthentication;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug)]
+pub struct DeleteUserDataStreamParams<'a> {
+    pub listen_key: &'a str,
+}
+
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteUserDataStreamQuery<'a> {
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    timestamp: DateTime<Utc>,
+    listen_key: &'a str,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteUserDataStreamOutput {
+    pub listen_key: String,
+}
+
+#[async_trait]
+pub trait DeleteUserDataStreamEndpoint {
+    async fn delete_user_data_stream(
+        &self,
+        params: DeleteUserDataStreamParams<'_>,
+    ) -> ApiResult<DeleteUserDataStreamOutput>;
+}
+
+#[async_trait]
+impl DeleteUserDataStreamEndpoint for MexcSpotApiClientWithAuthentication {
+    async fn delete_user_data_stream(
+        &self,
+        params: DeleteUserDataStreamParams<'_>,
+    ) -> ApiResult<DeleteUserDataStreamOutput> {
+        let url = format!("{}/api/v3/userDataStream", self.endpoint.as_ref());
+        let query = DeleteUserDataStreamQuery {
+            timestamp: Utc::now(),
+            listen_key: params.listen_key,
+        };
+        let query = self.sign_query(&query)?;
+        let response = self
+            .reqwest_client
+            .delete(&url)
+            .query(&query)
+            .send()
+            .await?;
+        let api_response = response
+            .json::<ApiResponse<DeleteUserDataStreamOutput>>()
+            .await?;
+        let output = api_response.into_api_result()?;
+
+        Ok(output)
+    }
+}
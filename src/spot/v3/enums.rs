@@ -1,3 +1,5 @@
+use chrono::{DateTime, Duration, Months, Utc};
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum OrderSide {
@@ -83,6 +85,25 @@ pub enum KlineInterval {
     OneMonth,
 }
 
+impl KlineInterval {
+    /// Open time of the candle that follows the one opened at the given time.
+    pub fn next_open_time(&self, open_time: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            KlineInterval::OneMinute => open_time + Duration::minutes(1),
+            KlineInterval::FiveMinutes => open_time + Duration::minutes(5),
+            KlineInterval::FifteenMinutes => open_time + Duration::minutes(15),
+            KlineInterval::ThirtyMinutes => open_time + Duration::minutes(30),
+            KlineInterval::OneHour => open_time + Duration::hours(1),
+            KlineInterval::FourHours => open_time + Duration::hours(4),
+            KlineInterval::OneDay => open_time + Duration::days(1),
+            KlineInterval::OneWeek => open_time + Duration::weeks(1),
+            KlineInterval::OneMonth => open_time
+                .checked_add_months(Months::new(1))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+        }
+    }
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ChangedType {
@@ -16,7 +16,7 @@ pub enum ExchangeInformationParams<'a> {
 #[serde(rename_all = "camelCase")]
 pub struct ExchangeInformationSymbol {
     pub symbol: String,
-    pub status: String,
+    pub status: SymbolStatus,
     pub base_asset: String,
     pub base_asset_precision: i32,
     pub quote_asset: String,
@@ -30,21 +30,179 @@ pub struct ExchangeInformationSymbol {
     pub is_margin_trading_allowed: bool,
     pub quote_amount_precision: Decimal,
     pub base_size_precision: Decimal,
-    pub permissions: Vec<String>,
-    pub filters: Vec<serde_json::Value>,
+    pub permissions: Vec<SymbolPermission>,
+    pub filters: Vec<SymbolFilter>,
     pub max_quote_amount: Decimal,
     pub maker_commission: Decimal,
     pub taker_commission: Decimal,
 }
 
+#[derive(Debug, serde::Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String")]
+pub enum SymbolStatus {
+    Online,
+    Paused,
+    Offline,
+    Unknown(String),
+}
+
+impl From<String> for SymbolStatus {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "1" | "ENABLED" => SymbolStatus::Online,
+            "2" => SymbolStatus::Paused,
+            "3" => SymbolStatus::Offline,
+            _ => SymbolStatus::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String")]
+pub enum SymbolPermission {
+    Spot,
+    Margin,
+    Unknown(String),
+}
+
+impl From<String> for SymbolPermission {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "SPOT" => SymbolPermission::Spot,
+            "MARGIN" => SymbolPermission::Margin,
+            _ => SymbolPermission::Unknown(value),
+        }
+    }
+}
+
+/// Trading rule of a symbol, filters that are not known or not in the expected shape are kept as
+/// [`SymbolFilter::Unknown`].
+#[derive(Debug, serde::Deserialize, Clone, PartialEq)]
+#[serde(tag = "filterType", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SymbolFilter {
+    #[serde(rename_all = "camelCase")]
+    PriceFilter {
+        min_price: Decimal,
+        max_price: Decimal,
+        tick_size: Decimal,
+    },
+    #[serde(rename_all = "camelCase")]
+    LotSize {
+        #[serde(rename = "minQty")]
+        min_quantity: Decimal,
+        #[serde(rename = "maxQty")]
+        max_quantity: Decimal,
+        step_size: Decimal,
+    },
+    #[serde(rename_all = "camelCase")]
+    MarketLotSize {
+        #[serde(rename = "minQty")]
+        min_quantity: Decimal,
+        #[serde(rename = "maxQty")]
+        max_quantity: Decimal,
+        step_size: Decimal,
+    },
+    #[serde(rename_all = "camelCase")]
+    MinNotional { min_notional: Decimal },
+    #[serde(rename_all = "camelCase")]
+    Notional {
+        min_notional: Option<Decimal>,
+        max_notional: Option<Decimal>,
+    },
+    /// Limits of the order price relative to the last price, per side.
+    #[serde(rename_all = "camelCase")]
+    PercentPriceBySide {
+        bid_multiplier_up: Decimal,
+        bid_multiplier_down: Decimal,
+        ask_multiplier_up: Decimal,
+        ask_multiplier_down: Decimal,
+    },
+    #[serde(rename_all = "camelCase")]
+    MaxNumOrders { max_num_orders: u32 },
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+/// Trading rule of the whole exchange, filters that are not known or not in the expected shape
+/// are kept as [`ExchangeFilter::Unknown`].
+#[derive(Debug, serde::Deserialize, Clone, PartialEq)]
+#[serde(tag = "filterType", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExchangeFilter {
+    #[serde(rename_all = "camelCase")]
+    ExchangeMaxNumOrders { max_num_orders: u32 },
+    #[serde(rename_all = "camelCase")]
+    ExchangeMaxNumAlgoOrders { max_num_algo_orders: u32 },
+    #[serde(untagged)]
+    Unknown(serde_json::Value),
+}
+
+/// Rate limit of the exchange, limits that are not in the expected shape are kept as
+/// [`RateLimit::Unknown`].
+#[derive(Debug, serde::Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum RateLimit {
+    Known(KnownRateLimit),
+    Unknown(serde_json::Value),
+}
+
+#[derive(Debug, serde::Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownRateLimit {
+    pub rate_limit_type: RateLimitType,
+    pub interval: RateLimitInterval,
+    /// Amount of intervals the limit applies to.
+    pub interval_num: u32,
+    pub limit: u32,
+}
+
+#[derive(Debug, serde::Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String")]
+pub enum RateLimitType {
+    RequestWeight,
+    Orders,
+    RawRequests,
+    Unknown(String),
+}
+
+impl From<String> for RateLimitType {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "REQUEST_WEIGHT" => RateLimitType::RequestWeight,
+            "ORDERS" => RateLimitType::Orders,
+            "RAW_REQUESTS" => RateLimitType::RawRequests,
+            _ => RateLimitType::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(from = "String")]
+pub enum RateLimitInterval {
+    Second,
+    Minute,
+    Day,
+    Unknown(String),
+}
+
+impl From<String> for RateLimitInterval {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "SECOND" => RateLimitInterval::Second,
+            "MINUTE" => RateLimitInterval::Minute,
+            "DAY" => RateLimitInterval::Day,
+            _ => RateLimitInterval::Unknown(value),
+        }
+    }
+}
+
 #[derive(Debug, serde::Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ExchangeInformationOutput {
     pub timezone: String,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub server_time: DateTime<Utc>,
-    pub rate_limits: Vec<serde_json::Value>,
-    pub exchange_filters: Vec<serde_json::Value>,
+    pub rate_limits: Vec<RateLimit>,
+    pub exchange_filters: Vec<ExchangeFilter>,
     pub symbols: Vec<ExchangeInformationSymbol>,
 }
 
@@ -130,6 +288,49 @@ mod tests {
         assert_eq!(first_symbol.symbol, "BTCUSDT");
     }
 
+    #[test]
+    fn deserialize_typed_fields() {
+        let json = r#"{"timezone":"CST","serverTime":1690000000000,"rateLimits":[{"rateLimitType":"REQUEST_WEIGHT","interval":"MINUTE","intervalNum":1,"limit":1200},{"rateLimitType":"ORDERS"}],"exchangeFilters":[{"filterType":"EXCHANGE_MAX_NUM_ORDERS","maxNumOrders":200}],"symbols":[{"symbol":"BTCUSDT","status":"1","baseAsset":"BTC","baseAssetPrecision":6,"quoteAsset":"USDT","quotePrecision":2,"quoteAssetPrecision":2,"baseCommissionPrecision":6,"quoteCommissionPrecision":2,"orderTypes":["LIMIT","MARKET"],"isSpotTradingAllowed":true,"isMarginTradingAllowed":false,"quoteAmountPrecision":"5","baseSizePrecision":"0.000001","permissions":["SPOT","FUTURE"],"filters":[{"filterType":"LOT_SIZE","minQty":"0.000001","maxQty":"1000","stepSize":"0.000001"},{"filterType":"PERCENT_PRICE_BY_SIDE","bidMultiplierUp":"5","bidMultiplierDown":"0.2","askMultiplierUp":"5","askMultiplierDown":"0.2"},{"filterType":"ICEBERG_PARTS","limit":10},{"filterType":"LOT_SIZE","minQty":"1"}],"maxQuoteAmount":"2000000","makerCommission":"0","takerCommission":"0.0005"}]}"#;
+        let output = serde_json::from_str::<ExchangeInformationOutput>(json).unwrap();
+
+        assert!(matches!(
+            &output.rate_limits[0],
+            RateLimit::Known(KnownRateLimit {
+                rate_limit_type: RateLimitType::RequestWeight,
+                interval: RateLimitInterval::Minute,
+                interval_num: 1,
+                limit: 1200,
+            })
+        ));
+        assert!(matches!(&output.rate_limits[1], RateLimit::Unknown(_)));
+        assert_eq!(
+            output.exchange_filters,
+            vec![ExchangeFilter::ExchangeMaxNumOrders {
+                max_num_orders: 200
+            }]
+        );
+
+        let symbol = &output.symbols[0];
+        assert_eq!(symbol.status, SymbolStatus::Online);
+        assert_eq!(
+            symbol.permissions,
+            vec![
+                SymbolPermission::Spot,
+                SymbolPermission::Unknown("FUTURE".to_string())
+            ]
+        );
+        assert!(matches!(
+            symbol.filters[0],
+            SymbolFilter::LotSize { step_size, .. } if step_size == Decimal::new(1, 6)
+        ));
+        assert!(matches!(
+            symbol.filters[1],
+            SymbolFilter::PercentPriceBySide { .. }
+        ));
+        assert!(matches!(&symbol.filters[2], SymbolFilter::Unknown(value) if value["limit"] == 10));
+        assert!(matches!(&symbol.filters[3], SymbolFilter::Unknown(_)));
+    }
+
     #[tokio::test]
     async fn test_multiple_symbols() {
         let client = MexcSpotApiClient::default();
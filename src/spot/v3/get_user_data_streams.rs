@@ -0,0 +1,54 @@
+use crate::spot::v3::{ApiResponse, ApiResult};
+use crate::spot::MexcSpotApiClientWithAuthentication;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, serde::Serialize)]
+pub struct GetUserDataStreamsQuery {
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct GetUserDataStreamsOutput {
+    /// Valid listen keys of the user
+    #[serde(rename = "listenKey", default)]
+    pub listen_keys: Vec<String>,
+}
+
+#[async_trait]
+pub trait GetUserDataStreamsEndpoint {
+    async fn get_user_data_streams(&self) -> ApiResult<GetUserDataStreamsOutput>;
+}
+
+#[async_trait]
+impl GetUserDataStreamsEndpoint for MexcSpotApiClientWithAuthentication {
+    async fn get_user_data_streams(&self) -> ApiResult<GetUserDataStreamsOutput> {
+        let url = format!("{}/api/v3/userDataStream", self.endpoint.as_ref());
+        let query = GetUserDataStreamsQuery {
+            timestamp: Utc::now(),
+        };
+        let query = self.sign_query(&query)?;
+        let response = self.reqwest_client.get(&url).query(&query).send().await?;
+        let api_response = response
+            .json::<ApiResponse<GetUserDataStreamsOutput>>()
+            .await?;
+        let output = api_response.into_api_result()?;
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_output() {
+        let output = serde_json::from_str::<GetUserDataStreamsOutput>(
+            r#"{"listenKey":["pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1"]}"#,
+        )
+        .unwrap();
+        assert_eq!(output.listen_keys.len(), 1);
+    }
+}
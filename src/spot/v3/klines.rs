@@ -1,8 +1,11 @@
+use crate::kline_history::{KlineHistoryCursor, KlineHistoryItem};
 use crate::spot::v3::enums::KlineInterval;
 use crate::spot::v3::{ApiError, ApiResult, ErrorResponse};
 use crate::spot::MexcSpotApiTrait;
 use async_trait::async_trait;
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use futures::stream::BoxStream;
+use futures::StreamExt;
 use rust_decimal::Decimal;
 
 #[derive(Debug, serde::Serialize)]
@@ -56,9 +59,46 @@ pub struct Kline {
 //     Ok(deser)
 // }
 
+/// Most klines that are returned by a single request.
+pub const KLINES_LIMIT: u32 = 1000;
+
+#[derive(Debug, Clone)]
+pub struct KlinesHistoryParams {
+    pub symbol: String,
+    pub interval: KlineInterval,
+    pub start_time: DateTime<Utc>,
+    /// Exclusive.
+    pub end_time: DateTime<Utc>,
+}
+
+impl KlinesHistoryParams {
+    pub fn new(
+        symbol: String,
+        interval: KlineInterval,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            symbol,
+            interval,
+            start_time,
+            end_time,
+        }
+    }
+}
+
 #[async_trait]
 pub trait KlinesEndpoint {
     async fn klines(&self, params: KlinesParams<'_>) -> ApiResult<KlinesOutput>;
+
+    /// Klines of any range, oldest first, requested page by page. Missing candles are reported as
+    /// gaps.
+    ///
+    /// The stream ends after the first error.
+    fn klines_history<'a>(
+        &'a self,
+        params: KlinesHistoryParams,
+    ) -> BoxStream<'a, ApiResult<KlineHistoryItem<Kline>>>;
 }
 
 #[async_trait]
@@ -96,7 +136,10 @@ impl<T: MexcSpotApiTrait + Sync> KlinesEndpoint for T {
                     .ok_or(ApiError::UnableToParseResponse)?
                     .as_i64()
                     .ok_or(ApiError::UnableToParseResponse)?;
-                let open_time = Utc.timestamp_millis_opt(open_time_ts_milliseconds).unwrap();
+                let open_time = Utc
+                    .timestamp_millis_opt(open_time_ts_milliseconds)
+                    .single()
+                    .ok_or(ApiError::UnableToParseResponse)?;
 
                 let open_str = entries
                     .get(1)
@@ -160,7 +203,8 @@ impl<T: MexcSpotApiTrait + Sync> KlinesEndpoint for T {
                     .ok_or(ApiError::UnableToParseResponse)?;
                 let close_time = Utc
                     .timestamp_millis_opt(close_time_ts_milliseconds)
-                    .unwrap();
+                    .single()
+                    .ok_or(ApiError::UnableToParseResponse)?;
 
                 let quote_asset_volume_str = entries
                     .get(7)
@@ -193,6 +237,45 @@ impl<T: MexcSpotApiTrait + Sync> KlinesEndpoint for T {
 
         Ok(output)
     }
+
+    fn klines_history<'a>(
+        &'a self,
+        params: KlinesHistoryParams,
+    ) -> BoxStream<'a, ApiResult<KlineHistoryItem<Kline>>> {
+        let stream = async_stream::stream! {
+            let mut cursor = KlineHistoryCursor::new(params.start_time, params.end_time);
+            while let Some((start_time, end_time)) = cursor.next_range() {
+                let result = self
+                    .klines(KlinesParams {
+                        symbol: &params.symbol,
+                        interval: params.interval,
+                        start_time: Some(start_time),
+                        // The end time is inclusive.
+                        end_time: Some(end_time - Duration::milliseconds(1)),
+                        limit: Some(KLINES_LIMIT),
+                    })
+                    .await;
+                let output = match result {
+                    Ok(output) => output,
+                    Err(err) => {
+                        yield Err(err);
+                        return;
+                    }
+                };
+                let items = cursor.advance(
+                    output.klines,
+                    end_time,
+                    |kline| kline.open_time,
+                    |time| params.interval.next_open_time(time),
+                );
+                for item in items {
+                    yield Ok(item);
+                }
+            }
+        };
+
+        stream.boxed()
+    }
 }
 
 fn filter_decimal_str(string: &str) -> String {
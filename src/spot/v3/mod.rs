@@ -4,22 +4,28 @@ use reqwest::StatusCode;
 use std::fmt::{Display, Formatter};
 
 pub mod account_information;
+pub mod agg_trades;
 pub mod avg_price;
+pub mod book_ticker;
 pub mod cancel_all_open_orders_on_a_symbol;
 pub mod cancel_order;
 pub mod create_user_data_stream;
 pub mod default_symbols;
+pub mod delete_user_data_stream;
 pub mod depth;
 pub mod enums;
 pub mod exchange_information;
 pub mod get_open_orders;
 pub mod get_order;
+pub mod get_user_data_streams;
 pub mod keep_alive_user_data_stream;
 pub mod klines;
 pub mod models;
 pub mod order;
 pub mod ping;
 pub mod query_order;
+pub mod ticker_24hr;
+pub mod ticker_price;
 pub mod time;
 pub mod trades;
 
@@ -0,0 +1,122 @@
+use crate::spot::v3::{ApiResponse, ApiResult};
+use crate::spot::MexcSpotApiTrait;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde_with::{serde_as, NoneAsEmptyString, PickFirst};
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct Ticker24hrParams<'a> {
+    /// All symbols when not given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ticker24hrOutput {
+    pub tickers: Vec<Ticker24hr>,
+}
+
+/// Price change statistics of the last 24 hours. Quantities the exchange leaves empty are `None`.
+#[serde_as]
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Ticker24hr {
+    pub symbol: String,
+    pub price_change: Decimal,
+    /// Fraction of the open price, e.g. `0.004` for a change of 0.4%.
+    pub price_change_percent: Decimal,
+    pub prev_close_price: Decimal,
+    pub last_price: Decimal,
+    #[serde_as(as = "PickFirst<(NoneAsEmptyString, _)>")]
+    #[serde(default)]
+    pub bid_price: Option<Decimal>,
+    #[serde_as(as = "PickFirst<(NoneAsEmptyString, _)>")]
+    #[serde(default, rename = "bidQty")]
+    pub bid_quantity: Option<Decimal>,
+    #[serde_as(as = "PickFirst<(NoneAsEmptyString, _)>")]
+    #[serde(default)]
+    pub ask_price: Option<Decimal>,
+    #[serde_as(as = "PickFirst<(NoneAsEmptyString, _)>")]
+    #[serde(default, rename = "askQty")]
+    pub ask_quantity: Option<Decimal>,
+    pub open_price: Decimal,
+    pub high_price: Decimal,
+    pub low_price: Decimal,
+    pub volume: Decimal,
+    #[serde_as(as = "PickFirst<(NoneAsEmptyString, _)>")]
+    #[serde(default)]
+    pub quote_volume: Option<Decimal>,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub open_time: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub close_time: DateTime<Utc>,
+    /// Amount of trades.
+    pub count: Option<u64>,
+}
+
+/// A single ticker is returned when a symbol was given, all of them otherwise.
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum Ticker24hrResponse {
+    One(Box<Ticker24hr>),
+    Many(Vec<Ticker24hr>),
+}
+
+#[async_trait]
+pub trait Ticker24hrEndpoint {
+    async fn ticker_24hr(&self, params: Ticker24hrParams<'_>) -> ApiResult<Ticker24hrOutput>;
+}
+
+#[async_trait]
+impl<T: MexcSpotApiTrait + Sync> Ticker24hrEndpoint for T {
+    async fn ticker_24hr(&self, params: Ticker24hrParams<'_>) -> ApiResult<Ticker24hrOutput> {
+        let endpoint = format!("{}/api/v3/ticker/24hr", self.endpoint().as_ref());
+        let response = self
+            .reqwest_client()
+            .get(&endpoint)
+            .query(&params)
+            .send()
+            .await?;
+        let api_response = response.json::<ApiResponse<Ticker24hrResponse>>().await?;
+        let tickers = match api_response.into_api_result()? {
+            Ticker24hrResponse::One(ticker) => vec![*ticker],
+            Ticker24hrResponse::Many(tickers) => tickers,
+        };
+
+        Ok(Ticker24hrOutput { tickers })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::spot::MexcSpotApiClient;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ticker_24hr() {
+        let client = MexcSpotApiClient::default();
+        let params = Ticker24hrParams {
+            symbol: Some("BTCUSDT"),
+        };
+        let result = client.ticker_24hr(params).await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn deserialize() {
+        let json = r#"{"symbol":"BTCUSDT","priceChange":"184.34","priceChangePercent":"0.00400048","prevClosePrice":"46079.37","lastPrice":"46263.71","bidPrice":"46260.38","bidQty":"","askPrice":"46260.41","askQty":"0.5","openPrice":"46079.37","highPrice":"47550.01","lowPrice":"45555.5","volume":"1732.461487","quoteVolume":null,"openTime":1641349500000,"closeTime":1641349582808,"count":null}"#;
+        let response = serde_json::from_str::<Ticker24hrResponse>(json).unwrap();
+        let Ticker24hrResponse::One(ticker) = response else {
+            panic!("Expected a single ticker");
+        };
+        assert_eq!(ticker.bid_quantity, None);
+        assert_eq!(ticker.ask_quantity, Some(Decimal::new(5, 1)));
+        assert_eq!(ticker.quote_volume, None);
+        assert_eq!(ticker.count, None);
+
+        let response = serde_json::from_str::<Ticker24hrResponse>(&format!("[{}]", json)).unwrap();
+        assert!(matches!(response, Ticker24hrResponse::Many(tickers) if tickers.len() == 1));
+    }
+}
@@ -0,0 +1,74 @@
+use crate::spot::v3::{ApiResponse, ApiResult};
+use crate::spot::MexcSpotApiTrait;
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct TickerPriceParams<'a> {
+    /// All symbols when not given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<&'a str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TickerPriceOutput {
+    pub tickers: Vec<TickerPrice>,
+}
+
+#[derive(Debug, serde::Deserialize, Clone)]
+pub struct TickerPrice {
+    pub symbol: String,
+    /// Latest price.
+    pub price: Decimal,
+}
+
+/// A single ticker is returned when a symbol was given, all of them otherwise.
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum TickerPriceResponse {
+    One(TickerPrice),
+    Many(Vec<TickerPrice>),
+}
+
+#[async_trait]
+pub trait TickerPriceEndpoint {
+    /// Symbol price ticker
+    async fn ticker_price(&self, params: TickerPriceParams<'_>) -> ApiResult<TickerPriceOutput>;
+}
+
+#[async_trait]
+impl<T: MexcSpotApiTrait + Sync> TickerPriceEndpoint for T {
+    async fn ticker_price(&self, params: TickerPriceParams<'_>) -> ApiResult<TickerPriceOutput> {
+        let endpoint = format!("{}/api/v3/ticker/price", self.endpoint().as_ref());
+        let response = self
+            .reqwest_client()
+            .get(&endpoint)
+            .query(&params)
+            .send()
+            .await?;
+        let api_response = response.json::<ApiResponse<TickerPriceResponse>>().await?;
+        let tickers = match api_response.into_api_result()? {
+            TickerPriceResponse::One(ticker) => vec![ticker],
+            TickerPriceResponse::Many(tickers) => tickers,
+        };
+
+        Ok(TickerPriceOutput { tickers })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::spot::MexcSpotApiClient;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ticker_price() {
+        let client = MexcSpotApiClient::default();
+        let params = TickerPriceParams {
+            symbol: Some("BTCUSDT"),
+        };
+        let result = client.ticker_price(params).await;
+        assert!(result.is_ok());
+    }
+}
@@ -1,25 +1,32 @@
-use crate::spot::v3::create_user_data_stream::CreateUserDataStreamEndpoint;
-use crate::spot::v3::keep_alive_user_data_stream::{
-    KeepAliveUserDataStreamEndpoint, KeepAliveUserDataStreamParams,
-};
 use crate::spot::v3::ApiError;
 use crate::spot::ws::auth::WebsocketAuth;
+use crate::spot::ws::connection_event::{ConnectionEventKind, DisconnectCause};
+use crate::spot::ws::health::WebsocketMetrics;
+use crate::spot::ws::listen_key::{
+    acquire_listen_key, delete_listen_key, renew_listen_key, take_unused_listen_key,
+};
+use crate::spot::ws::recorder::RecordedFrameData;
 use crate::spot::ws::topic::Topic;
+use crate::spot::ws::watchdog::spawn_websocket_watchdog_task;
 use crate::spot::ws::{message, Inner, MexcSpotWebsocketClient, SendableMessage, WebsocketEntry};
-use crate::spot::MexcSpotApiClientWithAuthentication;
 use async_channel::Sender;
 use async_trait::async_trait;
+use futures::future::BoxFuture;
 use futures::stream::{SplitSink, SplitStream};
-use futures::{SinkExt, StreamExt};
+use futures::{FutureExt, SinkExt, StreamExt};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpStream;
 use tokio::sync::RwLock;
-use tokio_tungstenite::tungstenite::error::ProtocolError;
 use tokio_tungstenite::tungstenite::{Error, Message};
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+/// Time given to send the close frame when a websocket is closed.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
 #[derive(Debug)]
 pub(crate) struct AcquireWebsocketsForTopicsParams {
     pub auth: Option<WebsocketAuth>,
@@ -86,6 +93,9 @@ pub(crate) enum AcquireWebsocketForTopicsError {
 
     #[error("Could not create datastream (listen key)")]
     CouldNotCreateDataStream(#[from] ApiError),
+
+    #[error("Client was shut down")]
+    ClientShutDown,
 }
 
 #[async_trait]
@@ -111,37 +121,16 @@ impl AcquireWebsocketsForTopics for MexcSpotWebsocketClient {
             return Err(AcquireWebsocketForTopicsError::RequestedTopicsRequireAuthentication);
         }
 
-        let mut inner = self.inner.write().await;
-
-        let mut acquired_websockets =
-            match acquire_websockets_for_public_topics(self.clone(), &mut inner, public_topics)
-                .await
-            {
-                Ok(x) => x,
-                Err(err) => match err {
-                    AcquireWebsocketsForPublicTopicsError::TungesteniteError(err) => {
-                        return Err(AcquireWebsocketForTopicsError::TungesteniteError(err));
-                    }
-                },
-            };
-
+        let mut acquired_websockets = vec![];
+        if !public_topics.is_empty() {
+            acquired_websockets
+                .extend(acquire_websockets(self.clone(), None, public_topics).await?);
+        }
         if let Some(auth) = params.auth {
-            let private_acquired_websockets = match acquire_websockets_for_private_topics(self.clone(), &mut inner, &auth, private_topics)
-                .await {
-                Ok(x) => x,
-                Err(err) => match err {
-                    AcquireWebsocketsForPrivateTopicsError::MaximumAmountOfTopicsForUserWillBeExceeded => {
-                        return Err(AcquireWebsocketForTopicsError::MaximumAmountOfTopicsForUserWillBeExceeded);
-                    }
-                    AcquireWebsocketsForPrivateTopicsError::TungesteniteError(err) => {
-                        return Err(AcquireWebsocketForTopicsError::TungesteniteError(err));
-                    }
-                    AcquireWebsocketsForPrivateTopicsError::CouldNotCreateDataStream(err) => {
-                        return Err(AcquireWebsocketForTopicsError::CouldNotCreateDataStream(err));
-                    }
-                }
-            };
-            acquired_websockets.extend(private_acquired_websockets);
+            if !private_topics.is_empty() {
+                acquired_websockets
+                    .extend(acquire_websockets(self.clone(), Some(auth), private_topics).await?);
+            }
         }
 
         Ok(AcquireWebsocketsForTopicsOutput {
@@ -150,311 +139,266 @@ impl AcquireWebsocketsForTopics for MexcSpotWebsocketClient {
     }
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum AcquireWebsocketsForPublicTopicsError {
-    #[error("Tungestenite error: {0}")]
-    TungesteniteError(#[from] tokio_tungstenite::tungstenite::Error),
-}
-
-async fn acquire_websockets_for_public_topics(
+/// Puts the topics on the websockets of the auth, connecting new websockets when the existing ones
+/// are full. Connecting happens without holding the lock on inner, which is only taken to decide
+/// where the topics go and to commit that decision.
+async fn acquire_websockets(
     this: Arc<MexcSpotWebsocketClient>,
-    inner: &mut Inner,
-    public_topics: Vec<Topic>,
-) -> Result<Vec<AcquiredWebsocket>, AcquireWebsocketsForPublicTopicsError> {
-    // Look for existing websockets that have a subscription to one or
-    // more of these topics.
-
-    let mut matching_websockets = vec![];
-    for websocket_entry in inner.websockets.iter() {
-        let topics = websocket_entry.topics.read().await;
-        let topics_facilitated_by_websocket = topics
-            .iter()
-            .filter(|&t| public_topics.contains(t))
-            .cloned()
-            .collect::<Vec<_>>();
-        if topics_facilitated_by_websocket.is_empty() {
-            continue;
+    auth: Option<WebsocketAuth>,
+    topics: Vec<Topic>,
+) -> Result<Vec<AcquiredWebsocket>, AcquireWebsocketForTopicsError> {
+    let mut new_websockets: Vec<Arc<WebsocketEntry>> = vec![];
+    // Amount of private websockets that were reserved for this acquisition.
+    let mut reserved = 0;
+
+    loop {
+        let missing_websockets = {
+            let mut inner = this.inner.write().await;
+            if this.shutdown_token.is_cancelled() {
+                drop(inner);
+                abort_acquisition(&this, auth.as_ref(), new_websockets, reserved).await;
+                return Err(AcquireWebsocketForTopicsError::ClientShutDown);
+            }
+
+            // A new websocket that already disconnected cannot be reconnected, as it is unknown.
+            let mut connected_websockets = Vec::with_capacity(new_websockets.len());
+            for websocket_entry in new_websockets.drain(..) {
+                if !websocket_entry
+                    .cancellation_token
+                    .read()
+                    .await
+                    .is_cancelled()
+                {
+                    connected_websockets.push(websocket_entry);
+                }
+            }
+            new_websockets = connected_websockets;
+
+            match assign_topics(&this, &inner, auth.as_ref(), &topics, &new_websockets).await {
+                Ok(assignments) => {
+                    let acquired_websockets =
+                        commit_assignments(&this, &mut inner, assignments, &new_websockets).await;
+                    release_reservation(&mut inner, auth.as_ref(), reserved);
+                    return Ok(acquired_websockets);
+                }
+                Err(missing_websockets) => {
+                    if let Some(auth) = &auth {
+                        let amount_of_websockets_for_auth = inner
+                            .websockets
+                            .iter()
+                            .filter(|websocket| websocket.auth.as_ref() == Some(auth))
+                            .count()
+                            + inner.pending_websockets.get(auth).copied().unwrap_or(0);
+                        if amount_of_websockets_for_auth + missing_websockets > 5 {
+                            drop(inner);
+                            abort_acquisition(&this, Some(auth), new_websockets, reserved).await;
+                            return Err(AcquireWebsocketForTopicsError::MaximumAmountOfTopicsForUserWillBeExceeded);
+                        }
+                        // Keeps the listen key around and counts towards the limit while connecting.
+                        *inner.pending_websockets.entry(auth.clone()).or_insert(0) +=
+                            missing_websockets;
+                        reserved += missing_websockets;
+                    }
+                    missing_websockets
+                }
+            }
+        };
+
+        match connect_websockets(this.clone(), auth.as_ref(), missing_websockets).await {
+            Ok(websocket_entries) => new_websockets.extend(websocket_entries),
+            Err(err) => {
+                abort_acquisition(&this, auth.as_ref(), new_websockets, reserved).await;
+                return Err(err);
+            }
         }
-        matching_websockets.push((websocket_entry.clone(), topics_facilitated_by_websocket));
     }
+}
 
-    let topics_not_covered_matching_websockets = public_topics
+/// Decides which websocket facilitates each of the topics, or returns how many websockets are
+/// missing to facilitate all of them.
+async fn assign_topics(
+    this: &MexcSpotWebsocketClient,
+    inner: &Inner,
+    auth: Option<&WebsocketAuth>,
+    topics: &[Topic],
+    new_websockets: &[Arc<WebsocketEntry>],
+) -> Result<Vec<(Arc<WebsocketEntry>, Vec<Topic>)>, usize> {
+    let mut assignments = vec![];
+    for websocket_entry in inner
+        .websockets
         .iter()
-        .filter(|&topic| {
-            matching_websockets
-                .iter()
-                .all(|(_, topics)| !topics.contains(topic))
-        })
-        .cloned()
-        .collect::<Vec<_>>();
-
-    if topics_not_covered_matching_websockets.is_empty() {
-        // We can reuse the websocket(s) that we found.
-        return Ok(matching_websockets
-            .into_iter()
-            .map(|(ws_entry, topics)| AcquiredWebsocket {
-                websocket_entry: ws_entry,
-                for_topics: topics.iter().map(|t| (*t).clone()).collect::<Vec<Topic>>(),
-            })
-            .collect());
+        .filter(|websocket_entry| websocket_entry.auth.as_ref() == auth)
+        .chain(new_websockets.iter())
+    {
+        let topics = websocket_entry.topics.read().await.clone();
+        assignments.push((websocket_entry.clone(), topics, vec![]));
     }
 
-    // Check whether one of the websockets have enough space to accommodate the topics.
-    let mut websocket_that_can_accommodate = None;
-    for websocket in inner.websockets.iter() {
-        if websocket.auth.is_none()
-            && websocket.topics.read().await.len() + public_topics.len() <= 30
+    let mut unassigned_topics = vec![];
+    for topic in topics {
+        // Topics that are already facilitated by a websocket are sent to it again.
+        match assignments
+            .iter_mut()
+            .find(|(_, facilitated_topics, _)| facilitated_topics.contains(topic))
         {
-            websocket_that_can_accommodate = Some(websocket.clone());
-            break;
+            Some((_, _, assigned_topics)) => assigned_topics.push(topic.clone()),
+            None if !unassigned_topics.contains(topic) => unassigned_topics.push(topic.clone()),
+            None => {}
         }
     }
 
-    if let Some(websocket_entry) = websocket_that_can_accommodate {
-        // Check whether this websocket is part of the matching websockets via id
-        if matching_websockets
-            .iter()
-            .any(|(matching_websocket, _)| matching_websocket.id == websocket_entry.id)
-        {
-            // This is an already matching websocket
-            return Ok(matching_websockets
-                .into_iter()
-                .map(|(ws_entry, topics)| {
-                    let mut for_topics =
-                        topics.iter().map(|t| (*t).clone()).collect::<Vec<Topic>>();
-                    if ws_entry.id == websocket_entry.id {
-                        // Extend this websocket with the topics that are not yet covered
-                        for_topics.extend(topics_not_covered_matching_websockets.iter().cloned());
-                    }
-                    AcquiredWebsocket {
-                        websocket_entry: ws_entry,
-                        for_topics,
-                    }
-                })
-                .collect());
-        } else {
-            // This is another socket which we can put the topics onto
-            return Ok([(websocket_entry, topics_not_covered_matching_websockets)]
-                .into_iter()
-                .chain(matching_websockets.into_iter())
-                .map(|(ws_entry, topics)| AcquiredWebsocket {
-                    websocket_entry: ws_entry,
-                    for_topics: topics.iter().map(|t| (*t).clone()).collect::<Vec<Topic>>(),
-                })
-                .collect());
+    let max_topics_per_websocket = this.config.max_topics_per_websocket;
+    for (index, topic) in unassigned_topics.iter().enumerate() {
+        let websocket_with_room = assignments
+            .iter_mut()
+            .find(|(_, facilitated_topics, _)| facilitated_topics.len() < max_topics_per_websocket);
+        match websocket_with_room {
+            Some((_, facilitated_topics, assigned_topics)) => {
+                // Keeps count of the topics when this websocket is considered for the next one.
+                facilitated_topics.push(topic.clone());
+                assigned_topics.push(topic.clone());
+            }
+            None => {
+                let topics_left = unassigned_topics.len() - index;
+                return Err(topics_left.div_ceil(max_topics_per_websocket));
+            }
         }
     }
 
-    // Create new websocket for the topics
-    let websocket_entry = match create_public_websocket(this.clone(), inner).await {
-        Ok(x) => x,
-        Err(err) => match err {
-            CreatePublicWebsocketError::TungesteniteError(err) => {
-                return Err(AcquireWebsocketsForPublicTopicsError::TungesteniteError(
-                    err,
-                ));
-            }
-        },
-    };
-
-    Ok([(websocket_entry, topics_not_covered_matching_websockets)]
+    Ok(assignments
         .into_iter()
-        .chain(matching_websockets.into_iter())
-        .map(|(ws_entry, topics)| AcquiredWebsocket {
-            websocket_entry: ws_entry,
-            for_topics: topics.iter().map(|t| (*t).clone()).collect::<Vec<Topic>>(),
-        })
+        .filter(|(_, _, assigned_topics)| !assigned_topics.is_empty())
+        .map(|(websocket_entry, _, assigned_topics)| (websocket_entry, assigned_topics))
         .collect())
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum AcquireWebsocketsForPrivateTopicsError {
-    #[error("Maximum amount of topics for user will be exceeded")]
-    MaximumAmountOfTopicsForUserWillBeExceeded,
-
-    #[error("Tungestenite error: {0}")]
-    TungesteniteError(#[from] tokio_tungstenite::tungstenite::Error),
-
-    #[error("Could not create datastream (listen key)")]
-    CouldNotCreateDataStream(#[from] ApiError),
-}
-
-async fn acquire_websockets_for_private_topics(
-    this: Arc<MexcSpotWebsocketClient>,
+/// Adds the topics to their websockets and the new websockets that got topics to inner, the other
+/// new websockets are closed.
+async fn commit_assignments(
+    this: &MexcSpotWebsocketClient,
     inner: &mut Inner,
-    auth: &WebsocketAuth,
-    private_topics: Vec<Topic>,
-) -> Result<Vec<AcquiredWebsocket>, AcquireWebsocketsForPrivateTopicsError> {
-    // Look for existing websockets with the same auth, that have a subscription to one or more of
-    // these topics.
-    // If we find one, we can reuse it.
-    // Otherwise, we have to set up a new websocket that we could subscribe/unsubscribe to.
-    // We can assume once we find a topic for a websocket with the same auth, that there won't be
-    // any other left
-    let mut matching_websockets = vec![];
-    for websocket_entry in inner.websockets.iter() {
-        if websocket_entry.auth.as_ref() != Some(auth) {
-            continue;
+    assignments: Vec<(Arc<WebsocketEntry>, Vec<Topic>)>,
+    new_websockets: &[Arc<WebsocketEntry>],
+) -> Vec<AcquiredWebsocket> {
+    for (websocket_entry, assigned_topics) in assignments.iter() {
+        let mut topics = websocket_entry.topics.write().await;
+        for topic in assigned_topics {
+            if !topics.contains(topic) {
+                topics.push(topic.clone());
+            }
         }
-        let topics = websocket_entry.topics.read().await;
-        let topics_facilitated_by_websocket = topics
+    }
+
+    for websocket_entry in new_websockets {
+        let is_used = assignments
             .iter()
-            .filter(|&t| private_topics.contains(t))
-            .cloned()
-            .collect::<Vec<_>>();
-        if topics_facilitated_by_websocket.is_empty() {
+            .any(|(assigned_websocket_entry, _)| assigned_websocket_entry.id == websocket_entry.id);
+        if !is_used {
+            websocket_entry.cancellation_token.read().await.cancel();
             continue;
         }
-        matching_websockets.push((websocket_entry.clone(), topics_facilitated_by_websocket));
+        inner.websockets.push(websocket_entry.clone());
+        this.emit_connection_event(websocket_entry.id, vec![], ConnectionEventKind::Connected);
     }
 
-    let topics_not_covered_matching_websockets = private_topics
-        .iter()
-        .filter(|&topic| {
-            matching_websockets
-                .iter()
-                .all(|(_, topics)| !topics.contains(topic))
+    assignments
+        .into_iter()
+        .map(|(websocket_entry, for_topics)| AcquiredWebsocket {
+            websocket_entry,
+            for_topics,
         })
-        .cloned()
-        .collect::<Vec<_>>();
-
-    if topics_not_covered_matching_websockets.is_empty() {
-        // We can reuse the websocket(s) that we found.
-        return Ok(matching_websockets
-            .into_iter()
-            .map(|(ws_entry, topics)| AcquiredWebsocket {
-                websocket_entry: ws_entry,
-                for_topics: topics.iter().map(|t| (*t).clone()).collect::<Vec<Topic>>(),
-            })
-            .collect());
-    }
+        .collect()
+}
 
-    // Check whether one of the websockets have enough space to accommodate the topics.
-    let mut websocket_that_can_accommodate = None;
-    for websocket in inner.websockets.iter() {
-        if websocket.auth.as_ref() == Some(auth)
-            && websocket.topics.read().await.len() + private_topics.len() <= 30
-        {
-            websocket_that_can_accommodate = Some(websocket.clone());
-            break;
+fn release_reservation(inner: &mut Inner, auth: Option<&WebsocketAuth>, reserved: usize) {
+    let Some(auth) = auth else {
+        return;
+    };
+    if let Some(pending_websockets) = inner.pending_websockets.get_mut(auth) {
+        *pending_websockets = pending_websockets.saturating_sub(reserved);
+        if *pending_websockets == 0 {
+            inner.pending_websockets.remove(auth);
         }
     }
+}
 
-    if let Some(websocket_entry) = websocket_that_can_accommodate {
-        // Check whether this websocket is part of the matching websockets via id
-        if matching_websockets
-            .iter()
-            .any(|(matching_websocket, _)| matching_websocket.id == websocket_entry.id)
-        {
-            // This is an already matching websocket
-            return Ok(matching_websockets
-                .into_iter()
-                .map(|(ws_entry, topics)| {
-                    let mut for_topics =
-                        topics.iter().map(|t| (*t).clone()).collect::<Vec<Topic>>();
-                    if ws_entry.id == websocket_entry.id {
-                        // Extend this websocket with the topics that are not yet covered
-                        for_topics.extend(topics_not_covered_matching_websockets.iter().cloned());
-                    }
-                    AcquiredWebsocket {
-                        websocket_entry: ws_entry,
-                        for_topics,
-                    }
-                })
-                .collect());
-        } else {
-            // This is another socket which we can put the topics onto
-            return Ok([(websocket_entry, topics_not_covered_matching_websockets)]
-                .into_iter()
-                .chain(matching_websockets.into_iter())
-                .map(|(ws_entry, topics)| AcquiredWebsocket {
-                    websocket_entry: ws_entry,
-                    for_topics: topics.iter().map(|t| (*t).clone()).collect::<Vec<Topic>>(),
-                })
-                .collect());
-        }
+/// Closes the websockets that were connected for an acquisition that failed.
+async fn abort_acquisition(
+    this: &MexcSpotWebsocketClient,
+    auth: Option<&WebsocketAuth>,
+    new_websockets: Vec<Arc<WebsocketEntry>>,
+    reserved: usize,
+) {
+    for websocket_entry in new_websockets {
+        websocket_entry.cancellation_token.read().await.cancel();
     }
 
-    // Create new websocket for the topics
-    let websocket_entry = match create_private_websocket(this.clone(), inner, auth.clone()).await {
-        Ok(x) => x,
-        Err(err) => match err {
-            CreatePrivateWebsocketError::MaximumAmountOfTopicsForUserWillBeExceeded => {
-                return Err(AcquireWebsocketsForPrivateTopicsError::MaximumAmountOfTopicsForUserWillBeExceeded);
-            }
-            CreatePrivateWebsocketError::TungesteniteError(err) => {
-                return Err(AcquireWebsocketsForPrivateTopicsError::TungesteniteError(
-                    err,
-                ));
-            }
-            CreatePrivateWebsocketError::CouldNotCreateDataStream(err) => {
-                return Err(AcquireWebsocketsForPrivateTopicsError::CouldNotCreateDataStream(err));
-            }
-        },
+    let unused_listen_key = {
+        let mut inner = this.inner.write().await;
+        release_reservation(&mut inner, auth, reserved);
+        auth.and_then(|auth| take_unused_listen_key(&mut inner, auth))
     };
-
-    Ok([(websocket_entry, topics_not_covered_matching_websockets)]
-        .into_iter()
-        .chain(matching_websockets.into_iter())
-        .map(|(ws_entry, topics)| AcquiredWebsocket {
-            websocket_entry: ws_entry,
-            for_topics: topics.iter().map(|t| (*t).clone()).collect::<Vec<Topic>>(),
-        })
-        .collect())
+    if let (Some(auth), Some(listen_key)) = (auth, unused_listen_key) {
+        delete_listen_key(this, auth, &listen_key).await;
+    }
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum CreatePrivateWebsocketError {
-    #[error("Maximum amount of topics for user will be exceeded")]
-    MaximumAmountOfTopicsForUserWillBeExceeded,
+/// Connects the websockets in parallel, all of them are closed when one fails to connect.
+async fn connect_websockets(
+    this: Arc<MexcSpotWebsocketClient>,
+    auth: Option<&WebsocketAuth>,
+    amount: usize,
+) -> Result<Vec<Arc<WebsocketEntry>>, AcquireWebsocketForTopicsError> {
+    let listen_key = match auth {
+        Some(auth) => Some(acquire_listen_key(this.clone(), auth).await?),
+        None => None,
+    };
 
-    #[error("Tungestenite error: {0}")]
-    TungesteniteError(#[from] tokio_tungstenite::tungstenite::Error),
+    let results = futures::future::join_all(
+        (0..amount).map(|_| connect_websocket(this.clone(), auth.cloned(), listen_key.as_deref())),
+    )
+    .await;
+
+    let mut websocket_entries = Vec::with_capacity(amount);
+    let mut first_error = None;
+    for result in results {
+        match result {
+            Ok(websocket_entry) => websocket_entries.push(websocket_entry),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    if let Some(err) = first_error {
+        for websocket_entry in websocket_entries {
+            websocket_entry.cancellation_token.read().await.cancel();
+        }
+        return Err(err.into());
+    }
 
-    #[error("Could not create datastream (listen key)")]
-    CouldNotCreateDataStream(#[from] ApiError),
+    Ok(websocket_entries)
 }
 
-async fn create_private_websocket(
+/// Connects a websocket and spawns its tasks, it is only used once it is added to inner.
+async fn connect_websocket(
     this: Arc<MexcSpotWebsocketClient>,
-    inner: &mut Inner,
-    auth: WebsocketAuth,
-) -> Result<Arc<WebsocketEntry>, CreatePrivateWebsocketError> {
-    // Check whether we can create a new websocket for the topics
-    let amount_of_websockets_for_auth = inner
-        .websockets
-        .iter()
-        .filter(|websocket| websocket.auth.as_ref() == Some(&auth))
-        .count();
-    if amount_of_websockets_for_auth >= 5 {
-        return Err(CreatePrivateWebsocketError::MaximumAmountOfTopicsForUserWillBeExceeded);
-    }
-
-    tracing::debug!("Creating listen key for private websocket...");
-    let spot_client_with_auth = MexcSpotApiClientWithAuthentication::new(
-        this.spot_api_endpoint.as_ref().clone(),
-        auth.api_key.clone(),
-        auth.secret_key.clone(),
-    );
-    let user_data_stream_output = spot_client_with_auth.create_user_data_stream().await?;
-    tracing::debug!(
-        "Listen key created: {}",
-        &user_data_stream_output.listen_key
-    );
-
+    auth: Option<WebsocketAuth>,
+    listen_key: Option<&str>,
+) -> Result<Arc<WebsocketEntry>, Error> {
     let endpoint_str = this.ws_endpoint.to_string();
-    let ws_url = format!(
-        "{}?listenKey={}",
-        endpoint_str, &user_data_stream_output.listen_key
-    );
+    let ws_url = match listen_key {
+        Some(listen_key) => format!("{}?listenKey={}", endpoint_str, listen_key),
+        None => endpoint_str,
+    };
 
-    let (ws_stream, _) = tokio_tungstenite::connect_async(&ws_url).await?;
+    let ws_stream = this.config.connect(&ws_url).await?;
     let (ws_tx, ws_rx) = ws_stream.split();
-    let (tx, rx) = async_channel::unbounded();
+    let (tx, rx) = this.config.message_channel();
 
-    let cancellation_token = CancellationToken::new();
+    let cancellation_token = this.shutdown_token.child_token();
 
     let websocket_id = Uuid::new_v4();
+    let metrics = Arc::new(WebsocketMetrics::new());
 
     // Spawn all necessary tasks for this websocket...
     spawn_websocket_sender_task(
@@ -463,84 +407,31 @@ async fn create_private_websocket(
         rx,
         cancellation_token.clone(),
         websocket_id,
+        metrics.clone(),
     );
     spawn_websocket_receiver_task(
         this.clone(),
         ws_rx,
         cancellation_token.clone(),
         websocket_id,
+        metrics.clone(),
     );
-    spawn_websocket_ping_task(tx.clone(), cancellation_token.clone());
-    spawn_websocket_keepalive_task(
-        spot_client_with_auth,
-        user_data_stream_output.listen_key.clone(),
-        cancellation_token,
-    );
-
-    inner
-        .auth_to_listen_key_map
-        .insert(auth.clone(), user_data_stream_output.listen_key.clone());
-
-    let websocket_entry = WebsocketEntry {
-        id: websocket_id,
-        auth: Some(auth),
-        listen_key: Some(user_data_stream_output.listen_key),
-        topics: Arc::new(RwLock::new(vec![])),
-        message_tx: Arc::new(RwLock::new(tx)),
-    };
-    let websocket_entry = Arc::new(websocket_entry);
-    inner.websockets.push(websocket_entry.clone());
-
-    Ok(websocket_entry)
-}
-
-#[derive(Debug, thiserror::Error)]
-pub enum CreatePublicWebsocketError {
-    #[error("Tungestenite error: {0}")]
-    TungesteniteError(#[from] tokio_tungstenite::tungstenite::Error),
-}
-
-async fn create_public_websocket(
-    this: Arc<MexcSpotWebsocketClient>,
-    inner: &mut Inner,
-) -> Result<Arc<WebsocketEntry>, CreatePublicWebsocketError> {
-    let endpoint_str = this.ws_endpoint.to_string();
-
-    let (ws_stream, _) = tokio_tungstenite::connect_async(&endpoint_str).await?;
-    let (ws_tx, ws_rx) = ws_stream.split();
-    let (tx, rx) = async_channel::unbounded();
-
-    let cancellation_token = CancellationToken::new();
-
-    let websocket_id = Uuid::new_v4();
-
-    // Spawn all necessary tasks for this websocket...
-    spawn_websocket_sender_task(
-        this.clone(),
-        ws_tx,
-        rx,
-        cancellation_token.clone(),
-        websocket_id,
-    );
-    spawn_websocket_receiver_task(
+    spawn_websocket_ping_task(this.clone(), tx.clone(), cancellation_token.clone());
+    spawn_websocket_watchdog_task(
         this.clone(),
-        ws_rx,
         cancellation_token.clone(),
         websocket_id,
+        metrics.clone(),
     );
-    spawn_websocket_ping_task(tx.clone(), cancellation_token);
 
-    let websocket_entry = WebsocketEntry {
+    Ok(Arc::new(WebsocketEntry {
         id: websocket_id,
-        auth: None,
-        listen_key: None,
+        auth,
         topics: Arc::new(RwLock::new(vec![])),
         message_tx: Arc::new(RwLock::new(tx)),
-    };
-    let websocket_entry = Arc::new(websocket_entry);
-    inner.websockets.push(websocket_entry.clone());
-
-    Ok(websocket_entry)
+        cancellation_token: Arc::new(RwLock::new(cancellation_token)),
+        metrics,
+    }))
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -553,6 +444,9 @@ pub enum ReconnectWebsocketError {
 
     #[error("Resubscribe send error")]
     ResubscribeSendError(#[from] async_channel::SendError<SendableMessage>),
+
+    #[error("Listen key was rejected: {listen_key}")]
+    ListenKeyRejected { listen_key: String },
 }
 
 async fn reconnect_websocket(
@@ -560,29 +454,76 @@ async fn reconnect_websocket(
     websocket_id: Uuid,
 ) -> Result<(), ReconnectWebsocketError> {
     tracing::debug!("Reconnecting websocket with id...: {}", websocket_id);
-    let inner = this.inner.read().await;
-    let websocket = inner
-        .websockets
-        .iter()
-        .find(|ws| ws.id == websocket_id)
-        .ok_or(ReconnectWebsocketError::UnknownWebsocket)?;
+    // Connecting can take up to the connect timeout, so the lock on inner is only held to look up
+    // the websocket and its listen key.
+    let (websocket, listen_key) = {
+        let inner = this.inner.read().await;
+        let websocket = inner
+            .websockets
+            .iter()
+            .find(|ws| ws.id == websocket_id)
+            .cloned()
+            .ok_or(ReconnectWebsocketError::UnknownWebsocket)?;
+        let listen_key = match &websocket.auth {
+            Some(auth) => Some(
+                inner
+                    .listen_keys
+                    .get(auth)
+                    .map(|managed_listen_key| managed_listen_key.listen_key.clone())
+                    .ok_or(ReconnectWebsocketError::UnknownWebsocket)?,
+            ),
+            None => None,
+        };
+        (websocket, listen_key)
+    };
 
     let endpoint_str = this.ws_endpoint.to_string();
-    let ws_url = match &websocket.listen_key {
+    let ws_url = match &listen_key {
         Some(listen_key) => {
             format!("{}?listenKey={}", endpoint_str, listen_key)
         }
         None => endpoint_str,
     };
 
-    let (ws_stream, _) = tokio_tungstenite::connect_async(&ws_url).await?;
+    let ws_stream = match this.config.connect(&ws_url).await {
+        Ok(ws_stream) => ws_stream,
+        Err(Error::Http(response)) if listen_key.is_some() => {
+            tracing::debug!(
+                "Private websocket connection refused with status {}",
+                response.status()
+            );
+            return Err(ReconnectWebsocketError::ListenKeyRejected {
+                listen_key: listen_key.unwrap_or_default(),
+            });
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    // Closed while connecting, e.g. because its last topics were unsubscribed from.
+    let is_known = this
+        .inner
+        .read()
+        .await
+        .websockets
+        .iter()
+        .any(|ws| ws.id == websocket_id);
+    if !is_known {
+        return Err(ReconnectWebsocketError::UnknownWebsocket);
+    }
 
     tracing::debug!("Reconnected websocket with id: {}", websocket_id);
+    this.emit_connection_event(
+        websocket_id,
+        websocket.topics.read().await.clone(),
+        ConnectionEventKind::Reconnected,
+    );
 
     let (ws_tx, ws_rx) = ws_stream.split();
-    let (tx, rx) = async_channel::unbounded();
+    let (tx, rx) = this.config.message_channel();
 
-    let cancellation_token = CancellationToken::new();
+    let cancellation_token = this.shutdown_token.child_token();
+    let metrics = websocket.metrics.clone();
+    metrics.record_reconnected();
 
     // Spawn all necessary tasks for this websocket...
     spawn_websocket_sender_task(
@@ -591,36 +532,23 @@ async fn reconnect_websocket(
         rx,
         cancellation_token.clone(),
         websocket_id,
+        metrics.clone(),
     );
     spawn_websocket_receiver_task(
         this.clone(),
         ws_rx,
         cancellation_token.clone(),
         websocket_id,
+        metrics.clone(),
     );
-    spawn_websocket_ping_task(tx.clone(), cancellation_token.clone());
-    if let Some(listen_key) = &websocket.listen_key {
-        let spot_client_with_auth = MexcSpotApiClientWithAuthentication::new(
-            this.spot_api_endpoint.as_ref().clone(),
-            websocket
-                .auth
-                .as_ref()
-                .expect("Listen key set but not auth?")
-                .api_key
-                .clone(),
-            websocket
-                .auth
-                .as_ref()
-                .expect("Listen key set but not auth?")
-                .secret_key
-                .clone(),
-        );
-        spawn_websocket_keepalive_task(
-            spot_client_with_auth,
-            listen_key.clone(),
-            cancellation_token.clone(),
-        );
-    }
+    spawn_websocket_ping_task(this.clone(), tx.clone(), cancellation_token.clone());
+    spawn_websocket_watchdog_task(
+        this.clone(),
+        cancellation_token.clone(),
+        websocket_id,
+        metrics.clone(),
+    );
+    *websocket.cancellation_token.write().await = cancellation_token;
 
     let mut message_tx = websocket.message_tx.write().await;
     *message_tx = tx;
@@ -638,22 +566,189 @@ async fn reconnect_websocket(
             "Resubscribed to all topics for websocket with id: {}",
             websocket_id
         );
+        this.emit_connection_event(
+            websocket_id,
+            topics.clone(),
+            ConnectionEventKind::Resubscribed,
+        );
     }
 
     Ok(())
 }
 
+/// Cancels the tasks of the websocket, returns false when they were already cancelled so that a
+/// disconnect is only handled once.
+pub(crate) fn cancel_websocket(cancellation_token: &CancellationToken) -> bool {
+    let was_cancelled = cancellation_token.is_cancelled();
+    cancellation_token.cancel();
+    !was_cancelled
+}
+
+pub(crate) async fn handle_disconnect(
+    this: Arc<MexcSpotWebsocketClient>,
+    websocket_id: Uuid,
+    cause: DisconnectCause,
+) {
+    let topics = this.websocket_topics(websocket_id).await;
+    let is_reconnectable = cause.is_reconnectable();
+    this.emit_connection_event(
+        websocket_id,
+        topics.clone(),
+        ConnectionEventKind::Disconnected(cause),
+    );
+    if this.shutdown_token.is_cancelled() {
+        // Shutting down takes care of the websockets.
+        return;
+    }
+    if !is_reconnectable {
+        // Nothing would facilitate the topics anymore, they get a new websocket when they are
+        // subscribed to again.
+        abandon_websocket(this.clone(), websocket_id).await;
+        this.emit_connection_event(
+            websocket_id,
+            topics,
+            ConnectionEventKind::ReconnectAbandoned { attempts: 0 },
+        );
+        return;
+    }
+
+    reconnect_with_policy(this, websocket_id, topics).await;
+}
+
+/// Keeps reconnecting the websocket until it succeeds or the reconnect policy gives up.
+pub(crate) fn reconnect_with_policy(
+    this: Arc<MexcSpotWebsocketClient>,
+    websocket_id: Uuid,
+    topics: Vec<Topic>,
+) -> BoxFuture<'static, ()> {
+    // Boxed, as reconnecting might renew the listen key which reconnects websockets again.
+    async move {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            if !this.config.reconnect_policy.allows_attempt(attempt) {
+                tracing::error!(
+                    "Giving up reconnecting websocket with id {} after {} attempts",
+                    websocket_id,
+                    attempt - 1
+                );
+                abandon_websocket(this.clone(), websocket_id).await;
+                this.emit_connection_event(
+                    websocket_id,
+                    topics,
+                    ConnectionEventKind::ReconnectAbandoned {
+                        attempts: attempt - 1,
+                    },
+                );
+                return;
+            }
+
+            tokio::select! {
+                _ = this.shutdown_token.cancelled() => {
+                    return;
+                }
+                _ = tokio::time::sleep(this.config.reconnect_policy.delay(attempt)) => {}
+            }
+            this.emit_connection_event(
+                websocket_id,
+                topics.clone(),
+                ConnectionEventKind::Reconnecting { attempt },
+            );
+            match reconnect_websocket(this.clone(), websocket_id).await {
+                Ok(_) => return,
+                Err(ReconnectWebsocketError::UnknownWebsocket) => {
+                    // The websocket was closed in the meantime, nothing to reconnect.
+                    return;
+                }
+                Err(ReconnectWebsocketError::ListenKeyRejected { listen_key }) => {
+                    tracing::error!("Listen key was rejected while reconnecting, recreating it");
+                    this.emit_connection_event(
+                        websocket_id,
+                        topics.clone(),
+                        ConnectionEventKind::ReconnectFailed {
+                            attempt,
+                            error: format!("Listen key was rejected: {}", listen_key),
+                        },
+                    );
+                    if let Some(auth) = websocket_auth(&this, websocket_id).await {
+                        renew_listen_key(
+                            this.clone(),
+                            &auth,
+                            &listen_key,
+                            DisconnectCause::ListenKeyRejected,
+                        )
+                        .await;
+                    }
+                }
+                Err(err) => {
+                    tracing::error!("Failed to reconnect websocket: {}", err);
+                    this.emit_connection_event(
+                        websocket_id,
+                        topics.clone(),
+                        ConnectionEventKind::ReconnectFailed {
+                            attempt,
+                            error: err.to_string(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+    .boxed()
+}
+
+/// Removes a websocket that could not be reconnected, so that its topics get a new websocket when
+/// they are subscribed to again.
+async fn abandon_websocket(this: Arc<MexcSpotWebsocketClient>, websocket_id: Uuid) {
+    let (websocket_entry, unused_listen_key) = {
+        let mut inner = this.inner.write().await;
+        let Some(index) = inner
+            .websockets
+            .iter()
+            .position(|websocket_entry| websocket_entry.id == websocket_id)
+        else {
+            return;
+        };
+        let websocket_entry = inner.websockets.remove(index);
+        let unused_listen_key = websocket_entry
+            .auth
+            .as_ref()
+            .and_then(|auth| take_unused_listen_key(&mut inner, auth));
+        (websocket_entry, unused_listen_key)
+    };
+
+    websocket_entry.cancellation_token.read().await.cancel();
+    if let (Some(auth), Some(listen_key)) = (&websocket_entry.auth, &unused_listen_key) {
+        delete_listen_key(&this, auth, listen_key).await;
+    }
+}
+
+async fn websocket_auth(
+    this: &MexcSpotWebsocketClient,
+    websocket_id: Uuid,
+) -> Option<WebsocketAuth> {
+    let inner = this.inner.read().await;
+    inner
+        .websockets
+        .iter()
+        .find(|websocket_entry| websocket_entry.id == websocket_id)
+        .and_then(|websocket_entry| websocket_entry.auth.clone())
+}
+
 fn spawn_websocket_sender_task(
     this: Arc<MexcSpotWebsocketClient>,
     mut ws_tx: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
     rx: async_channel::Receiver<SendableMessage>,
     cancellation_token: CancellationToken,
     websocket_id: Uuid,
+    metrics: Arc<WebsocketMetrics>,
 ) {
-    tokio::spawn(async move {
+    this.clone().spawn_task(async move {
         loop {
             tokio::select! {
                 _ = cancellation_token.cancelled() => {
+                    // Let the server know we are going, the connection might already be gone.
+                    let _ = tokio::time::timeout(CLOSE_TIMEOUT, ws_tx.close()).await;
                     break;
                 }
                 message_result = rx.recv() => {
@@ -665,52 +760,18 @@ fn spawn_websocket_sender_task(
                             break;
                         }
                     };
+                    if matches!(message, SendableMessage::Ping) {
+                        metrics.record_ping_sent();
+                    }
                     let json = serde_json::to_string(&message).expect("Failed to serialize message");
                     let message = Message::Text(json);
 
-                    match ws_tx.send(message).await {
-                        Ok(_) => {}
-                        Err(err) => match err {
-                            Error::ConnectionClosed => {
-                                cancellation_token.cancel();
-                                tracing::error!("Failed to send message to websocket because the connection was closed");
-                                if let Err(err) = reconnect_websocket(this.clone(), websocket_id).await {
-                                    tracing::error!("Failed to reconnect websocket: {}", err);
-                                }
-                                break;
-                            }
-                            Error::AlreadyClosed => {
-                                cancellation_token.cancel();
-                                tracing::error!("Failed to send message to websocket because the connection was already closed");
-                                if let Err(err) = reconnect_websocket(this.clone(), websocket_id).await {
-                                    tracing::error!("Failed to reconnect websocket: {}", err);
-                                }
-                                break;
-                            }
-                            Error::Protocol(protocol_err) => match protocol_err {
-                                ProtocolError::ResetWithoutClosingHandshake => {
-                                    cancellation_token.cancel();
-                                    tracing::error!("Failed to send message to websocket because the connection was reset without closing handshake");
-                                    if let Err(err) = reconnect_websocket(this.clone(), websocket_id).await {
-                                        tracing::error!("Failed to reconnect websocket: {}", err);
-                                    }
-                                    break;
-                                }
-                                _ => {
-                                    cancellation_token.cancel();
-                                    tracing::error!(
-                                        "Protocol error sending message to websocket: {}",
-                                        protocol_err
-                                    );
-                                    break;
-                                }
-                            },
-                            _ => {
-                                cancellation_token.cancel();
-                                tracing::error!("Error sending message to websocket: {}", err);
-                                break;
-                            }
-                        },
+                    if let Err(err) = ws_tx.send(message).await {
+                        tracing::error!("Failed to send message to websocket: {}", err);
+                        if cancel_websocket(&cancellation_token) {
+                            handle_disconnect(this.clone(), websocket_id, DisconnectCause::from(&err)).await;
+                        }
+                        break;
                     }
                 }
             }
@@ -723,9 +784,11 @@ fn spawn_websocket_receiver_task(
     mut ws_rx: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
     cancellation_token: CancellationToken,
     websocket_id: Uuid,
+    metrics: Arc<WebsocketMetrics>,
 ) {
     let broadcast_tx = this.broadcast_tx.clone();
-    tokio::spawn(async move {
+    let id_code_tx = this.id_code_tx.clone();
+    this.clone().spawn_task(async move {
         loop {
             tokio::select! {
                 _ = cancellation_token.cancelled() => {
@@ -735,78 +798,36 @@ fn spawn_websocket_receiver_task(
                     let message_result = match message_result_opt {
                         Some(x) => x,
                         None => {
-                            cancellation_token.cancel();
+                            if cancel_websocket(&cancellation_token) {
+                                handle_disconnect(this.clone(), websocket_id, DisconnectCause::StreamEnded).await;
+                            }
                             break;
                         }
                     };
                     let message = match message_result {
                         Ok(message) => message,
-                        Err(err) => match err {
-                            Error::ConnectionClosed => {
-                                cancellation_token.cancel();
-                                tracing::error!("Failed to receive message from websocket because the connection was closed");
-                                if let Err(err) = reconnect_websocket(this.clone(), websocket_id).await {
-                                    tracing::error!("Failed to reconnect websocket: {}", err);
-                                }
-                                break;
-                            }
-                            Error::AlreadyClosed => {
-                                cancellation_token.cancel();
-                                tracing::error!("Failed to receive message from websocket because the connection was already closed");
-                                if let Err(err) = reconnect_websocket(this.clone(), websocket_id).await {
-                                    tracing::error!("Failed to reconnect websocket: {}", err);
-                                }
-                                break;
-                            }
-                            Error::Protocol(protocol_err) => match protocol_err {
-                                ProtocolError::ResetWithoutClosingHandshake => {
-                                    cancellation_token.cancel();
-                                    tracing::error!("Failed to receive message from websocket because the connection was reset without closing handshake");
-                                    if let Err(err) = reconnect_websocket(this.clone(), websocket_id).await {
-                                        tracing::error!("Failed to reconnect websocket: {}", err);
-                                    }
-                                    break;
-                                }
-                                _ => {
-                                    cancellation_token.cancel();
-                                    tracing::error!(
-                                        "Protocol error receiving message from websocket: {}",
-                                        protocol_err
-                                    );
-                                    break;
-                                }
-                            },
-                            _ => {
-                                cancellation_token.cancel();
-                                tracing::error!("Error receiving message from websocket: {}", err);
-                                break;
+                        Err(err) => {
+                            tracing::error!("Failed to receive message from websocket: {}", err);
+                            if cancel_websocket(&cancellation_token) {
+                                handle_disconnect(this.clone(), websocket_id, DisconnectCause::from(&err)).await;
                             }
-                        },
+                            break;
+                        }
                     };
+                    metrics.record_frame();
 
-                    let mex_msg = match message {
+                    let parsed_frame = match message {
                         Message::Text(text) => {
-                            let raw_message = match serde_json::from_str::<message::RawMessage>(&text) {
-                                Ok(x) => x,
-                                Err(err) => {
-                                    cancellation_token.cancel();
-                                    tracing::error!("Failed to deserialize message: {}\njson: {}", err, &text);
-                                    break;
-                                }
-                            };
-
-                            let Ok(mexc_message) = message::Message::try_from(&raw_message) else {
-                                tracing::trace!("Received unrecognized message: {raw_message:?}");
-                                continue;
-                            };
-                            mexc_message
-                        },
-                        Message::Binary(proto) => {
-                            let Ok(mexc_message) = message::Message::from_proto(&proto)  else {
-                                tracing::trace!("Fail to parse binary message: {}", hex::encode(&proto));
-                                continue;
-                            };
-                            mexc_message
+                            if let Some(frame_recorder) = &this.config.frame_recorder {
+                                frame_recorder.record(websocket_id, RecordedFrameData::Text(text.clone()));
+                            }
+                            message::parse_text_frame(text)
+                        }
+                        Message::Binary(data) => {
+                            if let Some(frame_recorder) = &this.config.frame_recorder {
+                                frame_recorder.record(websocket_id, RecordedFrameData::Binary(data.clone()));
+                            }
+                            message::parse_binary_frame(data)
                         }
                         _ => {
                             tracing::debug!("Received non-text message: {:?}", message);
@@ -814,16 +835,29 @@ fn spawn_websocket_receiver_task(
                         }
                     };
 
-                     match broadcast_tx.send(Arc::new(mex_msg)) {
-                        Ok(_) => {}
-                        Err(err) => {
-                            // cancellation_token.cancel();
-                            tracing::error!("Failed to broadcast message: {}", err);
-                            break;
+                    let mex_msg = match parsed_frame {
+                        message::ParsedFrame::IdCode(id_code_message) => {
+                            if id_code_message.message == "PONG" {
+                                metrics.record_pong();
+                            }
+                            // Replies to our own requests, nobody might be waiting for them.
+                            let _ = id_code_tx.send((websocket_id, Arc::new(id_code_message)));
+                            continue;
                         }
-                    }
-
+                        message::ParsedFrame::Message { message, channel } => {
+                            metrics.record_topic_message(&channel);
+                            message
+                        }
+                        message::ParsedFrame::Unknown(unknown_message) => {
+                            this.parse_errors.fetch_add(1, Ordering::Relaxed);
+                            message::Message::Unknown(unknown_message)
+                        }
+                    };
 
+                    let mex_msg = Arc::new(mex_msg);
+                    // There might not be anyone listening
+                    let _ = broadcast_tx.send(mex_msg.clone());
+                    this.deliver_to_queued_consumers(mex_msg, &cancellation_token).await;
                 }
             }
         }
@@ -831,16 +865,18 @@ fn spawn_websocket_receiver_task(
 }
 
 fn spawn_websocket_ping_task(
+    this: Arc<MexcSpotWebsocketClient>,
     sender: Sender<SendableMessage>,
     cancellation_token: CancellationToken,
 ) {
-    tokio::spawn(async move {
+    let ping_interval = this.config.ping_interval;
+    this.clone().spawn_task(async move {
         loop {
             tokio::select! {
                 _ = cancellation_token.cancelled() => {
                     break;
                 }
-                _ = tokio::time::sleep(std::time::Duration::from_secs(30)) => {
+                _ = tokio::time::sleep(ping_interval) => {
                     match sender.send(SendableMessage::Ping).await {
                         Ok(_) => {}
                         Err(err) => {
@@ -855,33 +891,61 @@ fn spawn_websocket_ping_task(
     });
 }
 
-fn spawn_websocket_keepalive_task(
-    spot_client_with_auth: MexcSpotApiClientWithAuthentication,
-    listen_key: String,
-    cancellation_token: CancellationToken,
-) {
-    tokio::spawn(async move {
-        loop {
-            tokio::select! {
-                _ = cancellation_token.cancelled() => {
-                    break;
-                }
-                _ = tokio::time::sleep(std::time::Duration::from_secs(60 * 30)) => {
-                    match spot_client_with_auth
-                        .keep_alive_user_data_stream(KeepAliveUserDataStreamParams {
-                            listen_key: &listen_key,
-                        })
-                        .await
-                    {
-                        Ok(_) => {}
-                        Err(err) => {
-                            cancellation_token.cancel();
-                            tracing::error!("Failed to keep alive user data stream: {}", err);
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-    });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::ws::topic::DealsTopic;
+
+    fn websocket_entry(topics: Vec<Topic>) -> Arc<WebsocketEntry> {
+        Arc::new(WebsocketEntry {
+            id: Uuid::new_v4(),
+            auth: None,
+            topics: Arc::new(RwLock::new(topics)),
+            message_tx: Arc::new(RwLock::new(async_channel::unbounded().0)),
+            cancellation_token: Arc::new(RwLock::new(CancellationToken::new())),
+            metrics: Arc::new(WebsocketMetrics::new()),
+        })
+    }
+
+    fn deals_topic(symbol: &str) -> Topic {
+        Topic::Deals(DealsTopic::new(symbol.to_string()))
+    }
+
+    #[tokio::test]
+    async fn assign_topics_fills_websockets_before_connecting_new_ones() {
+        let ws_client = MexcSpotWebsocketClient::builder()
+            .with_max_topics_per_websocket(2)
+            .build();
+        let existing_websocket = websocket_entry(vec![deals_topic("BTCUSDT")]);
+        ws_client
+            .inner
+            .write()
+            .await
+            .websockets
+            .push(existing_websocket.clone());
+        let topics = ["BTCUSDT", "ETHUSDT", "SOLUSDT", "XRPUSDT"]
+            .into_iter()
+            .map(deals_topic)
+            .collect::<Vec<_>>();
+
+        let inner = ws_client.inner.read().await;
+        let missing_websockets = assign_topics(&ws_client, &inner, None, &topics, &[]).await;
+        assert!(matches!(missing_websockets, Err(1)));
+
+        let new_websocket = websocket_entry(vec![]);
+        let assignments = assign_topics(
+            &ws_client,
+            &inner,
+            None,
+            &topics,
+            std::slice::from_ref(&new_websocket),
+        )
+        .await
+        .unwrap();
+        assert_eq!(assignments.len(), 2);
+        assert_eq!(assignments[0].0.id, existing_websocket.id);
+        assert_eq!(assignments[0].1, topics[..2]);
+        assert_eq!(assignments[1].0.id, new_websocket.id);
+        assert_eq!(assignments[1].1, topics[2..]);
+    }
 }
@@ -0,0 +1,234 @@
+use crate::spot::ws::endpoint::MexcWebsocketEndpoint;
+use crate::spot::ws::reconnect_policy::ReconnectPolicy;
+use crate::spot::ws::recorder::FrameRecorder;
+use crate::spot::ws::watchdog::WatchdogPolicy;
+use crate::spot::ws::{MexcSpotWebsocketClient, SendableMessage};
+use crate::spot::MexcSpotApiEndpoint;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderMap, HeaderName, HeaderValue};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// Settings of the websocket client, see [`MexcSpotWebsocketClientBuilder`].
+#[derive(Debug, Clone)]
+pub(crate) struct WebsocketConfig {
+    pub max_topics_per_websocket: usize,
+    pub ping_interval: Duration,
+    pub listen_key_keepalive_interval: Duration,
+    pub message_channel_capacity: Option<usize>,
+    pub connect_timeout: Option<Duration>,
+    pub headers: HeaderMap,
+    pub reconnect_policy: ReconnectPolicy,
+    pub watchdog_policy: WatchdogPolicy,
+    pub frame_recorder: Option<Arc<FrameRecorder>>,
+}
+
+impl Default for WebsocketConfig {
+    fn default() -> Self {
+        Self {
+            max_topics_per_websocket: 30,
+            ping_interval: Duration::from_secs(30),
+            listen_key_keepalive_interval: Duration::from_secs(60 * 30),
+            message_channel_capacity: None,
+            connect_timeout: None,
+            headers: HeaderMap::new(),
+            reconnect_policy: ReconnectPolicy::default(),
+            watchdog_policy: WatchdogPolicy::default(),
+            frame_recorder: None,
+        }
+    }
+}
+
+impl WebsocketConfig {
+    /// Channel for the messages that are sent to a websocket.
+    pub fn message_channel(
+        &self,
+    ) -> (
+        async_channel::Sender<SendableMessage>,
+        async_channel::Receiver<SendableMessage>,
+    ) {
+        match self.message_channel_capacity {
+            Some(capacity) => async_channel::bounded(capacity.max(1)),
+            None => async_channel::unbounded(),
+        }
+    }
+
+    pub async fn connect(
+        &self,
+        url: &str,
+    ) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, tokio_tungstenite::tungstenite::Error>
+    {
+        let mut request = url.into_client_request()?;
+        for (name, value) in self.headers.iter() {
+            request.headers_mut().insert(name, value.clone());
+        }
+
+        let connect = tokio_tungstenite::connect_async(request);
+        let (ws_stream, _) = match self.connect_timeout {
+            Some(connect_timeout) => tokio::time::timeout(connect_timeout, connect)
+                .await
+                .map_err(|_| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        "Timed out connecting to websocket",
+                    )
+                })??,
+            None => connect.await?,
+        };
+
+        Ok(ws_stream)
+    }
+}
+
+#[derive(Debug)]
+pub struct MexcSpotWebsocketClientBuilder {
+    ws_endpoint: MexcWebsocketEndpoint,
+    spot_api_endpoint: MexcSpotApiEndpoint,
+    broadcast_capacity: usize,
+    /// Derived from the ping interval when not given.
+    watchdog_policy: Option<WatchdogPolicy>,
+    config: WebsocketConfig,
+}
+
+impl Default for MexcSpotWebsocketClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MexcSpotWebsocketClientBuilder {
+    pub fn new() -> Self {
+        Self {
+            ws_endpoint: MexcWebsocketEndpoint::Base,
+            spot_api_endpoint: MexcSpotApiEndpoint::Base,
+            broadcast_capacity: 1024,
+            watchdog_policy: None,
+            config: WebsocketConfig::default(),
+        }
+    }
+
+    pub fn with_ws_endpoint(mut self, ws_endpoint: MexcWebsocketEndpoint) -> Self {
+        self.ws_endpoint = ws_endpoint;
+        self
+    }
+
+    pub fn with_spot_api_endpoint(mut self, spot_api_endpoint: MexcSpotApiEndpoint) -> Self {
+        self.spot_api_endpoint = spot_api_endpoint;
+        self
+    }
+
+    /// Amount of topics per connection, the exchange allows up to 30.
+    pub fn with_max_topics_per_websocket(mut self, max_topics_per_websocket: usize) -> Self {
+        self.config.max_topics_per_websocket = max_topics_per_websocket.max(1);
+        self
+    }
+
+    pub fn with_ping_interval(mut self, ping_interval: Duration) -> Self {
+        self.config.ping_interval = ping_interval;
+        self
+    }
+
+    /// Listen keys expire after 60 minutes without being kept alive.
+    pub fn with_listen_key_keepalive_interval(
+        mut self,
+        listen_key_keepalive_interval: Duration,
+    ) -> Self {
+        self.config.listen_key_keepalive_interval = listen_key_keepalive_interval;
+        self
+    }
+
+    /// Amount of messages the shared broadcast channel holds before slow consumers start lagging.
+    pub fn with_broadcast_capacity(mut self, broadcast_capacity: usize) -> Self {
+        self.broadcast_capacity = broadcast_capacity.max(1);
+        self
+    }
+
+    /// Amount of outgoing messages that can be queued per connection, unbounded by default.
+    pub fn with_message_channel_capacity(mut self, message_channel_capacity: usize) -> Self {
+        self.config.message_channel_capacity = Some(message_channel_capacity);
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.config.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Header that is sent along with every connection request.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.config.headers.insert(name, value);
+        self
+    }
+
+    pub fn with_reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.config.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Reconnects websockets that stop receiving data without the connection being closed. By
+    /// default a websocket is reconnected after three ping intervals without receiving anything,
+    /// see [`WatchdogPolicy::for_ping_interval`].
+    pub fn with_watchdog_policy(mut self, watchdog_policy: WatchdogPolicy) -> Self {
+        self.watchdog_policy = Some(watchdog_policy);
+        self
+    }
+
+    /// Records every text and binary frame the websockets receive, to be played back with a
+    /// [`FrameReplayer`](crate::spot::ws::recorder::FrameReplayer). Keep a clone of the recorder
+    /// to see how many frames it had to drop.
+    pub fn with_frame_recorder(mut self, frame_recorder: FrameRecorder) -> Self {
+        self.config.frame_recorder = Some(Arc::new(frame_recorder));
+        self
+    }
+
+    pub fn build(mut self) -> MexcSpotWebsocketClient {
+        self.config.watchdog_policy = self
+            .watchdog_policy
+            .unwrap_or_else(|| WatchdogPolicy::for_ping_interval(self.config.ping_interval));
+        MexcSpotWebsocketClient::new_with_config(
+            self.ws_endpoint,
+            self.spot_api_endpoint,
+            self.broadcast_capacity,
+            self.config,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounded_message_channel() {
+        let ws_client = MexcSpotWebsocketClientBuilder::new()
+            .with_message_channel_capacity(2)
+            .build();
+        let (tx, _rx) = ws_client.config.message_channel();
+        assert_eq!(tx.capacity(), Some(2));
+
+        let (tx, _rx) = WebsocketConfig::default().message_channel();
+        assert_eq!(tx.capacity(), None);
+    }
+
+    #[test]
+    fn watchdog_window_follows_ping_interval() {
+        let ws_client = MexcSpotWebsocketClientBuilder::new()
+            .with_ping_interval(Duration::from_secs(120))
+            .build();
+        assert_eq!(
+            ws_client.config.watchdog_policy.default_window,
+            Some(Duration::from_secs(360))
+        );
+
+        let ws_client = MexcSpotWebsocketClientBuilder::new()
+            .with_watchdog_policy(WatchdogPolicy::new(Some(Duration::from_secs(10))))
+            .with_ping_interval(Duration::from_secs(120))
+            .build();
+        assert_eq!(
+            ws_client.config.watchdog_policy.default_window,
+            Some(Duration::from_secs(10))
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use crate::spot::ws::topic::Topic;
+use crate::spot::ws::MexcSpotWebsocketClient;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_tungstenite::tungstenite::error::ProtocolError;
+use tokio_tungstenite::tungstenite::Error;
+use uuid::Uuid;
+
+/// A change in the state of one of the websocket connections.
+#[derive(Debug, Clone)]
+pub struct ConnectionEvent {
+    pub websocket_id: Uuid,
+    /// Topics that are facilitated by the websocket at the time of the event.
+    pub topics: Vec<Topic>,
+    pub kind: ConnectionEventKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConnectionEventKind {
+    /// A new websocket connection was established.
+    Connected,
+    /// The connection was lost, messages for its topics are not received until it reconnects.
+    Disconnected(DisconnectCause),
+    /// Reconnecting the websocket, the first attempt is 1.
+    Reconnecting {
+        attempt: u32,
+    },
+    Reconnected,
+    ReconnectFailed {
+        attempt: u32,
+        error: String,
+    },
+    /// The reconnect policy ran out of attempts, the websocket is removed and its topics are no
+    /// longer facilitated.
+    ReconnectAbandoned {
+        attempts: u32,
+    },
+    /// The topics of the websocket were subscribed to again after reconnecting.
+    Resubscribed,
+    /// The connection was closed on purpose, e.g. because it has no topics left.
+    Closed,
+    /// The consumer fell behind and the given amount of events of any of the websockets were
+    /// skipped, so the state of the connections is unknown until their next events. The
+    /// websocket id is nil and there are no topics.
+    Lagged {
+        skipped: u64,
+    },
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum DisconnectCause {
+    #[error("Connection closed")]
+    ConnectionClosed,
+
+    #[error("Connection already closed")]
+    AlreadyClosed,
+
+    #[error("Connection reset without closing handshake")]
+    ResetWithoutClosingHandshake,
+
+    #[error("Websocket stream ended")]
+    StreamEnded,
+
+    /// Nothing was received within the window of the watchdog policy.
+    #[error("No frames received for {0:?}")]
+    Stale(std::time::Duration),
+
+    #[error("Protocol error: {0}")]
+    ProtocolError(String),
+
+    #[error("Websocket error: {0}")]
+    WebsocketError(String),
+
+    #[error("Failed to keep alive listen key: {0}")]
+    KeepAliveFailed(String),
+
+    #[error("Listen key was rejected")]
+    ListenKeyRejected,
+}
+
+impl DisconnectCause {
+    /// Whether the connection is reestablished after disconnecting for this cause. Every transport
+    /// failure is, a rejected listen key has to be renewed before the websockets can reconnect.
+    pub fn is_reconnectable(&self) -> bool {
+        !matches!(self, DisconnectCause::ListenKeyRejected)
+    }
+}
+
+impl From<&Error> for DisconnectCause {
+    fn from(err: &Error) -> Self {
+        match err {
+            Error::ConnectionClosed => DisconnectCause::ConnectionClosed,
+            Error::AlreadyClosed => DisconnectCause::AlreadyClosed,
+            Error::Protocol(ProtocolError::ResetWithoutClosingHandshake) => {
+                DisconnectCause::ResetWithoutClosingHandshake
+            }
+            Error::Protocol(protocol_err) => {
+                DisconnectCause::ProtocolError(protocol_err.to_string())
+            }
+            _ => DisconnectCause::WebsocketError(err.to_string()),
+        }
+    }
+}
+
+pub trait ConnectionEvents {
+    /// Events of all websockets. A consumer that falls behind receives a
+    /// [`ConnectionEventKind::Lagged`] event in place of the skipped ones.
+    fn connection_events<'a>(self: Arc<Self>) -> BoxStream<'a, ConnectionEvent>;
+}
+
+impl ConnectionEvents for MexcSpotWebsocketClient {
+    fn connection_events<'a>(self: Arc<Self>) -> BoxStream<'a, ConnectionEvent> {
+        let mut rx = self.connection_event_tx.subscribe();
+        let stream = async_stream::stream! {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => yield event,
+                    Err(RecvError::Lagged(skipped)) => yield ConnectionEvent {
+                        websocket_id: Uuid::nil(),
+                        topics: vec![],
+                        kind: ConnectionEventKind::Lagged { skipped },
+                    },
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        };
+        stream.boxed()
+    }
+}
+
+impl MexcSpotWebsocketClient {
+    pub(crate) async fn websocket_topics(&self, websocket_id: Uuid) -> Vec<Topic> {
+        let websocket_entry = {
+            let inner = self.inner.read().await;
+            inner
+                .websockets
+                .iter()
+                .find(|websocket_entry| websocket_entry.id == websocket_id)
+                .cloned()
+        };
+        match websocket_entry {
+            Some(websocket_entry) => websocket_entry.topics.read().await.clone(),
+            None => vec![],
+        }
+    }
+
+    pub(crate) fn emit_connection_event(
+        &self,
+        websocket_id: Uuid,
+        topics: Vec<Topic>,
+        kind: ConnectionEventKind,
+    ) {
+        // There might not be anyone listening
+        let _ = self.connection_event_tx.send(ConnectionEvent {
+            websocket_id,
+            topics,
+            kind,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disconnect_cause_from_error() {
+        assert!(DisconnectCause::from(&Error::ConnectionClosed).is_reconnectable());
+        assert!(DisconnectCause::from(&Error::Protocol(
+            ProtocolError::ResetWithoutClosingHandshake
+        ))
+        .is_reconnectable());
+        let cause = DisconnectCause::from(&Error::Protocol(ProtocolError::WrongHttpMethod));
+        assert!(matches!(cause, DisconnectCause::ProtocolError(_)));
+        assert!(cause.is_reconnectable());
+        let cause = DisconnectCause::from(&Error::Io(std::io::Error::from(
+            std::io::ErrorKind::ConnectionReset,
+        )));
+        assert!(matches!(cause, DisconnectCause::WebsocketError(_)));
+        assert!(cause.is_reconnectable());
+        assert!(!DisconnectCause::ListenKeyRejected.is_reconnectable());
+    }
+
+    #[tokio::test]
+    async fn connection_events_report_lag() {
+        let ws_client = MexcSpotWebsocketClient::default().into_arc();
+        let mut connection_events = ws_client.clone().connection_events();
+        for _ in 0..1030 {
+            ws_client.emit_connection_event(Uuid::new_v4(), vec![], ConnectionEventKind::Closed);
+        }
+
+        let event = connection_events.next().await.unwrap();
+        assert_eq!(event.websocket_id, Uuid::nil());
+        assert!(matches!(
+            event.kind,
+            ConnectionEventKind::Lagged { skipped: 6 }
+        ));
+        assert!(matches!(
+            connection_events.next().await.unwrap().kind,
+            ConnectionEventKind::Closed
+        ));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::spot::ws::topic::Topic;
+use crate::spot::ws::MexcSpotWebsocketClient;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct HealthOutput {
+    pub websockets: Vec<WebsocketHealth>,
+    /// Amount of frames that could not be parsed, across all websockets.
+    pub parse_errors: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct WebsocketHealth {
+    pub websocket_id: Uuid,
+    pub is_private: bool,
+    pub topics: Vec<Topic>,
+    /// Time since the current connection was established, `None` while disconnected.
+    pub uptime: Option<Duration>,
+    pub last_message_at: Option<DateTime<Utc>>,
+    /// Time since any frame was received on the current connection.
+    pub since_last_message: Option<Duration>,
+    /// Round trip time of the last answered ping.
+    pub ping_rtt: Option<Duration>,
+    /// Amount of messages received per channel, which matches the subscription string of the
+    /// topic, e.g. `spot@public.aggre.deals.v3.api.pb@100ms@BTCUSDT`.
+    pub messages_by_topic: HashMap<String, u64>,
+    pub reconnects: u32,
+}
+
+#[async_trait]
+pub trait Health {
+    /// Reports the health of every websocket connection, to tell a quiet topic apart from a
+    /// connection that stopped receiving data.
+    async fn health(&self) -> HealthOutput;
+}
+
+#[async_trait]
+impl Health for MexcSpotWebsocketClient {
+    async fn health(&self) -> HealthOutput {
+        let websockets = {
+            let inner = self.inner.read().await;
+            inner.websockets.clone()
+        };
+
+        let mut websocket_healths = Vec::with_capacity(websockets.len());
+        for websocket_entry in websockets {
+            let is_connected = !websocket_entry
+                .cancellation_token
+                .read()
+                .await
+                .is_cancelled();
+            let topics = websocket_entry.topics.read().await.clone();
+            let snapshot = websocket_entry.metrics.snapshot();
+            websocket_healths.push(WebsocketHealth {
+                websocket_id: websocket_entry.id,
+                is_private: websocket_entry.auth.is_some(),
+                topics,
+                uptime: is_connected.then(|| snapshot.connected_at.elapsed()),
+                last_message_at: snapshot.last_message_at,
+                since_last_message: snapshot.last_message_instant.map(|at| at.elapsed()),
+                ping_rtt: snapshot.ping_rtt,
+                messages_by_topic: snapshot.messages_by_topic,
+                reconnects: snapshot.reconnects,
+            });
+        }
+
+        HealthOutput {
+            websockets: websocket_healths,
+            parse_errors: self.parse_error_count(),
+        }
+    }
+}
+
+/// Metrics of a websocket that are kept across reconnects.
+#[derive(Debug)]
+pub(crate) struct WebsocketMetrics {
+    state: Mutex<MetricsState>,
+}
+
+#[derive(Debug, Clone)]
+struct MetricsState {
+    connected_at: Instant,
+    reconnects: u32,
+    last_message_at: Option<DateTime<Utc>>,
+    last_message_instant: Option<Instant>,
+    ping_sent_at: Option<Instant>,
+    ping_rtt: Option<Duration>,
+    messages_by_topic: HashMap<String, u64>,
+}
+
+impl WebsocketMetrics {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(MetricsState {
+                connected_at: Instant::now(),
+                reconnects: 0,
+                last_message_at: None,
+                last_message_instant: None,
+                ping_sent_at: None,
+                ping_rtt: None,
+                messages_by_topic: HashMap::new(),
+            }),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MetricsState> {
+        self.state.lock().expect("Websocket metrics lock poisoned")
+    }
+
+    fn snapshot(&self) -> MetricsState {
+        self.state().clone()
+    }
+
+    pub fn record_reconnected(&self) {
+        let mut state = self.state();
+        state.connected_at = Instant::now();
+        state.reconnects += 1;
+        state.last_message_instant = None;
+        state.ping_sent_at = None;
+    }
+
+    /// Any frame received, including replies and frames that could not be parsed.
+    pub fn record_frame(&self) {
+        let mut state = self.state();
+        state.last_message_at = Some(Utc::now());
+        state.last_message_instant = Some(Instant::now());
+    }
+
+    pub fn record_topic_message(&self, channel: &str) {
+        let mut state = self.state();
+        match state.messages_by_topic.get_mut(channel) {
+            Some(count) => *count += 1,
+            None => {
+                state.messages_by_topic.insert(channel.to_string(), 1);
+            }
+        }
+    }
+
+    pub fn record_ping_sent(&self) {
+        self.state().ping_sent_at = Some(Instant::now());
+    }
+
+    pub fn record_pong(&self) {
+        let mut state = self.state();
+        if let Some(ping_sent_at) = state.ping_sent_at.take() {
+            state.ping_rtt = Some(ping_sent_at.elapsed());
+        }
+    }
+
+    /// Time since the last frame, or since connecting when nothing was received yet.
+    pub fn idle_time(&self) -> Duration {
+        let state = self.state();
+        state
+            .last_message_instant
+            .unwrap_or(state.connected_at)
+            .elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_record_messages_and_ping_rtt() {
+        let metrics = WebsocketMetrics::new();
+        metrics.record_pong();
+        assert!(metrics.snapshot().ping_rtt.is_none());
+
+        metrics.record_ping_sent();
+        metrics.record_frame();
+        metrics.record_pong();
+        metrics.record_topic_message("spot@public.deals.v3.api@BTCUSDT");
+        metrics.record_topic_message("spot@public.deals.v3.api@BTCUSDT");
+
+        let snapshot = metrics.snapshot();
+        assert!(snapshot.ping_rtt.is_some());
+        assert!(snapshot.last_message_at.is_some());
+        assert_eq!(
+            snapshot
+                .messages_by_topic
+                .get("spot@public.deals.v3.api@BTCUSDT"),
+            Some(&2)
+        );
+
+        metrics.record_reconnected();
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.reconnects, 1);
+        assert!(snapshot.last_message_instant.is_none());
+    }
+
+    #[tokio::test]
+    async fn health_without_websockets() {
+        let ws_client = MexcSpotWebsocketClient::default();
+        let output = ws_client.health().await;
+        assert!(output.websockets.is_empty());
+        assert_eq!(output.parse_errors, 0);
+    }
+}
@@ -0,0 +1,413 @@
+use crate::spot::v3::create_user_data_stream::CreateUserDataStreamEndpoint;
+use crate::spot::v3::delete_user_data_stream::{
+    DeleteUserDataStreamEndpoint, DeleteUserDataStreamParams,
+};
+use crate::spot::v3::get_user_data_streams::GetUserDataStreamsEndpoint;
+use crate::spot::v3::keep_alive_user_data_stream::{
+    KeepAliveUserDataStreamEndpoint, KeepAliveUserDataStreamParams,
+};
+use crate::spot::v3::{ApiError, ErrorCode};
+use crate::spot::ws::acquire_websocket::{cancel_websocket, reconnect_with_policy};
+use crate::spot::ws::auth::WebsocketAuth;
+use crate::spot::ws::connection_event::{ConnectionEventKind, DisconnectCause};
+use crate::spot::ws::{Inner, MexcSpotWebsocketClient};
+use crate::spot::MexcSpotApiClientWithAuthentication;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Listen key that is shared by all private websockets of an auth.
+#[derive(Debug)]
+pub(crate) struct ManagedListenKey {
+    pub listen_key: String,
+    /// Whether this client created the listen key. Reused listen keys might be used by other
+    /// processes with the same api key, so only owned ones are deleted.
+    pub owned: bool,
+    /// Stops the keep alive task of the listen key.
+    pub cancellation_token: CancellationToken,
+}
+
+fn spot_client_with_auth(
+    this: &MexcSpotWebsocketClient,
+    auth: &WebsocketAuth,
+) -> MexcSpotApiClientWithAuthentication {
+    MexcSpotApiClientWithAuthentication::new(
+        this.spot_api_endpoint.as_ref().clone(),
+        auth.api_key.clone(),
+        auth.secret_key.clone(),
+    )
+}
+
+/// Returns the listen key of the auth. A listen key that is still valid from a previous run is
+/// reused before creating a new one, as there is a limit of 60 listen keys per user. The lock on
+/// inner is not held while talking to the api.
+pub(crate) async fn acquire_listen_key(
+    this: Arc<MexcSpotWebsocketClient>,
+    auth: &WebsocketAuth,
+) -> Result<String, ApiError> {
+    if let Some(listen_key) = current_listen_key(&this, auth).await {
+        return Ok(listen_key);
+    }
+
+    let spot_client_with_auth = spot_client_with_auth(&this, auth);
+    let (listen_key, owned) = match find_reusable_listen_key(&spot_client_with_auth).await {
+        Some(listen_key) => {
+            tracing::debug!("Reusing listen key: {}", listen_key);
+            (listen_key, false)
+        }
+        None => {
+            tracing::debug!("Creating listen key for private websocket...");
+            let output = spot_client_with_auth.create_user_data_stream().await?;
+            tracing::debug!("Listen key created: {}", &output.listen_key);
+            (output.listen_key, true)
+        }
+    };
+
+    let existing_listen_key = {
+        let mut inner = this.inner.write().await;
+        match inner.listen_keys.get(auth) {
+            Some(managed_listen_key) => Some(managed_listen_key.listen_key.clone()),
+            None => {
+                let cancellation_token = this.shutdown_token.child_token();
+                spawn_listen_key_keepalive_task(
+                    this.clone(),
+                    auth.clone(),
+                    cancellation_token.clone(),
+                );
+                inner.listen_keys.insert(
+                    auth.clone(),
+                    ManagedListenKey {
+                        listen_key: listen_key.clone(),
+                        owned,
+                        cancellation_token,
+                    },
+                );
+                None
+            }
+        }
+    };
+    match existing_listen_key {
+        // Acquired concurrently in the meantime, the one we got is not needed.
+        Some(existing_listen_key) => {
+            if owned && existing_listen_key != listen_key {
+                delete_listen_key(&this, auth, &listen_key).await;
+            }
+            Ok(existing_listen_key)
+        }
+        None => Ok(listen_key),
+    }
+}
+
+async fn find_reusable_listen_key(
+    spot_client_with_auth: &MexcSpotApiClientWithAuthentication,
+) -> Option<String> {
+    let output = match spot_client_with_auth.get_user_data_streams().await {
+        Ok(output) => output,
+        Err(err) => {
+            tracing::debug!("Failed to list existing listen keys: {}", err);
+            return None;
+        }
+    };
+
+    for listen_key in output.listen_keys {
+        // Only listen keys that can be kept alive are still valid.
+        let result = spot_client_with_auth
+            .keep_alive_user_data_stream(KeepAliveUserDataStreamParams {
+                listen_key: &listen_key,
+            })
+            .await;
+        if result.is_ok() {
+            return Some(listen_key);
+        }
+    }
+
+    None
+}
+
+fn spawn_listen_key_keepalive_task(
+    this: Arc<MexcSpotWebsocketClient>,
+    auth: WebsocketAuth,
+    cancellation_token: CancellationToken,
+) {
+    let spot_client_with_auth = spot_client_with_auth(&this, &auth);
+    let keepalive_interval = this.config.listen_key_keepalive_interval;
+    this.clone().spawn_task(async move {
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    break;
+                }
+                _ = tokio::time::sleep(keepalive_interval) => {}
+            }
+
+            let Some(listen_key) = current_listen_key(&this, &auth).await else {
+                break;
+            };
+            match spot_client_with_auth
+                .keep_alive_user_data_stream(KeepAliveUserDataStreamParams {
+                    listen_key: &listen_key,
+                })
+                .await
+            {
+                Ok(_) => {
+                    tracing::trace!("Listen key kept alive: {}", listen_key);
+                }
+                Err(err) if is_listen_key_rejection(&err) => {
+                    tracing::error!("Listen key was rejected, recreating it: {}", err);
+                    renew_listen_key(
+                        this.clone(),
+                        &auth,
+                        &listen_key,
+                        DisconnectCause::KeepAliveFailed(err.to_string()),
+                    )
+                    .await;
+                }
+                Err(err) => {
+                    // The listen key is probably still valid, it is kept alive on the next tick.
+                    tracing::warn!("Failed to keep alive listen key, retrying later: {}", err);
+                }
+            }
+        }
+    });
+}
+
+/// Whether the api answered that the listen key is invalid or expired, as opposed to a failure
+/// that might go away by itself, like a network error or an overloaded server.
+fn is_listen_key_rejection(err: &ApiError) -> bool {
+    match err {
+        ApiError::ErrorResponse(response) => !matches!(
+            response.code,
+            ErrorCode::TooManyRequests
+                | ErrorCode::InternalError
+                | ErrorCode::ServiceUnavailable
+                | ErrorCode::GatewayTimeout
+                | ErrorCode::InvalidResponse
+        ),
+        _ => false,
+    }
+}
+
+pub(crate) async fn current_listen_key(
+    this: &MexcSpotWebsocketClient,
+    auth: &WebsocketAuth,
+) -> Option<String> {
+    let inner = this.inner.read().await;
+    inner
+        .listen_keys
+        .get(auth)
+        .map(|managed_listen_key| managed_listen_key.listen_key.clone())
+}
+
+/// Replaces a listen key that expired or was rejected, and reconnects the websockets of the auth
+/// with the new listen key. Nothing happens when the listen key was already replaced. When the
+/// reconnect policy runs out of attempts to create a new listen key, the websockets of the auth are
+/// removed.
+pub(crate) async fn renew_listen_key(
+    this: Arc<MexcSpotWebsocketClient>,
+    auth: &WebsocketAuth,
+    stale_listen_key: &str,
+    cause: DisconnectCause,
+) {
+    if current_listen_key(&this, auth).await.as_deref() != Some(stale_listen_key) {
+        return;
+    }
+
+    let spot_client_with_auth = spot_client_with_auth(&this, auth);
+    let mut attempt = 0;
+    let listen_key = loop {
+        attempt += 1;
+        match spot_client_with_auth.create_user_data_stream().await {
+            Ok(output) => break output.listen_key,
+            Err(err) => {
+                tracing::error!("Failed to recreate listen key: {}", err);
+                if !this.config.reconnect_policy.allows_attempt(attempt + 1) {
+                    tracing::error!("Giving up recreating listen key after {} attempts", attempt);
+                    abandon_listen_key(&this, auth, stale_listen_key, attempt).await;
+                    return;
+                }
+                tokio::select! {
+                    _ = this.shutdown_token.cancelled() => {
+                        return;
+                    }
+                    _ = tokio::time::sleep(this.config.reconnect_policy.delay(attempt)) => {}
+                }
+            }
+        }
+    };
+    tracing::debug!(
+        "Listen key {} replaced by {}",
+        stale_listen_key,
+        &listen_key
+    );
+
+    let replaced = {
+        let mut inner = this.inner.write().await;
+        match inner.listen_keys.get_mut(auth) {
+            Some(managed_listen_key) if managed_listen_key.listen_key == stale_listen_key => {
+                let was_owned = managed_listen_key.owned;
+                managed_listen_key.listen_key = listen_key.clone();
+                managed_listen_key.owned = true;
+                let websockets = inner
+                    .websockets
+                    .iter()
+                    .filter(|websocket_entry| websocket_entry.auth.as_ref() == Some(auth))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                Some((websockets, was_owned))
+            }
+            _ => None,
+        }
+    };
+    let Some((websockets, was_owned)) = replaced else {
+        // Released or replaced in the meantime, the new listen key is not needed.
+        delete_listen_key(&this, auth, &listen_key).await;
+        return;
+    };
+    if was_owned {
+        delete_listen_key(&this, auth, stale_listen_key).await;
+    }
+
+    for websocket_entry in websockets {
+        let cancellation_token = websocket_entry.cancellation_token.read().await.clone();
+        if !cancel_websocket(&cancellation_token) {
+            // Already disconnected, it picks up the new listen key when reconnecting.
+            continue;
+        }
+        let topics = websocket_entry.topics.read().await.clone();
+        this.emit_connection_event(
+            websocket_entry.id,
+            topics.clone(),
+            ConnectionEventKind::Disconnected(cause.clone()),
+        );
+        let websocket_id = websocket_entry.id;
+        this.clone()
+            .spawn_task(reconnect_with_policy(this.clone(), websocket_id, topics));
+    }
+}
+
+/// Stops managing a listen key that could not be replaced, and removes the websockets that used it.
+async fn abandon_listen_key(
+    this: &MexcSpotWebsocketClient,
+    auth: &WebsocketAuth,
+    stale_listen_key: &str,
+    attempts: u32,
+) {
+    let websockets = {
+        let mut inner = this.inner.write().await;
+        let is_current = inner
+            .listen_keys
+            .get(auth)
+            .is_some_and(|managed_listen_key| managed_listen_key.listen_key == stale_listen_key);
+        if !is_current {
+            return;
+        }
+        if let Some(managed_listen_key) = inner.listen_keys.remove(auth) {
+            managed_listen_key.cancellation_token.cancel();
+        }
+        let (websockets, remaining_websockets) = std::mem::take(&mut inner.websockets)
+            .into_iter()
+            .partition::<Vec<_>, _>(|websocket_entry| {
+            websocket_entry.auth.as_ref() == Some(auth)
+        });
+        inner.websockets = remaining_websockets;
+        websockets
+    };
+
+    for websocket_entry in websockets {
+        websocket_entry.cancellation_token.read().await.cancel();
+        let topics = websocket_entry.topics.read().await.clone();
+        this.emit_connection_event(
+            websocket_entry.id,
+            topics,
+            ConnectionEventKind::ReconnectAbandoned { attempts },
+        );
+    }
+}
+
+/// Stops managing the listen key of the auth when no websocket uses it anymore. A listen key is
+/// only returned when this client created it, it should be deleted once the lock on inner is
+/// released.
+pub(crate) fn take_unused_listen_key(inner: &mut Inner, auth: &WebsocketAuth) -> Option<String> {
+    if inner.pending_websockets.contains_key(auth)
+        || inner
+            .websockets
+            .iter()
+            .any(|websocket_entry| websocket_entry.auth.as_ref() == Some(auth))
+    {
+        return None;
+    }
+
+    let managed_listen_key = inner.listen_keys.remove(auth)?;
+    managed_listen_key.cancellation_token.cancel();
+    managed_listen_key
+        .owned
+        .then_some(managed_listen_key.listen_key)
+}
+
+pub(crate) async fn delete_listen_key(
+    this: &MexcSpotWebsocketClient,
+    auth: &WebsocketAuth,
+    listen_key: &str,
+) {
+    match spot_client_with_auth(this, auth)
+        .delete_user_data_stream(DeleteUserDataStreamParams { listen_key })
+        .await
+    {
+        Ok(_) => {
+            tracing::debug!("Listen key deleted: {}", listen_key);
+        }
+        Err(err) => {
+            tracing::error!("Failed to delete listen key: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::v3::ErrorResponse;
+    use std::collections::HashMap;
+
+    #[test]
+    fn only_owned_listen_keys_are_deleted() {
+        let auth = WebsocketAuth::new("key".to_string(), "secret".to_string());
+        let mut inner = Inner {
+            listen_keys: HashMap::new(),
+            websockets: vec![],
+            pending_websockets: HashMap::new(),
+        };
+        for owned in [false, true] {
+            let cancellation_token = CancellationToken::new();
+            inner.listen_keys.insert(
+                auth.clone(),
+                ManagedListenKey {
+                    listen_key: "listen-key".to_string(),
+                    owned,
+                    cancellation_token: cancellation_token.clone(),
+                },
+            );
+            let listen_key = take_unused_listen_key(&mut inner, &auth);
+            assert_eq!(listen_key.is_some(), owned);
+            assert!(cancellation_token.is_cancelled());
+            assert!(inner.listen_keys.is_empty());
+        }
+    }
+
+    #[test]
+    fn only_rejections_renew_the_listen_key() {
+        let error_response = |code| {
+            ApiError::ErrorResponse(ErrorResponse {
+                code,
+                msg: String::new(),
+                _extend: None,
+            })
+        };
+        assert!(is_listen_key_rejection(&error_response(
+            ErrorCode::YourInputParamIsInvalidOrParameterError
+        )));
+        assert!(!is_listen_key_rejection(&error_response(
+            ErrorCode::ServiceUnavailable
+        )));
+        assert!(!is_listen_key_rejection(&ApiError::InternalServerError));
+        assert!(!is_listen_key_rejection(&ApiError::RateLimitExceeded));
+    }
+}
@@ -0,0 +1,420 @@
+use crate::order_book::{
+    spawn_fetch_task, DepthDiff, GapRecovery, MaintainedBooks, OrderBookLevel, SymbolSync,
+    SyncAction,
+};
+use crate::spot::v3::depth::{DepthEndpoint, DepthOutput, DepthParams, PriceAndQuantity};
+use crate::spot::ws::message::Message;
+use crate::spot::ws::stream::{Stream, StreamItem};
+use crate::spot::ws::subscribe::{RejectedTopic, Subscribe, SubscribeError, SubscribeParams};
+use crate::spot::ws::topic::{DepthTopic, DepthTopicFrequency, Topic};
+use crate::spot::ws::unsubscribe::{Unsubscribe, UnsubscribeError, UnsubscribeParams};
+use crate::spot::ws::MexcSpotWebsocketClient;
+use crate::spot::MexcSpotApiClient;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug)]
+pub struct LocalOrderBookParams {
+    pub symbols: Vec<String>,
+    /// Amount of levels requested for the snapshot, default 1000; max 5000. `None` leaves it to
+    /// the api, which returns 100 levels.
+    pub depth_limit: Option<u32>,
+    pub frequency: DepthTopicFrequency,
+}
+
+impl Default for LocalOrderBookParams {
+    fn default() -> Self {
+        Self::new(Vec::new(), Some(1000), DepthTopicFrequency::Freq100ms)
+    }
+}
+
+impl LocalOrderBookParams {
+    pub fn new(
+        symbols: Vec<String>,
+        depth_limit: Option<u32>,
+        frequency: DepthTopicFrequency,
+    ) -> Self {
+        Self {
+            symbols,
+            depth_limit,
+            frequency,
+        }
+    }
+
+    pub fn with_symbol(mut self, symbol: String) -> Self {
+        self.symbols.push(symbol);
+        self
+    }
+
+    pub fn with_symbols(mut self, symbols: Vec<String>) -> Self {
+        self.symbols.extend(symbols);
+        self
+    }
+
+    pub fn with_depth_limit(mut self, depth_limit: u32) -> Self {
+        self.depth_limit = Some(depth_limit);
+        self
+    }
+
+    pub fn with_frequency(mut self, frequency: DepthTopicFrequency) -> Self {
+        self.frequency = frequency;
+        self
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LocalOrderBookError {
+    #[error("Subscribe error: {0}")]
+    SubscribeError(#[from] SubscribeError),
+
+    #[error("Depth topics were rejected: {0:?}")]
+    TopicsRejected(Vec<RejectedTopic>),
+}
+
+/// L2 order book of a single symbol.
+pub type OrderBook = crate::order_book::OrderBook<PriceAndQuantity>;
+
+impl OrderBook {
+    pub fn from_snapshot(symbol: String, snapshot: &DepthOutput) -> Self {
+        Self::new(
+            symbol,
+            snapshot.last_update_id,
+            &snapshot.bids,
+            &snapshot.asks,
+        )
+    }
+}
+
+impl OrderBookLevel for PriceAndQuantity {
+    fn price(&self) -> Decimal {
+        self.price
+    }
+
+    fn quantity(&self) -> Decimal {
+        self.quantity
+    }
+}
+
+/// Depth diffs of the message along with their symbol.
+fn depth_diffs(message: &Message) -> Vec<(String, DepthDiff<PriceAndQuantity>)> {
+    match message {
+        Message::AggregatedDepth(depth) => vec![(
+            depth.symbol.clone(),
+            DepthDiff {
+                from_version: depth.from_version,
+                to_version: depth.to_version,
+                asks: depth.asks.clone(),
+                bids: depth.bids.clone(),
+            },
+        )],
+        Message::OrderbookUpdate(update) => vec![(
+            update.symbol.clone(),
+            DepthDiff {
+                from_version: update.version,
+                to_version: update.version,
+                asks: update.asks.clone(),
+                bids: update.bids.clone(),
+            },
+        )],
+        Message::OrderbookUpdateBatch(batch) => batch
+            .updates
+            .iter()
+            .map(|update| {
+                (
+                    update.symbol.clone(),
+                    DepthDiff {
+                        from_version: update.version,
+                        to_version: update.version,
+                        asks: update.asks.clone(),
+                        bids: update.bids.clone(),
+                    },
+                )
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Maintains L2 order books by combining depth snapshots of the REST api with the depth diffs
+/// of the websocket, resyncing from a new snapshot whenever a version gap is detected.
+///
+/// Dropping it stops maintaining the order books, but only [`LocalOrderBook::stop`] unsubscribes
+/// from the depth topics.
+#[derive(Debug)]
+pub struct LocalOrderBook {
+    ws_client: Arc<MexcSpotWebsocketClient>,
+    /// Depth topics that were not subscribed to before, the others are left alone when stopping.
+    topics: Vec<Topic>,
+    books: MaintainedBooks<PriceAndQuantity>,
+}
+
+impl LocalOrderBook {
+    pub async fn new(
+        ws_client: Arc<MexcSpotWebsocketClient>,
+        params: LocalOrderBookParams,
+    ) -> Result<Self, LocalOrderBookError> {
+        let topics = params
+            .symbols
+            .iter()
+            .map(|symbol| {
+                Topic::Depth(DepthTopic {
+                    symbol: symbol.clone(),
+                    frequency: params.frequency.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let subscribed_topics = subscribed_public_topics(&ws_client).await;
+        let added_topics = topics
+            .iter()
+            .filter(|topic| !subscribed_topics.contains(topic))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // Listen before subscribing so that no diff gets lost.
+        let messages = ws_client.clone().stream_with_lag();
+        let subscribe_output = ws_client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topics(topics))
+            .await?;
+        if !subscribe_output.rejected_topics.is_empty() {
+            return Err(LocalOrderBookError::TopicsRejected(
+                subscribe_output.rejected_topics,
+            ));
+        }
+
+        let local_order_book = Self {
+            ws_client,
+            topics: added_topics,
+            books: MaintainedBooks::new(CancellationToken::new()),
+        };
+        spawn_local_order_book_task(&local_order_book, messages, params);
+
+        Ok(local_order_book)
+    }
+
+    /// The order book of the symbol, `None` while it is (re)syncing.
+    pub async fn order_book(&self, symbol: &str) -> Option<Arc<OrderBook>> {
+        self.books.get(symbol).await
+    }
+
+    /// Every order book after an update was applied. A consumer that falls behind skips the
+    /// pending updates and receives the current book of every symbol instead.
+    pub fn stream(&self) -> BoxStream<'static, Arc<OrderBook>> {
+        self.books.stream()
+    }
+
+    /// Stops maintaining the order books and unsubscribes from the depth topics it subscribed
+    /// to, topics that were already subscribed to stay subscribed.
+    pub async fn stop(&self) -> Result<(), UnsubscribeError> {
+        self.books.stop().await;
+        self.ws_client
+            .clone()
+            .unsubscribe(UnsubscribeParams::default().with_topics(self.topics.clone()))
+            .await?;
+
+        Ok(())
+    }
+}
+
+async fn subscribed_public_topics(ws_client: &MexcSpotWebsocketClient) -> Vec<Topic> {
+    let inner = ws_client.inner.read().await;
+    let mut topics = vec![];
+    for websocket_entry in inner.websockets.iter() {
+        if websocket_entry.auth.is_none() {
+            topics.extend(websocket_entry.topics.read().await.iter().cloned());
+        }
+    }
+    topics
+}
+
+fn spawn_local_order_book_task(
+    local_order_book: &LocalOrderBook,
+    mut messages: BoxStream<'static, StreamItem>,
+    params: LocalOrderBookParams,
+) {
+    let ws_client = local_order_book.ws_client.clone();
+    let publisher = local_order_book.books.publisher();
+    let cancellation_token = local_order_book.books.cancellation_token().clone();
+    let spot_client = MexcSpotApiClient::new(ws_client.spot_api_endpoint.as_ref().clone());
+    let depth_limit = params.depth_limit;
+
+    let (snapshot_tx, mut snapshot_rx) = mpsc::unbounded_channel::<(String, DepthOutput)>();
+    let fetch_snapshot = move |symbol: String| {
+        let spot_client = spot_client.clone();
+        let fetch_symbol = symbol.clone();
+        spawn_fetch_task(
+            symbol,
+            "depth snapshot",
+            move || {
+                let spot_client = spot_client.clone();
+                let symbol = fetch_symbol.clone();
+                async move {
+                    spot_client
+                        .depth(DepthParams {
+                            symbol: &symbol,
+                            limit: depth_limit,
+                        })
+                        .await
+                }
+            },
+            snapshot_tx.clone(),
+            cancellation_token.child_token(),
+        );
+    };
+    let cancellation_token = local_order_book.books.cancellation_token().clone();
+
+    tokio::spawn(async move {
+        let mut states = params
+            .symbols
+            .into_iter()
+            .map(|symbol| (symbol, SymbolSync::new(GapRecovery::Snapshot)))
+            .collect::<HashMap<_, _>>();
+        for symbol in states.keys() {
+            fetch_snapshot(symbol.clone());
+        }
+
+        loop {
+            let (symbol, action) = tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    break;
+                }
+                Some((symbol, snapshot)) = snapshot_rx.recv() => {
+                    let Some(state) = states.get_mut(&symbol) else {
+                        continue;
+                    };
+                    let action = state.on_snapshot(OrderBook::from_snapshot(symbol.clone(), &snapshot));
+                    (symbol, action)
+                }
+                item_opt = messages.next() => {
+                    let message = match item_opt {
+                        Some(StreamItem::Message(message)) => message,
+                        Some(StreamItem::Lagged { skipped }) => {
+                            // Diffs might have been skipped, so every book is suspect.
+                            tracing::warn!(
+                                "Depth stream skipped {} messages, resyncing all order books",
+                                skipped
+                            );
+                            publisher.clear().await;
+                            for (symbol, state) in states.iter_mut() {
+                                state.reset();
+                                fetch_snapshot(symbol.clone());
+                            }
+                            continue;
+                        }
+                        None => break,
+                    };
+
+                    let mut published_symbol = None;
+                    for (symbol, diff) in depth_diffs(&message) {
+                        let Some(state) = states.get_mut(&symbol) else {
+                            continue;
+                        };
+                        match state.on_diff(diff) {
+                            SyncAction::None => {}
+                            SyncAction::Publish => published_symbol = Some(symbol),
+                            SyncAction::FetchSnapshot | SyncAction::FetchMissedDiffs => {
+                                publisher.remove(&symbol).await;
+                                fetch_snapshot(symbol);
+                                published_symbol = None;
+                            }
+                        }
+                    }
+                    let Some(symbol) = published_symbol else {
+                        continue;
+                    };
+                    (symbol, SyncAction::Publish)
+                }
+            };
+
+            match action {
+                SyncAction::None => {}
+                SyncAction::Publish => {
+                    if let Some(book) = states.get(&symbol).and_then(|state| state.book()) {
+                        publisher.publish(book).await;
+                    }
+                }
+                SyncAction::FetchSnapshot | SyncAction::FetchMissedDiffs => {
+                    publisher.remove(&symbol).await;
+                    fetch_snapshot(symbol);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::ws::mock_server::MockMexcWebsocketServer;
+    use crate::spot::MexcSpotApiEndpoint;
+    use std::time::Duration;
+
+    fn depth_topic(symbol: &str) -> Topic {
+        Topic::Depth(DepthTopic {
+            symbol: symbol.to_string(),
+            frequency: DepthTopicFrequency::Freq100ms,
+        })
+    }
+
+    #[tokio::test]
+    async fn stop_leaves_topics_that_were_already_subscribed() {
+        let server = MockMexcWebsocketServer::start().await.unwrap();
+        let ws_client = MexcSpotWebsocketClient::builder()
+            .with_ws_endpoint(server.endpoint())
+            .with_spot_api_endpoint(MexcSpotApiEndpoint::Custom(
+                "http://127.0.0.1:1".to_string(),
+            ))
+            .build()
+            .into_arc();
+        ws_client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(depth_topic("BTCUSDT")))
+            .await
+            .unwrap();
+
+        let local_order_book = LocalOrderBook::new(
+            ws_client.clone(),
+            LocalOrderBookParams::default()
+                .with_symbols(vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(local_order_book.topics, vec![depth_topic("ETHUSDT")]);
+        local_order_book.stop().await.unwrap();
+
+        assert!(
+            server
+                .wait_until(Duration::from_secs(5), |server| server.subscribed_topics()
+                    == vec![depth_topic("BTCUSDT").to_topic_subscription_string()])
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn drop_stops_the_tasks() {
+        let server = MockMexcWebsocketServer::start().await.unwrap();
+        let ws_client = MexcSpotWebsocketClient::builder()
+            .with_ws_endpoint(server.endpoint())
+            .with_spot_api_endpoint(MexcSpotApiEndpoint::Custom(
+                "http://127.0.0.1:1".to_string(),
+            ))
+            .build()
+            .into_arc();
+        let local_order_book = LocalOrderBook::new(
+            ws_client,
+            LocalOrderBookParams::default().with_symbol("BTCUSDT".to_string()),
+        )
+        .await
+        .unwrap();
+        let cancellation_token = local_order_book.books.cancellation_token().clone();
+
+        drop(local_order_book);
+        assert!(cancellation_token.is_cancelled());
+    }
+}
@@ -1,32 +1,57 @@
 use self::orderbook_update::{
-    channel_message_to_spot_orderbook_update_message, OrderbookUpdateMessage, RawOrderData,
+    channel_message_to_spot_orderbook_update_message, proto_to_orderbook_update_message,
+    OrderbookUpdateMessage, RawOrderData,
 };
-use crate::proto::push_data_v3_api_wrapper::Body::PublicAggreDepths;
-use crate::proto::{PublicAggreDepthsV3Api, PushDataV3ApiWrapper};
+use crate::proto::push_data_v3_api_wrapper::Body;
+use crate::proto::{PublicBookTickerV3Api, PushDataV3ApiWrapper};
 use crate::spot::ws::message::account_deals::{
-    channel_message_to_account_deals_message, AccountDealsMessage, RawAccountDealsData,
+    channel_message_to_account_deals_message, proto_to_account_deals_message, AccountDealsMessage,
+    RawAccountDealsData,
 };
 use crate::spot::ws::message::account_orders::{
-    channel_message_to_account_orders_message, AccountOrdersMessage,
-    RawAccountOrdersChannelMessageData,
+    channel_message_to_account_orders_message, proto_to_account_orders_message,
+    AccountOrdersMessage, RawAccountOrdersChannelMessageData,
 };
 use crate::spot::ws::message::account_update::{
-    channel_message_to_account_update_message, AccountUpdateMessage, RawAccountUpdateData,
+    channel_message_to_account_update_message, proto_to_account_update_message,
+    AccountUpdateMessage, RawAccountUpdateData,
+};
+use crate::spot::ws::message::aggregated_depth::{
+    proto_to_aggregated_depth_message, AggregatedDepthMessage,
+};
+use crate::spot::ws::message::book_ticker::{
+    proto_to_book_ticker_batch_message, proto_to_book_ticker_message, BookTickerBatchMessage,
+    BookTickerMessage,
 };
 use crate::spot::ws::message::deals::{
-    channel_message_to_spot_deals_message, RawSpotDealData, SpotDealsMessage,
+    channel_message_to_spot_deals_message, proto_to_spot_deals_message, RawSpotDealData,
+    SpotDealsMessage,
 };
 use crate::spot::ws::message::kline::{
-    channel_message_to_spot_kline_message, RawKlineData, SpotKlineMessage,
+    channel_message_to_spot_kline_message, proto_to_spot_kline_message, RawKlineData,
+    SpotKlineMessage,
+};
+use crate::spot::ws::message::limit_depth::{proto_to_limit_depth_message, LimitDepthMessage};
+use crate::spot::ws::message::mini_ticker::{
+    proto_to_mini_ticker_message, proto_to_mini_tickers_message, MiniTickerMessage,
+    MiniTickersMessage,
 };
-use chrono::{DateTime, Utc};
+use crate::spot::ws::message::orderbook_update::{
+    proto_to_orderbook_update_batch_message, OrderbookUpdateBatchMessage,
+};
+use chrono::{DateTime, TimeZone, Utc};
 use prost::Message as ProstMessage;
+use rust_decimal::Decimal;
 
 pub mod account_deals;
 pub mod account_orders;
 pub mod account_update;
+pub mod aggregated_depth;
+pub mod book_ticker;
 pub mod deals;
 pub mod kline;
+pub mod limit_depth;
+pub mod mini_ticker;
 pub mod orderbook_update;
 
 #[derive(Debug)]
@@ -37,22 +62,214 @@ pub enum Message {
     Deals(SpotDealsMessage),
     Kline(SpotKlineMessage),
     OrderbookUpdate(OrderbookUpdateMessage),
-    PublicAggreDepthsV3Api(PublicAggreDepthsV3Api),
+    OrderbookUpdateBatch(OrderbookUpdateBatchMessage),
+    AggregatedDepth(AggregatedDepthMessage),
+    LimitDepth(LimitDepthMessage),
+    BookTicker(BookTickerMessage),
+    BookTickerBatch(BookTickerBatchMessage),
+    MiniTicker(MiniTickerMessage),
+    MiniTickers(MiniTickersMessage),
+    /// Frame that could not be parsed, e.g. because the exchange changed the shape of a channel.
+    Unknown(UnknownMessage),
+}
+
+#[derive(Debug)]
+pub enum UnknownMessage {
+    Text(String),
+    Binary(Vec<u8>),
 }
 
 impl Message {
-    pub fn from_proto(data: &[u8]) -> Result<Message, ()> {
-        let wrapper = PushDataV3ApiWrapper::decode(data).or_else(|_| Err(()))?;
-        let Some(body) = wrapper.body else {
-            return Err(());
+    pub fn from_proto(data: &[u8]) -> Result<Message, ProtoMessageError> {
+        let wrapper = PushDataV3ApiWrapper::decode(data)?;
+        Self::from_proto_wrapper(&wrapper)
+    }
+
+    pub(crate) fn from_proto_wrapper(
+        wrapper: &PushDataV3ApiWrapper,
+    ) -> Result<Message, ProtoMessageError> {
+        let Some(body) = &wrapper.body else {
+            return Err(ProtoMessageError::MissingBody);
         };
-        match body {
-            PublicAggreDepths(depth) => Ok(Message::PublicAggreDepthsV3Api(depth)),
-            _ => Err(()),
+        let message = match body {
+            Body::PublicDeals(deals) => Message::Deals(proto_to_spot_deals_message(
+                wrapper,
+                deals
+                    .deals
+                    .iter()
+                    .map(RawSpotDealData::try_from)
+                    .collect::<Result<_, _>>()?,
+            )?),
+            Body::PublicAggreDeals(deals) => Message::Deals(proto_to_spot_deals_message(
+                wrapper,
+                deals
+                    .deals
+                    .iter()
+                    .map(RawSpotDealData::try_from)
+                    .collect::<Result<_, _>>()?,
+            )?),
+            Body::PublicSpotKline(kline) => {
+                Message::Kline(proto_to_spot_kline_message(wrapper, kline)?)
+            }
+            Body::PublicIncreaseDepths(depths) => {
+                Message::OrderbookUpdate(proto_to_orderbook_update_message(wrapper, depths)?)
+            }
+            Body::PublicIncreaseDepthsBatch(batch) => Message::OrderbookUpdateBatch(
+                proto_to_orderbook_update_batch_message(wrapper, batch)?,
+            ),
+            Body::PublicAggreDepths(depths) => {
+                Message::AggregatedDepth(proto_to_aggregated_depth_message(wrapper, depths)?)
+            }
+            Body::PublicLimitDepths(depths) => {
+                Message::LimitDepth(proto_to_limit_depth_message(wrapper, depths)?)
+            }
+            Body::PublicBookTicker(ticker) => {
+                Message::BookTicker(proto_to_book_ticker_message(wrapper, ticker)?)
+            }
+            Body::PublicAggreBookTicker(ticker) => Message::BookTicker(
+                proto_to_book_ticker_message(wrapper, &PublicBookTickerV3Api::from(ticker))?,
+            ),
+            Body::PublicBookTickerBatch(batch) => {
+                Message::BookTickerBatch(proto_to_book_ticker_batch_message(wrapper, batch)?)
+            }
+            Body::PublicMiniTicker(ticker) => {
+                Message::MiniTicker(proto_to_mini_ticker_message(wrapper, ticker)?)
+            }
+            Body::PublicMiniTickers(tickers) => {
+                Message::MiniTickers(proto_to_mini_tickers_message(wrapper, tickers)?)
+            }
+            Body::PrivateOrders(orders) => {
+                Message::AccountOrders(proto_to_account_orders_message(wrapper, orders)?)
+            }
+            Body::PrivateDeals(deals) => {
+                Message::AccountDeals(proto_to_account_deals_message(wrapper, deals)?)
+            }
+            Body::PrivateAccount(account) => {
+                Message::AccountUpdate(proto_to_account_update_message(wrapper, account)?)
+            }
+        };
+
+        Ok(message)
+    }
+}
+
+/// Frame received from the websocket after parsing.
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum ParsedFrame {
+    /// Reply to one of our own requests.
+    IdCode(RawIdCodeMessage),
+    Message {
+        message: Message,
+        channel: String,
+    },
+    Unknown(UnknownMessage),
+}
+
+pub(crate) fn parse_text_frame(text: String) -> ParsedFrame {
+    match serde_json::from_str::<RawMessage>(&text) {
+        Ok(RawMessage::IdCodeMessage(id_code_message)) => ParsedFrame::IdCode(id_code_message),
+        Ok(RawMessage::ChannelMessage(channel_message)) => {
+            let channel = channel_message.channel.clone();
+            match Message::try_from(&RawMessage::ChannelMessage(channel_message)) {
+                Ok(message) => ParsedFrame::Message { message, channel },
+                Err(_) => {
+                    tracing::debug!("Received unrecognized message: {}", &text);
+                    ParsedFrame::Unknown(UnknownMessage::Text(text))
+                }
+            }
+        }
+        Err(err) => {
+            tracing::debug!("Failed to deserialize message: {}\njson: {}", err, &text);
+            ParsedFrame::Unknown(UnknownMessage::Text(text))
         }
     }
 }
 
+pub(crate) fn parse_binary_frame(data: Vec<u8>) -> ParsedFrame {
+    let result = PushDataV3ApiWrapper::decode(data.as_slice())
+        .map_err(ProtoMessageError::from)
+        .and_then(|wrapper| Ok((Message::from_proto_wrapper(&wrapper)?, wrapper.channel)));
+    match result {
+        Ok((message, channel)) => ParsedFrame::Message { message, channel },
+        Err(err) => {
+            tracing::debug!(
+                "Failed to parse binary message: {}: {}",
+                err,
+                hex::encode(&data)
+            );
+            ParsedFrame::Unknown(UnknownMessage::Binary(data))
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProtoMessageError {
+    #[error("Failed to decode protobuf message: {0}")]
+    Decode(#[from] prost::DecodeError),
+
+    #[error("Protobuf message has no body")]
+    MissingBody,
+
+    #[error("Protobuf message has no symbol")]
+    MissingSymbol,
+
+    #[error("Protobuf message has no send or create time")]
+    MissingTimestamp,
+
+    #[error("Invalid decimal for {field}: {value}")]
+    InvalidDecimal { field: &'static str, value: String },
+
+    #[error("Invalid value for {field}: {value}")]
+    InvalidValue { field: &'static str, value: String },
+}
+
+pub(crate) fn proto_symbol(wrapper: &PushDataV3ApiWrapper) -> Result<String, ProtoMessageError> {
+    wrapper
+        .symbol
+        .clone()
+        .ok_or(ProtoMessageError::MissingSymbol)
+}
+
+pub(crate) fn proto_event_time(
+    wrapper: &PushDataV3ApiWrapper,
+) -> Result<DateTime<Utc>, ProtoMessageError> {
+    let millis = wrapper
+        .send_time
+        .or(wrapper.create_time)
+        .ok_or(ProtoMessageError::MissingTimestamp)?;
+    proto_timestamp_millis("sendTime", millis)
+}
+
+pub(crate) fn proto_timestamp_millis(
+    field: &'static str,
+    millis: i64,
+) -> Result<DateTime<Utc>, ProtoMessageError> {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .ok_or(ProtoMessageError::InvalidValue {
+            field,
+            value: millis.to_string(),
+        })
+}
+
+/// Numeric values are sent as strings, unset proto3 strings are empty and treated as zero.
+pub(crate) fn proto_decimal(
+    field: &'static str,
+    value: &str,
+) -> Result<Decimal, ProtoMessageError> {
+    if value.is_empty() {
+        return Ok(Decimal::ZERO);
+    }
+    value
+        .parse::<Decimal>()
+        .or_else(|_| Decimal::from_scientific(value))
+        .map_err(|_| ProtoMessageError::InvalidDecimal {
+            field,
+            value: value.to_string(),
+        })
+}
+
 impl TryFrom<&RawMessage> for Message {
     type Error = ();
 
@@ -162,6 +379,150 @@ pub(crate) enum RawEventChannelMessageData {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::proto::{
+        PrivateOrdersV3Api, PublicAggreDealsV3Api, PublicAggreDealsV3ApiItem,
+        PublicAggreDepthV3ApiItem, PublicAggreDepthsV3Api, PublicSpotKlineV3Api,
+    };
+    use crate::spot::v3::enums::{OrderSide, OrderStatus};
+    use crate::spot::ws::message::account_orders::OrderKind;
+    use crate::spot::ws::message::kline::KlineIntervalTopic;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn encode_proto(symbol: &str, body: Body) -> Vec<u8> {
+        PushDataV3ApiWrapper {
+            channel: "channel".to_string(),
+            symbol: Some(symbol.to_string()),
+            symbol_id: None,
+            create_time: Some(1736417034280),
+            send_time: Some(1736417034281),
+            body: Some(body),
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn proto_aggregated_depth() {
+        let data = encode_proto(
+            "BTCUSDT",
+            Body::PublicAggreDepths(PublicAggreDepthsV3Api {
+                asks: vec![PublicAggreDepthV3ApiItem {
+                    price: "92877.58".to_string(),
+                    quantity: "0.00000000".to_string(),
+                }],
+                bids: vec![],
+                event_type: "spot@public.aggre.depth.v3.api.pb@100ms".to_string(),
+                from_version: "10589632359".to_string(),
+                to_version: "10589632360".to_string(),
+            }),
+        );
+
+        let Message::AggregatedDepth(depth) = Message::from_proto(&data).unwrap() else {
+            panic!("expected aggregated depth");
+        };
+        assert_eq!(depth.symbol, "BTCUSDT");
+        assert_eq!(depth.from_version, 10589632359);
+        assert_eq!(depth.to_version, 10589632360);
+        assert_eq!(depth.asks[0].price, dec("92877.58"));
+        assert_eq!(depth.asks[0].quantity, dec("0"));
+    }
+
+    #[test]
+    fn proto_aggregated_deals() {
+        let data = encode_proto(
+            "BTCUSDT",
+            Body::PublicAggreDeals(PublicAggreDealsV3Api {
+                deals: vec![PublicAggreDealsV3ApiItem {
+                    price: "93220.00".to_string(),
+                    quantity: "0.04438243".to_string(),
+                    trade_type: 2,
+                    time: 1736409765051,
+                }],
+                event_type: "spot@public.aggre.deals.v3.api.pb@100ms".to_string(),
+            }),
+        );
+
+        let Message::Deals(deals) = Message::from_proto(&data).unwrap() else {
+            panic!("expected deals");
+        };
+        assert_eq!(deals.deals.len(), 1);
+        assert_eq!(deals.deals[0].symbol, "BTCUSDT");
+        assert_eq!(deals.deals[0].quantity, dec("0.04438243"));
+        assert_eq!(deals.deals[0].timestamp.timestamp_millis(), 1736409765051);
+    }
+
+    #[test]
+    fn proto_kline() {
+        let data = encode_proto(
+            "BTCUSDT",
+            Body::PublicSpotKline(PublicSpotKlineV3Api {
+                interval: "Min15".to_string(),
+                window_start: 1736410500,
+                opening_price: "92925".to_string(),
+                closing_price: "93158.47".to_string(),
+                highest_price: "93158.47".to_string(),
+                lowest_price: "92800".to_string(),
+                volume: "36.83803224".to_string(),
+                amount: "3424811.46".to_string(),
+                window_end: 1736411400,
+            }),
+        );
+
+        let Message::Kline(kline) = Message::from_proto(&data).unwrap() else {
+            panic!("expected kline");
+        };
+        assert_eq!(kline.interval, KlineIntervalTopic::FifteenMinutes);
+        assert_eq!(kline.start_time.timestamp(), 1736410500);
+        assert_eq!(kline.volume, dec("3424811.46"));
+        assert_eq!(kline.quantity, dec("36.83803224"));
+    }
+
+    #[test]
+    fn proto_private_orders() {
+        let data = encode_proto(
+            "MXUSDT",
+            Body::PrivateOrders(PrivateOrdersV3Api {
+                id: "C02__335858395366199296".to_string(),
+                client_id: "".to_string(),
+                price: "3".to_string(),
+                quantity: "2".to_string(),
+                amount: "6".to_string(),
+                avg_price: "".to_string(),
+                order_type: 1,
+                trade_type: 2,
+                is_maker: false,
+                remain_amount: "6".to_string(),
+                remain_quantity: "2".to_string(),
+                cumulative_quantity: "0".to_string(),
+                cumulative_amount: "0".to_string(),
+                status: 1,
+                create_time: 1661938138000,
+                ..Default::default()
+            }),
+        );
+
+        let Message::AccountOrders(AccountOrdersMessage::LimitOrMarket(order)) =
+            Message::from_proto(&data).unwrap()
+        else {
+            panic!("expected limit order");
+        };
+        assert_eq!(order.symbol, "MXUSDT");
+        assert_eq!(order.order_kind, OrderKind::LimitOrder);
+        assert_eq!(order.trade_type, OrderSide::Sell);
+        assert_eq!(order.status, OrderStatus::New);
+        assert_eq!(order.average_price, dec("0"));
+    }
+
+    #[test]
+    fn proto_without_body() {
+        let data = PushDataV3ApiWrapper::default().encode_to_vec();
+        assert!(matches!(
+            Message::from_proto(&data),
+            Err(ProtoMessageError::MissingBody)
+        ));
+    }
 
     #[test]
     fn raw_message_kline() {
@@ -1,5 +1,9 @@
+use crate::proto::{PrivateDealsV3Api, PushDataV3ApiWrapper};
 use crate::spot::v3::enums::OrderSide;
-use crate::spot::ws::message::{RawChannelMessage, RawChannelMessageData};
+use crate::spot::ws::message::{
+    proto_decimal, proto_event_time, proto_symbol, proto_timestamp_millis, ProtoMessageError,
+    RawChannelMessage, RawChannelMessageData,
+};
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 
@@ -80,3 +84,36 @@ pub(crate) fn channel_message_to_account_deals_message(
 
     Ok(message)
 }
+
+pub(crate) fn proto_to_account_deals_message(
+    wrapper: &PushDataV3ApiWrapper,
+    deals: &PrivateDealsV3Api,
+) -> Result<AccountDealsMessage, ProtoMessageError> {
+    let message = AccountDealsMessage {
+        asset: proto_symbol(wrapper)?,
+        trade_type: match deals.trade_type {
+            1 => OrderSide::Buy,
+            2 => OrderSide::Sell,
+            trade_type => {
+                return Err(ProtoMessageError::InvalidValue {
+                    field: "tradeType",
+                    value: trade_type.to_string(),
+                })
+            }
+        },
+        trade_time: proto_timestamp_millis("time", deals.time)?,
+        client_order_id: deals.client_order_id.clone(),
+        order_id: deals.order_id.clone(),
+        is_maker: deals.is_maker,
+        price: proto_decimal("price", &deals.price)?,
+        is_self_trade: deals.is_self_trade,
+        trade_id: deals.trade_id.clone(),
+        quantity: proto_decimal("quantity", &deals.quantity)?,
+        deals_amount: proto_decimal("amount", &deals.amount)?,
+        commission_fee: proto_decimal("feeAmount", &deals.fee_amount)?,
+        commission_asset: deals.fee_currency.clone(),
+        event_time: proto_event_time(wrapper)?,
+    };
+
+    Ok(message)
+}
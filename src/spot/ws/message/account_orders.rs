@@ -1,5 +1,9 @@
+use crate::proto::{PrivateOrdersV3Api, PushDataV3ApiWrapper};
 use crate::spot::v3::enums::{OrderSide, OrderStatus};
-use crate::spot::ws::message::{RawChannelMessage, RawChannelMessageData};
+use crate::spot::ws::message::{
+    proto_decimal, proto_event_time, proto_symbol, proto_timestamp_millis, ProtoMessageError,
+    RawChannelMessage, RawChannelMessageData,
+};
 use chrono::{DateTime, Utc};
 use num_traits::FromPrimitive;
 use rust_decimal::Decimal;
@@ -137,6 +141,92 @@ pub(crate) fn channel_message_to_account_orders_message(
     Ok(message)
 }
 
+pub(crate) fn proto_to_account_orders_message(
+    wrapper: &PushDataV3ApiWrapper,
+    order: &PrivateOrdersV3Api,
+) -> Result<AccountOrdersMessage, ProtoMessageError> {
+    let invalid_value = |field: &'static str, value: i32| ProtoMessageError::InvalidValue {
+        field,
+        value: value.to_string(),
+    };
+
+    let symbol = proto_symbol(wrapper)?;
+    let order_kind = u8::try_from(order.order_type)
+        .ok()
+        .and_then(OrderKind::from_u8)
+        .ok_or_else(|| invalid_value("orderType", order.order_type))?;
+    let trade_type = match order.trade_type {
+        1 => OrderSide::Buy,
+        2 => OrderSide::Sell,
+        trade_type => return Err(invalid_value("tradeType", trade_type)),
+    };
+
+    let message = match order_kind {
+        OrderKind::StopLimit => {
+            let trigger_type = order.trigger_type.unwrap_or_default();
+            let state = order.state.unwrap_or_default();
+            let msg = StopLimitAccountOrdersMessage {
+                symbol,
+                commission_asset: order.market.clone().unwrap_or_default(),
+                create_time: proto_timestamp_millis("createTime", order.create_time)?,
+                trigger_price: proto_decimal(
+                    "triggerPrice",
+                    order.trigger_price.as_deref().unwrap_or_default(),
+                )?,
+                trade_type,
+                direction: u8::try_from(trigger_type)
+                    .ok()
+                    .and_then(StopLimitDirection::from_u8)
+                    .ok_or_else(|| invalid_value("triggerType", trigger_type))?,
+                order_id: order.id.clone(),
+                order_kind,
+                price: proto_decimal("price", &order.price)?,
+                state: u8::try_from(state)
+                    .ok()
+                    .and_then(StopLimitOrderState::from_u8)
+                    .ok_or_else(|| invalid_value("state", state))?,
+                quantity: proto_decimal("quantity", &order.quantity)?,
+                timestamp: proto_event_time(wrapper)?,
+            };
+            AccountOrdersMessage::StopLimit(msg)
+        }
+        _ => {
+            let msg = LimitOrMarketAccountOrdersMessage {
+                symbol,
+                remain_amount: proto_decimal("remainAmount", &order.remain_amount)?,
+                create_time: proto_timestamp_millis("createTime", order.create_time)?,
+                trade_type,
+                remain_quantity: proto_decimal("remainQuantity", &order.remain_quantity)?,
+                amount: proto_decimal("amount", &order.amount)?,
+                client_order_id: order.client_id.clone(),
+                order_id: order.id.clone(),
+                is_maker: order.is_maker,
+                order_kind,
+                price: proto_decimal("price", &order.price)?,
+                status: match order.status {
+                    1 => OrderStatus::New,
+                    2 => OrderStatus::Filled,
+                    3 => OrderStatus::PartiallyFilled,
+                    4 => OrderStatus::Canceled,
+                    5 => OrderStatus::PartiallyCanceled,
+                    status => return Err(invalid_value("status", status)),
+                },
+                quantity: proto_decimal("quantity", &order.quantity)?,
+                average_price: proto_decimal("avgPrice", &order.avg_price)?,
+                cumulative_quantity: proto_decimal(
+                    "cumulativeQuantity",
+                    &order.cumulative_quantity,
+                )?,
+                cumulative_amount: proto_decimal("cumulativeAmount", &order.cumulative_amount)?,
+                timestamp: proto_event_time(wrapper)?,
+            };
+            AccountOrdersMessage::LimitOrMarket(msg)
+        }
+    };
+
+    Ok(message)
+}
+
 #[derive(Debug)]
 pub enum AccountOrdersMessage {
     LimitOrMarket(LimitOrMarketAccountOrdersMessage),
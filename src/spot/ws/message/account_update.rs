@@ -1,5 +1,9 @@
+use crate::proto::{PrivateAccountV3Api, PushDataV3ApiWrapper};
 use crate::spot::v3::enums::ChangedType;
-use crate::spot::ws::message::{RawChannelMessage, RawChannelMessageData};
+use crate::spot::ws::message::{
+    proto_decimal, proto_event_time, proto_timestamp_millis, ProtoMessageError, RawChannelMessage,
+    RawChannelMessageData,
+};
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 
@@ -30,6 +34,30 @@ pub(crate) fn channel_message_to_account_update_message(
     Ok(message)
 }
 
+pub(crate) fn proto_to_account_update_message(
+    wrapper: &PushDataV3ApiWrapper,
+    account: &PrivateAccountV3Api,
+) -> Result<AccountUpdateMessage, ProtoMessageError> {
+    let changed_type = serde_json::from_value(serde_json::Value::String(account.r#type.clone()))
+        .map_err(|_| ProtoMessageError::InvalidValue {
+            field: "type",
+            value: account.r#type.clone(),
+        })?;
+
+    let message = AccountUpdateMessage {
+        asset: account.vcoin_name.clone(),
+        change_time: proto_timestamp_millis("time", account.time)?,
+        free_balance: proto_decimal("balanceAmount", &account.balance_amount)?,
+        free_changed_amount: proto_decimal("balanceAmountChange", &account.balance_amount_change)?,
+        frozen_amount: proto_decimal("frozenAmount", &account.frozen_amount)?,
+        frozen_changed_amount: proto_decimal("frozenAmountChange", &account.frozen_amount_change)?,
+        changed_type,
+        event_time: proto_event_time(wrapper)?,
+    };
+
+    Ok(message)
+}
+
 #[allow(non_snake_case)]
 #[derive(Debug, serde::Deserialize)]
 pub(crate) struct RawAccountUpdateData {
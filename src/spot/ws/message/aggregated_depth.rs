@@ -0,0 +1,40 @@
+use crate::proto::{PublicAggreDepthsV3Api, PushDataV3ApiWrapper};
+use crate::spot::v3::depth::PriceAndQuantity;
+use crate::spot::ws::message::orderbook_update::{proto_to_price_and_quantity, proto_version};
+use crate::spot::ws::message::{proto_event_time, proto_symbol, ProtoMessageError};
+use chrono::{DateTime, Utc};
+
+/// Depth changes aggregated over the topic frequency, covering the versions
+/// `from_version..=to_version`.
+#[derive(Debug)]
+pub struct AggregatedDepthMessage {
+    pub symbol: String,
+    pub from_version: u64,
+    pub to_version: u64,
+    pub asks: Vec<PriceAndQuantity>,
+    pub bids: Vec<PriceAndQuantity>,
+    pub timestamp: DateTime<Utc>,
+}
+
+pub(crate) fn proto_to_aggregated_depth_message(
+    wrapper: &PushDataV3ApiWrapper,
+    depths: &PublicAggreDepthsV3Api,
+) -> Result<AggregatedDepthMessage, ProtoMessageError> {
+    let message = AggregatedDepthMessage {
+        symbol: proto_symbol(wrapper)?,
+        from_version: proto_version("fromVersion", &depths.from_version)?,
+        to_version: proto_version("toVersion", &depths.to_version)?,
+        asks: depths
+            .asks
+            .iter()
+            .map(|item| proto_to_price_and_quantity(&item.price, &item.quantity))
+            .collect::<Result<_, _>>()?,
+        bids: depths
+            .bids
+            .iter()
+            .map(|item| proto_to_price_and_quantity(&item.price, &item.quantity))
+            .collect::<Result<_, _>>()?,
+        timestamp: proto_event_time(wrapper)?,
+    };
+    Ok(message)
+}
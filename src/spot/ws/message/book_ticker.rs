@@ -0,0 +1,61 @@
+use crate::proto::{
+    PublicAggreBookTickerV3Api, PublicBookTickerBatchV3Api, PublicBookTickerV3Api,
+    PushDataV3ApiWrapper,
+};
+use crate::spot::ws::message::{proto_decimal, proto_event_time, proto_symbol, ProtoMessageError};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// Best bid and ask of a symbol.
+#[derive(Debug)]
+pub struct BookTickerMessage {
+    pub symbol: String,
+    pub bid_price: Decimal,
+    pub bid_quantity: Decimal,
+    pub ask_price: Decimal,
+    pub ask_quantity: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct BookTickerBatchMessage {
+    pub tickers: Vec<BookTickerMessage>,
+}
+
+impl From<&PublicAggreBookTickerV3Api> for PublicBookTickerV3Api {
+    fn from(value: &PublicAggreBookTickerV3Api) -> Self {
+        Self {
+            bid_price: value.bid_price.clone(),
+            bid_quantity: value.bid_quantity.clone(),
+            ask_price: value.ask_price.clone(),
+            ask_quantity: value.ask_quantity.clone(),
+        }
+    }
+}
+
+pub(crate) fn proto_to_book_ticker_message(
+    wrapper: &PushDataV3ApiWrapper,
+    ticker: &PublicBookTickerV3Api,
+) -> Result<BookTickerMessage, ProtoMessageError> {
+    let message = BookTickerMessage {
+        symbol: proto_symbol(wrapper)?,
+        bid_price: proto_decimal("bidPrice", &ticker.bid_price)?,
+        bid_quantity: proto_decimal("bidQuantity", &ticker.bid_quantity)?,
+        ask_price: proto_decimal("askPrice", &ticker.ask_price)?,
+        ask_quantity: proto_decimal("askQuantity", &ticker.ask_quantity)?,
+        timestamp: proto_event_time(wrapper)?,
+    };
+    Ok(message)
+}
+
+pub(crate) fn proto_to_book_ticker_batch_message(
+    wrapper: &PushDataV3ApiWrapper,
+    batch: &PublicBookTickerBatchV3Api,
+) -> Result<BookTickerBatchMessage, ProtoMessageError> {
+    let tickers = batch
+        .items
+        .iter()
+        .map(|ticker| proto_to_book_ticker_message(wrapper, ticker))
+        .collect::<Result<_, _>>()?;
+    Ok(BookTickerBatchMessage { tickers })
+}
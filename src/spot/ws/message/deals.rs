@@ -1,5 +1,7 @@
+use crate::proto::{PublicAggreDealsV3ApiItem, PublicDealsV3ApiItem, PushDataV3ApiWrapper};
 use crate::spot::ws::message::{
-    RawChannelMessage, RawChannelMessageData, RawEventChannelMessageData,
+    proto_decimal, proto_symbol, proto_timestamp_millis, ProtoMessageError, RawChannelMessage,
+    RawChannelMessageData, RawEventChannelMessageData,
 };
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
@@ -64,3 +66,49 @@ pub(crate) fn channel_message_to_spot_deals_message(
     let message = SpotDealsMessage { deals: spot_deals };
     Ok(message)
 }
+
+impl TryFrom<&PublicDealsV3ApiItem> for RawSpotDealData {
+    type Error = ProtoMessageError;
+
+    fn try_from(value: &PublicDealsV3ApiItem) -> Result<Self, Self::Error> {
+        Ok(Self {
+            price: proto_decimal("price", &value.price)?,
+            quantity: proto_decimal("quantity", &value.quantity)?,
+            timestamp: proto_timestamp_millis("time", value.time)?,
+            trade_type: value.trade_type,
+        })
+    }
+}
+
+impl TryFrom<&PublicAggreDealsV3ApiItem> for RawSpotDealData {
+    type Error = ProtoMessageError;
+
+    fn try_from(value: &PublicAggreDealsV3ApiItem) -> Result<Self, Self::Error> {
+        Ok(Self {
+            price: proto_decimal("price", &value.price)?,
+            quantity: proto_decimal("quantity", &value.quantity)?,
+            timestamp: proto_timestamp_millis("time", value.time)?,
+            trade_type: value.trade_type,
+        })
+    }
+}
+
+pub(crate) fn proto_to_spot_deals_message(
+    wrapper: &PushDataV3ApiWrapper,
+    deals: Vec<RawSpotDealData>,
+) -> Result<SpotDealsMessage, ProtoMessageError> {
+    let symbol = proto_symbol(wrapper)?;
+
+    let spot_deals = deals
+        .into_iter()
+        .map(|deal| SpotDeal {
+            symbol: symbol.clone(),
+            price: deal.price,
+            quantity: deal.quantity,
+            timestamp: deal.timestamp,
+            trade_type: deal.trade_type,
+        })
+        .collect();
+
+    Ok(SpotDealsMessage { deals: spot_deals })
+}
@@ -1,7 +1,12 @@
+use crate::proto::{PublicSpotKlineV3Api, PushDataV3ApiWrapper};
 use crate::spot::v3::enums::KlineInterval;
-use crate::spot::ws::message::{RawChannelMessage, RawChannelMessageData};
-use chrono::{DateTime, Utc};
+use crate::spot::ws::message::{
+    proto_decimal, proto_event_time, proto_symbol, ProtoMessageError, RawChannelMessage,
+    RawChannelMessageData,
+};
+use chrono::{DateTime, TimeZone, Utc};
 use rust_decimal::Decimal;
+use std::str::FromStr;
 
 use super::RawEventChannelMessageData;
 
@@ -77,6 +82,25 @@ impl AsRef<str> for KlineIntervalTopic {
     }
 }
 
+impl FromStr for KlineIntervalTopic {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Min1" => Ok(KlineIntervalTopic::OneMinute),
+            "Min5" => Ok(KlineIntervalTopic::FiveMinutes),
+            "Min15" => Ok(KlineIntervalTopic::FifteenMinutes),
+            "Min30" => Ok(KlineIntervalTopic::ThirtyMinutes),
+            "Min60" => Ok(KlineIntervalTopic::OneHour),
+            "Hour4" => Ok(KlineIntervalTopic::FourHours),
+            "Day1" => Ok(KlineIntervalTopic::OneDay),
+            "Week1" => Ok(KlineIntervalTopic::OneWeek),
+            "Month1" => Ok(KlineIntervalTopic::OneMonth),
+            _ => Err(()),
+        }
+    }
+}
+
 impl From<KlineIntervalTopic> for KlineInterval {
     fn from(value: KlineIntervalTopic) -> Self {
         match value {
@@ -158,3 +182,38 @@ pub(crate) fn channel_message_to_spot_kline_message(
     };
     Ok(message)
 }
+
+pub(crate) fn proto_to_spot_kline_message(
+    wrapper: &PushDataV3ApiWrapper,
+    kline: &PublicSpotKlineV3Api,
+) -> Result<SpotKlineMessage, ProtoMessageError> {
+    let interval = KlineIntervalTopic::from_str(&kline.interval).map_err(|_| {
+        ProtoMessageError::InvalidValue {
+            field: "interval",
+            value: kline.interval.clone(),
+        }
+    })?;
+    let window_time = |field: &'static str, seconds: i64| {
+        Utc.timestamp_opt(seconds, 0)
+            .single()
+            .ok_or(ProtoMessageError::InvalidValue {
+                field,
+                value: seconds.to_string(),
+            })
+    };
+
+    let message = SpotKlineMessage {
+        symbol: proto_symbol(wrapper)?,
+        interval,
+        end_time: window_time("windowEnd", kline.window_end)?,
+        volume: proto_decimal("amount", &kline.amount)?,
+        close: proto_decimal("closingPrice", &kline.closing_price)?,
+        high: proto_decimal("highestPrice", &kline.highest_price)?,
+        low: proto_decimal("lowestPrice", &kline.lowest_price)?,
+        open: proto_decimal("openingPrice", &kline.opening_price)?,
+        start_time: window_time("windowStart", kline.window_start)?,
+        quantity: proto_decimal("volume", &kline.volume)?,
+        timestamp: proto_event_time(wrapper)?,
+    };
+    Ok(message)
+}
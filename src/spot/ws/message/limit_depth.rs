@@ -0,0 +1,37 @@
+use crate::proto::{PublicLimitDepthsV3Api, PushDataV3ApiWrapper};
+use crate::spot::v3::depth::PriceAndQuantity;
+use crate::spot::ws::message::orderbook_update::{proto_to_price_and_quantity, proto_version};
+use crate::spot::ws::message::{proto_event_time, proto_symbol, ProtoMessageError};
+use chrono::{DateTime, Utc};
+
+/// Snapshot of the best levels of the order book.
+#[derive(Debug)]
+pub struct LimitDepthMessage {
+    pub symbol: String,
+    pub version: u64,
+    pub asks: Vec<PriceAndQuantity>,
+    pub bids: Vec<PriceAndQuantity>,
+    pub timestamp: DateTime<Utc>,
+}
+
+pub(crate) fn proto_to_limit_depth_message(
+    wrapper: &PushDataV3ApiWrapper,
+    depths: &PublicLimitDepthsV3Api,
+) -> Result<LimitDepthMessage, ProtoMessageError> {
+    let message = LimitDepthMessage {
+        symbol: proto_symbol(wrapper)?,
+        version: proto_version("version", &depths.version)?,
+        asks: depths
+            .asks
+            .iter()
+            .map(|item| proto_to_price_and_quantity(&item.price, &item.quantity))
+            .collect::<Result<_, _>>()?,
+        bids: depths
+            .bids
+            .iter()
+            .map(|item| proto_to_price_and_quantity(&item.price, &item.quantity))
+            .collect::<Result<_, _>>()?,
+        timestamp: proto_event_time(wrapper)?,
+    };
+    Ok(message)
+}
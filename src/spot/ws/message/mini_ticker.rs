@@ -0,0 +1,71 @@
+use crate::proto::{PublicMiniTickerV3Api, PublicMiniTickersV3Api, PushDataV3ApiWrapper};
+use crate::spot::ws::message::{proto_decimal, proto_event_time, proto_symbol, ProtoMessageError};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// Rolling 24 hour statistics of a symbol.
+#[derive(Debug)]
+pub struct MiniTickerMessage {
+    pub symbol: String,
+    pub price: Decimal,
+    pub price_change_rate: Decimal,
+    /// Price change rate in the requested timezone
+    pub zoned_price_change_rate: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub volume: Decimal,
+    pub quantity: Decimal,
+    pub last_close_price_change_rate: Decimal,
+    pub last_close_zoned_price_change_rate: Decimal,
+    pub last_close_high: Decimal,
+    pub last_close_low: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct MiniTickersMessage {
+    pub tickers: Vec<MiniTickerMessage>,
+}
+
+pub(crate) fn proto_to_mini_ticker_message(
+    wrapper: &PushDataV3ApiWrapper,
+    ticker: &PublicMiniTickerV3Api,
+) -> Result<MiniTickerMessage, ProtoMessageError> {
+    let symbol = if ticker.symbol.is_empty() {
+        proto_symbol(wrapper)?
+    } else {
+        ticker.symbol.clone()
+    };
+
+    let message = MiniTickerMessage {
+        symbol,
+        price: proto_decimal("price", &ticker.price)?,
+        price_change_rate: proto_decimal("rate", &ticker.rate)?,
+        zoned_price_change_rate: proto_decimal("zonedRate", &ticker.zoned_rate)?,
+        high: proto_decimal("high", &ticker.high)?,
+        low: proto_decimal("low", &ticker.low)?,
+        volume: proto_decimal("volume", &ticker.volume)?,
+        quantity: proto_decimal("quantity", &ticker.quantity)?,
+        last_close_price_change_rate: proto_decimal("lastCloseRate", &ticker.last_close_rate)?,
+        last_close_zoned_price_change_rate: proto_decimal(
+            "lastCloseZonedRate",
+            &ticker.last_close_zoned_rate,
+        )?,
+        last_close_high: proto_decimal("lastCloseHigh", &ticker.last_close_high)?,
+        last_close_low: proto_decimal("lastCloseLow", &ticker.last_close_low)?,
+        timestamp: proto_event_time(wrapper)?,
+    };
+    Ok(message)
+}
+
+pub(crate) fn proto_to_mini_tickers_message(
+    wrapper: &PushDataV3ApiWrapper,
+    tickers: &PublicMiniTickersV3Api,
+) -> Result<MiniTickersMessage, ProtoMessageError> {
+    let tickers = tickers
+        .items
+        .iter()
+        .map(|ticker| proto_to_mini_ticker_message(wrapper, ticker))
+        .collect::<Result<_, _>>()?;
+    Ok(MiniTickersMessage { tickers })
+}
@@ -1,8 +1,15 @@
 use rust_decimal::Decimal;
 
+use crate::proto::{
+    PublicIncreaseDepthV3ApiItem, PublicIncreaseDepthsBatchV3Api, PublicIncreaseDepthsV3Api,
+    PushDataV3ApiWrapper,
+};
 use crate::spot::v3::depth::PriceAndQuantity;
 
-use super::{RawChannelMessage, RawChannelMessageData, RawEventChannelMessageData};
+use super::{
+    proto_decimal, proto_symbol, ProtoMessageError, RawChannelMessage, RawChannelMessageData,
+    RawEventChannelMessageData,
+};
 
 #[allow(non_snake_case)]
 #[derive(Debug, Clone, Copy, serde::Deserialize)]
@@ -22,6 +29,11 @@ pub struct OrderbookUpdateMessage {
     pub bids: Vec<PriceAndQuantity>,
 }
 
+#[derive(Debug)]
+pub struct OrderbookUpdateBatchMessage {
+    pub updates: Vec<OrderbookUpdateMessage>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ChannelMessageToOrderbookUpdateMessageError {
     #[error("No orderbook update message")]
@@ -75,3 +87,56 @@ pub(crate) fn channel_message_to_spot_orderbook_update_message(
     };
     Ok(message)
 }
+
+pub(crate) fn proto_to_price_and_quantity(
+    price: &str,
+    quantity: &str,
+) -> Result<PriceAndQuantity, ProtoMessageError> {
+    Ok(PriceAndQuantity {
+        price: proto_decimal("price", price)?,
+        quantity: proto_decimal("quantity", quantity)?,
+    })
+}
+
+fn proto_depth_items_to_price_and_quantities(
+    items: &[PublicIncreaseDepthV3ApiItem],
+) -> Result<Vec<PriceAndQuantity>, ProtoMessageError> {
+    items
+        .iter()
+        .map(|item| proto_to_price_and_quantity(&item.price, &item.quantity))
+        .collect()
+}
+
+pub(crate) fn proto_version(field: &'static str, version: &str) -> Result<u64, ProtoMessageError> {
+    version
+        .parse()
+        .map_err(|_| ProtoMessageError::InvalidValue {
+            field,
+            value: version.to_string(),
+        })
+}
+
+pub(crate) fn proto_to_orderbook_update_message(
+    wrapper: &PushDataV3ApiWrapper,
+    depths: &PublicIncreaseDepthsV3Api,
+) -> Result<OrderbookUpdateMessage, ProtoMessageError> {
+    let message = OrderbookUpdateMessage {
+        symbol: proto_symbol(wrapper)?,
+        version: proto_version("version", &depths.version)?,
+        asks: proto_depth_items_to_price_and_quantities(&depths.asks)?,
+        bids: proto_depth_items_to_price_and_quantities(&depths.bids)?,
+    };
+    Ok(message)
+}
+
+pub(crate) fn proto_to_orderbook_update_batch_message(
+    wrapper: &PushDataV3ApiWrapper,
+    batch: &PublicIncreaseDepthsBatchV3Api,
+) -> Result<OrderbookUpdateBatchMessage, ProtoMessageError> {
+    let updates = batch
+        .items
+        .iter()
+        .map(|depths| proto_to_orderbook_update_message(wrapper, depths))
+        .collect::<Result<_, _>>()?;
+    Ok(OrderbookUpdateBatchMessage { updates })
+}
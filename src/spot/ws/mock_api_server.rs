@@ -0,0 +1,292 @@
+//! Local http server that serves the `/api/v3/userDataStream` endpoints of the MEXC spot api, so
+//! the private websocket flow can be tested together with the mock websocket server.
+//!
+//! Point [`crate::spot::MexcSpotApiEndpoint::Custom`] at [`MockUserDataStreamServer::endpoint`].
+//! Signatures are not checked, every request is answered from the listen keys of the server.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::sync::CancellationToken;
+
+const USER_DATA_STREAM_PATH: &str = "/api/v3/userDataStream";
+
+#[derive(Debug)]
+pub struct MockUserDataStreamServer {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    cancellation_token: CancellationToken,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    /// Listen keys that are valid, in order of creation.
+    listen_keys: Mutex<Vec<String>>,
+    deleted_listen_keys: Mutex<Vec<String>>,
+    /// Amount of listen keys that were created since starting.
+    created_listen_keys: Mutex<usize>,
+}
+
+impl MockUserDataStreamServer {
+    /// Starts the server on a random local port, it stops when dropped.
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(MockState::default());
+        let cancellation_token = CancellationToken::new();
+
+        tokio::spawn(accept_connections(
+            listener,
+            state.clone(),
+            cancellation_token.clone(),
+        ));
+
+        Ok(Self {
+            addr,
+            state,
+            cancellation_token,
+        })
+    }
+
+    pub fn endpoint(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Adds a listen key as if it was created before, e.g. by a previous run.
+    pub fn add_listen_key(&self, listen_key: impl Into<String>) {
+        self.state
+            .listen_keys
+            .lock()
+            .unwrap()
+            .push(listen_key.into());
+    }
+
+    /// Makes the listen key expire, keeping it alive fails from now on.
+    pub fn expire_listen_key(&self, listen_key: &str) {
+        self.state
+            .listen_keys
+            .lock()
+            .unwrap()
+            .retain(|key| key != listen_key);
+    }
+
+    pub fn listen_keys(&self) -> Vec<String> {
+        self.state.listen_keys.lock().unwrap().clone()
+    }
+
+    pub fn deleted_listen_keys(&self) -> Vec<String> {
+        self.state.deleted_listen_keys.lock().unwrap().clone()
+    }
+
+    pub fn created_listen_key_count(&self) -> usize {
+        *self.state.created_listen_keys.lock().unwrap()
+    }
+}
+
+impl Drop for MockUserDataStreamServer {
+    fn drop(&mut self) {
+        self.cancellation_token.cancel();
+    }
+}
+
+async fn accept_connections(
+    listener: TcpListener,
+    state: Arc<MockState>,
+    cancellation_token: CancellationToken,
+) {
+    loop {
+        let stream = tokio::select! {
+            _ = cancellation_token.cancelled() => {
+                return;
+            }
+            result = listener.accept() => match result {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    tracing::error!("Mock api server failed to accept connection: {}", err);
+                    return;
+                }
+            }
+        };
+        tokio::spawn(handle_connection(stream, state.clone()));
+    }
+}
+
+/// Answers a single request and closes the connection, the requests have no body.
+async fn handle_connection(stream: TcpStream, state: Arc<MockState>) {
+    let mut stream = BufReader::new(stream);
+    let mut request_line = String::new();
+    if stream.read_line(&mut request_line).await.is_err() {
+        return;
+    }
+    loop {
+        let mut header = String::new();
+        match stream.read_line(&mut header).await {
+            Ok(0) | Err(_) => return,
+            Ok(_) if header == "\r\n" => break,
+            Ok(_) => {}
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return;
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = serde_urlencoded::from_str::<HashMap<String, String>>(query).unwrap_or_default();
+    let (status, body) = handle_request(&state, method, path, &query);
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.get_mut().write_all(response.as_bytes()).await;
+    let _ = stream.get_mut().shutdown().await;
+}
+
+/// Returns the status line and the json body of the response.
+fn handle_request(
+    state: &MockState,
+    method: &str,
+    path: &str,
+    query: &HashMap<String, String>,
+) -> (&'static str, String) {
+    if path != USER_DATA_STREAM_PATH {
+        return ("404 Not Found", error_body(730003, "Unsupported operation"));
+    }
+
+    let mut listen_keys = state.listen_keys.lock().unwrap();
+    match method {
+        "POST" => {
+            let mut created_listen_keys = state.created_listen_keys.lock().unwrap();
+            *created_listen_keys += 1;
+            let listen_key = format!("mock-listen-key-{}", created_listen_keys);
+            listen_keys.push(listen_key.clone());
+            ("200 OK", listen_key_body(&listen_key))
+        }
+        "GET" => (
+            "200 OK",
+            serde_json::json!({ "listenKey": *listen_keys }).to_string(),
+        ),
+        "PUT" | "DELETE" => {
+            let Some(listen_key) = query.get("listenKey") else {
+                return ("400 Bad Request", error_body(730002, "Parameter error"));
+            };
+            let Some(index) = listen_keys.iter().position(|key| key == listen_key) else {
+                return (
+                    "400 Bad Request",
+                    error_body(730002, "Listen key does not exist"),
+                );
+            };
+            if method == "DELETE" {
+                listen_keys.remove(index);
+                state
+                    .deleted_listen_keys
+                    .lock()
+                    .unwrap()
+                    .push(listen_key.clone());
+            }
+            ("200 OK", listen_key_body(listen_key))
+        }
+        _ => (
+            "405 Method Not Allowed",
+            error_body(730003, "Unsupported operation"),
+        ),
+    }
+}
+
+fn listen_key_body(listen_key: &str) -> String {
+    serde_json::json!({ "listenKey": listen_key }).to_string()
+}
+
+fn error_body(code: i32, message: &str) -> String {
+    serde_json::json!({ "code": code, "msg": message }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::ws::auth::WebsocketAuth;
+    use crate::spot::ws::mock_server::MockMexcWebsocketServer;
+    use crate::spot::ws::subscribe::{Subscribe, SubscribeParams};
+    use crate::spot::ws::topic::Topic;
+    use crate::spot::ws::unsubscribe::{Unsubscribe, UnsubscribeParams};
+    use crate::spot::ws::MexcSpotWebsocketClient;
+    use crate::spot::MexcSpotApiEndpoint;
+
+    fn auth() -> WebsocketAuth {
+        WebsocketAuth::new("api_key".to_string(), "secret_key".to_string())
+    }
+
+    fn ws_client(
+        api_server: &MockUserDataStreamServer,
+        ws_server: &MockMexcWebsocketServer,
+    ) -> Arc<MexcSpotWebsocketClient> {
+        MexcSpotWebsocketClient::builder()
+            .with_ws_endpoint(ws_server.endpoint())
+            .with_spot_api_endpoint(MexcSpotApiEndpoint::Custom(api_server.endpoint()))
+            .build()
+            .into_arc()
+    }
+
+    fn account_deals_params() -> (SubscribeParams, UnsubscribeParams) {
+        (
+            SubscribeParams::default()
+                .with_auth(auth())
+                .with_topic(Topic::AccountDeals),
+            UnsubscribeParams::default()
+                .with_auth(auth())
+                .with_topic(Topic::AccountDeals),
+        )
+    }
+
+    #[tokio::test]
+    async fn private_flow_creates_and_deletes_listen_key() {
+        let api_server = MockUserDataStreamServer::start().await.unwrap();
+        let ws_server = MockMexcWebsocketServer::start().await.unwrap();
+        let ws_client = ws_client(&api_server, &ws_server);
+        let (subscribe_params, unsubscribe_params) = account_deals_params();
+
+        let output = ws_client.clone().subscribe(subscribe_params).await.unwrap();
+        assert_eq!(output.accepted_topics, vec![Topic::AccountDeals]);
+        assert_eq!(api_server.created_listen_key_count(), 1);
+        assert_eq!(
+            ws_server.connected_listen_keys(),
+            vec!["mock-listen-key-1".to_string()]
+        );
+
+        // Closing the last private websocket deletes the listen key that was created for it.
+        ws_client.unsubscribe(unsubscribe_params).await.unwrap();
+        assert_eq!(
+            api_server.deleted_listen_keys(),
+            vec!["mock-listen-key-1".to_string()]
+        );
+        assert!(api_server.listen_keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn private_flow_reuses_listen_key_without_deleting_it() {
+        let api_server = MockUserDataStreamServer::start().await.unwrap();
+        api_server.add_listen_key("existing-listen-key");
+        let ws_server = MockMexcWebsocketServer::start().await.unwrap();
+        let ws_client = ws_client(&api_server, &ws_server);
+        let (subscribe_params, unsubscribe_params) = account_deals_params();
+
+        ws_client.clone().subscribe(subscribe_params).await.unwrap();
+        assert_eq!(api_server.created_listen_key_count(), 0);
+        assert_eq!(
+            ws_server.connected_listen_keys(),
+            vec!["existing-listen-key".to_string()]
+        );
+
+        ws_client.unsubscribe(unsubscribe_params).await.unwrap();
+        assert!(api_server.deleted_listen_keys().is_empty());
+        assert_eq!(
+            api_server.listen_keys(),
+            vec!["existing-listen-key".to_string()]
+        );
+    }
+}
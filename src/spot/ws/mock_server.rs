@@ -0,0 +1,610 @@
+//! Local websocket server that speaks the MEXC spot websocket protocol, to test the client
+//! without a network or credentials.
+//!
+//! The server acknowledges `SUBSCRIPTION`, `UNSUBSCRIPTION` and `PING` requests the way MEXC
+//! does, and lets tests push frames to subscribers, drop connections and validate listen keys.
+
+use crate::spot::ws::endpoint::MexcWebsocketEndpoint;
+use futures::{SinkExt, StreamExt};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Notify};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug)]
+pub struct MockMexcWebsocketServer {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    cancellation_token: CancellationToken,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    connections: Mutex<Vec<MockConnection>>,
+    /// Listen keys that are accepted, `None` to accept any.
+    valid_listen_keys: Mutex<Option<HashSet<String>>>,
+    rejected_topics: Mutex<HashSet<String>>,
+    /// Amount of connections that were accepted since starting.
+    accepted_connections: Mutex<usize>,
+    /// Notified whenever connections or subscriptions change.
+    changed: Notify,
+}
+
+#[derive(Debug, Clone)]
+struct MockConnection {
+    id: usize,
+    listen_key: Option<String>,
+    topics: Vec<String>,
+    tx: mpsc::UnboundedSender<MockCommand>,
+}
+
+#[derive(Debug)]
+enum MockCommand {
+    Send(Message),
+    /// Drop the connection without a closing handshake.
+    Drop,
+    /// Abort the tcp connection with a reset, which the client reads as an io error.
+    Reset,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct MockRequest {
+    method: String,
+    #[serde(default)]
+    params: Vec<String>,
+}
+
+impl MockMexcWebsocketServer {
+    /// Starts the server on a random local port, it stops when dropped.
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(MockState::default());
+        let cancellation_token = CancellationToken::new();
+
+        tokio::spawn(accept_connections(
+            listener,
+            state.clone(),
+            cancellation_token.clone(),
+        ));
+
+        Ok(Self {
+            addr,
+            state,
+            cancellation_token,
+        })
+    }
+
+    pub fn endpoint(&self) -> MexcWebsocketEndpoint {
+        MexcWebsocketEndpoint::Custom(format!("ws://{}/ws", self.addr))
+    }
+
+    /// Only accept connections with one of these listen keys, others are refused with a 401.
+    pub fn set_valid_listen_keys(&self, listen_keys: impl IntoIterator<Item = String>) {
+        *self.state.valid_listen_keys.lock().unwrap() = Some(listen_keys.into_iter().collect());
+    }
+
+    /// Stops accepting the listen key and drops the connections that use it.
+    pub fn revoke_listen_key(&self, listen_key: &str) {
+        if let Some(valid_listen_keys) = self.state.valid_listen_keys.lock().unwrap().as_mut() {
+            valid_listen_keys.remove(listen_key);
+        }
+        for connection in self.state.connections.lock().unwrap().iter() {
+            if connection.listen_key.as_deref() == Some(listen_key) {
+                let _ = connection.tx.send(MockCommand::Drop);
+            }
+        }
+    }
+
+    /// Subscriptions to the topic are answered with `Not Subscribed successfully!`.
+    pub fn reject_topic(&self, topic: impl Into<String>) {
+        self.state
+            .rejected_topics
+            .lock()
+            .unwrap()
+            .insert(topic.into());
+    }
+
+    pub fn connection_count(&self) -> usize {
+        self.state.connections.lock().unwrap().len()
+    }
+
+    pub fn accepted_connection_count(&self) -> usize {
+        *self.state.accepted_connections.lock().unwrap()
+    }
+
+    /// Listen keys of the open connections, public connections are left out.
+    pub fn connected_listen_keys(&self) -> Vec<String> {
+        self.state
+            .connections
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|connection| connection.listen_key.clone())
+            .collect()
+    }
+
+    /// Topics subscribed to over all connections.
+    pub fn subscribed_topics(&self) -> Vec<String> {
+        self.state
+            .connections
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|connection| connection.topics.clone())
+            .collect()
+    }
+
+    /// Waits until the condition holds, returns false when it did not within the timeout.
+    pub async fn wait_until(
+        &self,
+        timeout: Duration,
+        condition: impl Fn(&MockMexcWebsocketServer) -> bool,
+    ) -> bool {
+        let wait = async {
+            loop {
+                let changed = self.state.changed.notified();
+                if condition(self) {
+                    return;
+                }
+                changed.await;
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+
+    /// Sends a json frame to the connections subscribed to the topic, returns to how many.
+    pub fn push_text(&self, topic: &str, text: impl Into<String>) -> usize {
+        self.push(topic, Message::Text(text.into()))
+    }
+
+    /// Sends a protobuf frame to the connections subscribed to the topic, returns to how many.
+    pub fn push_binary(&self, topic: &str, data: Vec<u8>) -> usize {
+        self.push(topic, Message::Binary(data))
+    }
+
+    fn push(&self, topic: &str, message: Message) -> usize {
+        let connections = self.state.connections.lock().unwrap();
+        let mut pushed = 0;
+        for connection in connections.iter() {
+            if connection.topics.iter().any(|t| t == topic)
+                && connection
+                    .tx
+                    .send(MockCommand::Send(message.clone()))
+                    .is_ok()
+            {
+                pushed += 1;
+            }
+        }
+        pushed
+    }
+
+    /// Drops all connections without a closing handshake, like a network failure.
+    pub fn disconnect_all(&self) {
+        for connection in self.state.connections.lock().unwrap().iter() {
+            let _ = connection.tx.send(MockCommand::Drop);
+        }
+    }
+
+    /// Aborts all tcp connections with a reset, like a connection that is lost halfway.
+    pub fn reset_all(&self) {
+        for connection in self.state.connections.lock().unwrap().iter() {
+            let _ = connection.tx.send(MockCommand::Reset);
+        }
+    }
+}
+
+impl Drop for MockMexcWebsocketServer {
+    fn drop(&mut self) {
+        self.cancellation_token.cancel();
+    }
+}
+
+async fn accept_connections(
+    listener: TcpListener,
+    state: Arc<MockState>,
+    cancellation_token: CancellationToken,
+) {
+    loop {
+        let stream = tokio::select! {
+            _ = cancellation_token.cancelled() => {
+                return;
+            }
+            result = listener.accept() => match result {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    tracing::error!("Mock server failed to accept connection: {}", err);
+                    return;
+                }
+            }
+        };
+        tokio::spawn(handle_connection(
+            stream,
+            state.clone(),
+            cancellation_token.clone(),
+        ));
+    }
+}
+
+fn listen_key_from_request(request: &Request) -> Option<String> {
+    request.uri().query()?.split('&').find_map(|pair| {
+        pair.strip_prefix("listenKey=")
+            .map(|listen_key| listen_key.to_string())
+    })
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    state: Arc<MockState>,
+    cancellation_token: CancellationToken,
+) {
+    let mut listen_key = None;
+    // The error response is what tungstenite expects from the callback.
+    #[allow(clippy::result_large_err)]
+    let callback = |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+        listen_key = listen_key_from_request(request);
+        if let (Some(valid_listen_keys), Some(key)) = (
+            state.valid_listen_keys.lock().unwrap().as_ref(),
+            &listen_key,
+        ) {
+            if !valid_listen_keys.contains(key) {
+                let mut error_response = ErrorResponse::new(Some("Invalid listen key".to_string()));
+                *error_response.status_mut() = StatusCode::UNAUTHORIZED;
+                return Err(error_response);
+            }
+        }
+        Ok(response)
+    };
+    let ws_stream = match tokio_tungstenite::accept_hdr_async(stream, callback).await {
+        Ok(ws_stream) => ws_stream,
+        Err(err) => {
+            tracing::debug!("Mock server refused connection: {}", err);
+            return;
+        }
+    };
+    let (mut ws_tx, mut ws_rx) = ws_stream.split();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let id = {
+        let mut accepted_connections = state.accepted_connections.lock().unwrap();
+        *accepted_connections += 1;
+        *accepted_connections
+    };
+    state.connections.lock().unwrap().push(MockConnection {
+        id,
+        listen_key,
+        topics: vec![],
+        tx,
+    });
+    state.changed.notify_waiters();
+
+    loop {
+        tokio::select! {
+            _ = cancellation_token.cancelled() => {
+                break;
+            }
+            command = rx.recv() => match command {
+                Some(MockCommand::Send(message)) => {
+                    if ws_tx.send(message).await.is_err() {
+                        break;
+                    }
+                }
+                Some(MockCommand::Drop) | None => {
+                    break;
+                }
+                Some(MockCommand::Reset) => {
+                    if let Ok(ws_stream) = ws_tx.reunite(ws_rx) {
+                        // Closing with a zero linger sends a reset instead of a fin.
+                        let _ = ws_stream.get_ref().set_linger(Some(Duration::ZERO));
+                    }
+                    remove_connection(&state, id);
+                    return;
+                }
+            },
+            message = ws_rx.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    for reply in handle_request(&state, id, &text) {
+                        if ws_tx.send(Message::Text(reply)).await.is_err() {
+                            break;
+                        }
+                    }
+                    state.changed.notify_waiters();
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                    break;
+                }
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    remove_connection(&state, id);
+}
+
+fn remove_connection(state: &MockState, id: usize) {
+    state
+        .connections
+        .lock()
+        .unwrap()
+        .retain(|connection| connection.id != id);
+    state.changed.notify_waiters();
+}
+
+fn id_code_reply(code: i32, message: &str) -> String {
+    serde_json::json!({ "id": 0, "code": code, "msg": message }).to_string()
+}
+
+/// Handles a request of a connection and returns the replies.
+fn handle_request(state: &MockState, id: usize, text: &str) -> Vec<String> {
+    let request = match serde_json::from_str::<MockRequest>(text) {
+        Ok(request) => request,
+        Err(_) => return vec![id_code_reply(1, "Invalid request")],
+    };
+
+    let mut connections = state.connections.lock().unwrap();
+    let Some(connection) = connections
+        .iter_mut()
+        .find(|connection| connection.id == id)
+    else {
+        return vec![];
+    };
+
+    match request.method.as_str() {
+        "PING" => vec![id_code_reply(0, "PONG")],
+        "SUBSCRIPTION" => {
+            let rejected_topics = state.rejected_topics.lock().unwrap();
+            let (rejected, accepted) = request
+                .params
+                .into_iter()
+                .partition::<Vec<_>, _>(|topic| rejected_topics.contains(topic));
+            for topic in accepted.iter() {
+                if !connection.topics.contains(topic) {
+                    connection.topics.push(topic.clone());
+                }
+            }
+
+            let mut replies = vec![];
+            if !accepted.is_empty() {
+                replies.push(id_code_reply(0, &accepted.join(",")));
+            }
+            if !rejected.is_empty() {
+                replies.push(id_code_reply(
+                    0,
+                    &format!(
+                        "Not Subscribed successfully! [{}].  Reason： Blocked! ",
+                        rejected.join(",")
+                    ),
+                ));
+            }
+            replies
+        }
+        "UNSUBSCRIPTION" => {
+            connection
+                .topics
+                .retain(|topic| !request.params.contains(topic));
+            vec![id_code_reply(0, &request.params.join(","))]
+        }
+        _ => vec![id_code_reply(1, "Invalid method")],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::ws::connection_event::{
+        ConnectionEventKind, ConnectionEvents, DisconnectCause,
+    };
+    use crate::spot::ws::message::kline::KlineIntervalTopic;
+    use crate::spot::ws::message::Message as MexcMessage;
+    use crate::spot::ws::reconnect_policy::ReconnectPolicy;
+    use crate::spot::ws::stream::Stream;
+    use crate::spot::ws::subscribe::{Subscribe, SubscribeParams};
+    use crate::spot::ws::topic::{DealsTopic, KlineTopic, Topic};
+    use crate::spot::ws::unsubscribe::{Unsubscribe, UnsubscribeParams};
+    use crate::spot::ws::MexcSpotWebsocketClient;
+    use crate::spot::MexcSpotApiEndpoint;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn kline_topic() -> Topic {
+        Topic::Kline(KlineTopic::new(
+            "BTCUSDT".to_string(),
+            KlineIntervalTopic::OneMinute,
+        ))
+    }
+
+    fn ws_client(server: &MockMexcWebsocketServer) -> Arc<MexcSpotWebsocketClient> {
+        MexcSpotWebsocketClient::builder()
+            .with_ws_endpoint(server.endpoint())
+            .with_spot_api_endpoint(MexcSpotApiEndpoint::Custom(
+                "http://127.0.0.1:1".to_string(),
+            ))
+            .with_reconnect_policy(
+                ReconnectPolicy::default().with_initial_delay(Duration::from_millis(10)),
+            )
+            .build()
+            .into_arc()
+    }
+
+    #[tokio::test]
+    async fn subscribe_and_receive_pushes() {
+        let server = MockMexcWebsocketServer::start().await.unwrap();
+        let ws_client = ws_client(&server);
+        let mut stream = ws_client.clone().stream();
+
+        let output = ws_client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(kline_topic()))
+            .await
+            .unwrap();
+        assert_eq!(output.accepted_topics, vec![kline_topic()]);
+
+        let pushed = server.push_text(
+            &kline_topic().to_topic_subscription_string(),
+            r#"{"d":{"e":"spot@public.kline.v3.api","k":{"t":1695680400,"o":"26288.47","c":"26289.11","h":"26289.12","l":"26288.46","v":"1.579991","a":"41535.11","T":1695680460,"i":"Min1"}},"c":"spot@public.kline.v3.api@BTCUSDT@Min1","t":1695680458622,"s":"BTCUSDT"}"#,
+        );
+        assert_eq!(pushed, 1);
+        let message = tokio::time::timeout(TIMEOUT, stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(message.as_ref(), MexcMessage::Kline(_)));
+    }
+
+    #[tokio::test]
+    async fn rejected_topic() {
+        let server = MockMexcWebsocketServer::start().await.unwrap();
+        server.reject_topic(kline_topic().to_topic_subscription_string());
+        let ws_client = ws_client(&server);
+
+        let output = ws_client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(kline_topic()))
+            .await
+            .unwrap();
+        assert!(output.accepted_topics.is_empty());
+        assert_eq!(output.rejected_topics.len(), 1);
+
+        // The websocket was only created for the rejected topic.
+        assert!(ws_client.inner.read().await.websockets.is_empty());
+        assert!(
+            server
+                .wait_until(TIMEOUT, |server| server.connection_count() == 0)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn unsubscribe_and_close_websocket_without_topics() {
+        let server = MockMexcWebsocketServer::start().await.unwrap();
+        let ws_client = ws_client(&server);
+        let deals_topic = Topic::Deals(DealsTopic::new("BTCUSDT".to_string()));
+
+        ws_client
+            .clone()
+            .subscribe(
+                SubscribeParams::default().with_topics(vec![kline_topic(), deals_topic.clone()]),
+            )
+            .await
+            .unwrap();
+        ws_client
+            .clone()
+            .unsubscribe(UnsubscribeParams::default().with_topic(kline_topic()))
+            .await
+            .unwrap();
+        assert!(
+            server
+                .wait_until(TIMEOUT, |server| server.subscribed_topics()
+                    == vec![deals_topic.to_topic_subscription_string()])
+                .await
+        );
+
+        ws_client
+            .clone()
+            .unsubscribe(UnsubscribeParams::default().with_topic(deals_topic))
+            .await
+            .unwrap();
+        assert!(ws_client.inner.read().await.websockets.is_empty());
+        assert!(
+            server
+                .wait_until(TIMEOUT, |server| server.connection_count() == 0)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn reconnect_and_resubscribe_after_disconnect() {
+        let server = MockMexcWebsocketServer::start().await.unwrap();
+        let ws_client = ws_client(&server);
+        let mut connection_events = ws_client.clone().connection_events();
+
+        ws_client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(kline_topic()))
+            .await
+            .unwrap();
+        server.disconnect_all();
+
+        let resubscribed = tokio::time::timeout(TIMEOUT, async {
+            while let Some(event) = connection_events.next().await {
+                if matches!(event.kind, ConnectionEventKind::Resubscribed) {
+                    return true;
+                }
+            }
+            false
+        })
+        .await;
+        assert_eq!(resubscribed, Ok(true));
+        assert!(
+            server
+                .wait_until(TIMEOUT, |server| server.subscribed_topics()
+                    == vec![kline_topic().to_topic_subscription_string()])
+                .await
+        );
+        assert_eq!(server.accepted_connection_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn reconnect_and_resubscribe_after_connection_reset() {
+        let server = MockMexcWebsocketServer::start().await.unwrap();
+        let ws_client = ws_client(&server);
+        let mut connection_events = ws_client.clone().connection_events();
+
+        ws_client
+            .clone()
+            .subscribe(SubscribeParams::default().with_topic(kline_topic()))
+            .await
+            .unwrap();
+        server.reset_all();
+
+        let events = tokio::time::timeout(TIMEOUT, async {
+            let mut causes = vec![];
+            while let Some(event) = connection_events.next().await {
+                match event.kind {
+                    ConnectionEventKind::Disconnected(cause) => causes.push(cause),
+                    ConnectionEventKind::Resubscribed => return causes,
+                    _ => {}
+                }
+            }
+            causes
+        })
+        .await
+        .unwrap();
+        assert!(matches!(
+            events.as_slice(),
+            [DisconnectCause::WebsocketError(_)]
+        ));
+        assert!(
+            server
+                .wait_until(TIMEOUT, |server| server.subscribed_topics()
+                    == vec![kline_topic().to_topic_subscription_string()])
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn refuse_invalid_listen_key() {
+        let server = MockMexcWebsocketServer::start().await.unwrap();
+        server.set_valid_listen_keys(["valid".to_string()]);
+
+        let endpoint = server.endpoint();
+        let result =
+            tokio_tungstenite::connect_async(format!("{}?listenKey=invalid", endpoint)).await;
+        assert!(matches!(
+            result,
+            Err(tokio_tungstenite::tungstenite::Error::Http(response))
+                if response.status() == StatusCode::UNAUTHORIZED
+        ));
+        assert!(
+            tokio_tungstenite::connect_async(format!("{}?listenKey=valid", endpoint))
+                .await
+                .is_ok()
+        );
+    }
+}
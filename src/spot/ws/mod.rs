@@ -3,32 +3,50 @@ use crate::spot::ws::endpoint::MexcWebsocketEndpoint;
 use crate::spot::ws::topic::Topic;
 use crate::spot::MexcSpotApiEndpoint;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 pub mod acquire_websocket;
 pub mod auth;
+pub mod builder;
+pub mod connection_event;
 pub mod endpoint;
+pub mod health;
+mod listen_key;
+pub mod local_order_book;
 pub mod message;
+#[cfg(any(test, feature = "test-support"))]
+pub mod mock_api_server;
+#[cfg(any(test, feature = "test-support"))]
+pub mod mock_server;
+pub mod reconnect_policy;
+pub mod recorder;
+pub mod shutdown;
 pub mod stream;
 pub mod subscribe;
 pub mod topic;
 pub mod unsubscribe;
+pub mod watchdog;
 
 #[derive(Debug, Clone)]
 pub struct WebsocketEntry {
     pub id: Uuid,
     pub auth: Option<WebsocketAuth>,
-    pub listen_key: Option<String>,
     pub topics: Arc<RwLock<Vec<Topic>>>,
     pub message_tx: Arc<RwLock<async_channel::Sender<SendableMessage>>>,
+    pub cancellation_token: Arc<RwLock<CancellationToken>>,
+    pub(crate) metrics: Arc<health::WebsocketMetrics>,
 }
 
 #[derive(Debug)]
 struct Inner {
-    pub auth_to_listen_key_map: HashMap<WebsocketAuth, String>,
+    pub listen_keys: HashMap<WebsocketAuth, listen_key::ManagedListenKey>,
     pub websockets: Vec<Arc<WebsocketEntry>>,
+    /// Private websockets per auth that are being connected, but not added to websockets yet.
+    pub pending_websockets: HashMap<WebsocketAuth, usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -37,6 +55,14 @@ pub struct MexcSpotWebsocketClient {
     ws_endpoint: Arc<MexcWebsocketEndpoint>,
     spot_api_endpoint: Arc<MexcSpotApiEndpoint>,
     broadcast_tx: tokio::sync::broadcast::Sender<Arc<message::Message>>,
+    id_code_tx: tokio::sync::broadcast::Sender<(Uuid, Arc<message::RawIdCodeMessage>)>,
+    connection_event_tx: tokio::sync::broadcast::Sender<connection_event::ConnectionEvent>,
+    config: Arc<builder::WebsocketConfig>,
+    queued_consumers: Arc<std::sync::Mutex<Vec<Arc<stream::QueuedConsumer>>>>,
+    /// Parent of the cancellation tokens of all websockets.
+    shutdown_token: CancellationToken,
+    tasks: Arc<std::sync::Mutex<Vec<tokio::task::JoinHandle<()>>>>,
+    parse_errors: Arc<AtomicU64>,
 }
 
 impl MexcSpotWebsocketClient {
@@ -44,22 +70,64 @@ impl MexcSpotWebsocketClient {
         ws_endpoint: MexcWebsocketEndpoint,
         spot_api_endpoint: MexcSpotApiEndpoint,
     ) -> Self {
-        let (broadcast_tx, _broadcast_rx) = tokio::sync::broadcast::channel(1024);
+        builder::MexcSpotWebsocketClientBuilder::new()
+            .with_ws_endpoint(ws_endpoint)
+            .with_spot_api_endpoint(spot_api_endpoint)
+            .build()
+    }
+
+    pub fn builder() -> builder::MexcSpotWebsocketClientBuilder {
+        builder::MexcSpotWebsocketClientBuilder::new()
+    }
+
+    fn new_with_config(
+        ws_endpoint: MexcWebsocketEndpoint,
+        spot_api_endpoint: MexcSpotApiEndpoint,
+        broadcast_capacity: usize,
+        config: builder::WebsocketConfig,
+    ) -> Self {
+        let (broadcast_tx, _broadcast_rx) = tokio::sync::broadcast::channel(broadcast_capacity);
+        let (id_code_tx, _id_code_rx) = tokio::sync::broadcast::channel(1024);
+        let (connection_event_tx, _connection_event_rx) = tokio::sync::broadcast::channel(1024);
 
         Self {
             inner: Arc::new(RwLock::new(Inner {
-                auth_to_listen_key_map: HashMap::new(),
+                listen_keys: HashMap::new(),
                 websockets: Vec::new(),
+                pending_websockets: HashMap::new(),
             })),
             ws_endpoint: Arc::new(ws_endpoint),
             spot_api_endpoint: Arc::new(spot_api_endpoint),
             broadcast_tx,
+            id_code_tx,
+            connection_event_tx,
+            config: Arc::new(config),
+            queued_consumers: Arc::new(std::sync::Mutex::new(Vec::new())),
+            shutdown_token: CancellationToken::new(),
+            tasks: Arc::new(std::sync::Mutex::new(Vec::new())),
+            parse_errors: Arc::new(AtomicU64::new(0)),
         }
     }
 
     pub fn into_arc(self) -> Arc<Self> {
         Arc::new(self)
     }
+
+    /// Amount of received frames that could not be parsed, see [`message::Message::Unknown`].
+    pub fn parse_error_count(&self) -> u64 {
+        self.parse_errors.load(Ordering::Relaxed)
+    }
+
+    /// Spawns a task that is awaited on shutdown.
+    pub(crate) fn spawn_task<F>(&self, future: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let handle = tokio::spawn(future);
+        let mut tasks = self.tasks.lock().expect("Tasks lock poisoned");
+        tasks.retain(|task| !task.is_finished());
+        tasks.push(handle);
+    }
 }
 
 impl Default for MexcSpotWebsocketClient {
@@ -0,0 +1 @@
+pub use crate::reconnect_policy::ReconnectPolicy;
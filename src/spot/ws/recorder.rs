@@ -0,0 +1,264 @@
+use crate::spot::ws::message::{parse_binary_frame, parse_text_frame, Message, ParsedFrame};
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use tokio::io::AsyncBufReadExt;
+use uuid::Uuid;
+
+/// Frame as it was received from one of the websockets, stored as a line of json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub received_at: DateTime<Utc>,
+    pub websocket_id: Uuid,
+    #[serde(flatten)]
+    pub data: RecordedFrameData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedFrameData {
+    Text(String),
+    /// Stored hex encoded.
+    Binary(
+        #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")] Vec<u8>,
+    ),
+}
+
+fn serialize_hex<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex::encode(data))
+}
+
+fn deserialize_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let hex_str = String::deserialize(deserializer)?;
+    hex::decode(hex_str).map_err(serde::de::Error::custom)
+}
+
+/// Amount of frames queued for the file by default, before frames are dropped.
+const DEFAULT_CAPACITY: usize = 65536;
+
+/// Appends every frame the websockets receive to a file, see
+/// [`MexcSpotWebsocketClientBuilder::with_frame_recorder`](crate::spot::ws::builder::MexcSpotWebsocketClientBuilder::with_frame_recorder).
+///
+/// The frames are written on a thread of its own, so the websockets are not held up by the file.
+/// When the file can't keep up and the queue is full, frames are dropped and counted instead of
+/// queueing up without bound. Clones share the file and the count.
+#[derive(Debug, Clone)]
+pub struct FrameRecorder {
+    tx: mpsc::SyncSender<RecordedFrame>,
+    dropped_frames: Arc<AtomicU64>,
+}
+
+impl FrameRecorder {
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::create_with_capacity(path, DEFAULT_CAPACITY)
+    }
+
+    /// Queues up to `capacity` frames for the file.
+    pub fn create_with_capacity(path: impl AsRef<Path>, capacity: usize) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        std::thread::spawn(move || write_frames(std::io::BufWriter::new(file), rx));
+
+        Ok(Self {
+            tx,
+            dropped_frames: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Amount of frames that were not recorded because the queue was full.
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record(&self, websocket_id: Uuid, data: RecordedFrameData) {
+        let frame = RecordedFrame {
+            received_at: Utc::now(),
+            websocket_id,
+            data,
+        };
+        match self.tx.try_send(frame) {
+            Ok(_) => {}
+            Err(mpsc::TrySendError::Full(_)) => {
+                self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+            }
+            // The writer only stops when writing failed, which it already reported.
+            Err(mpsc::TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+fn write_frames(mut writer: impl Write, rx: mpsc::Receiver<RecordedFrame>) {
+    while let Ok(frame) = rx.recv() {
+        let mut result = write_frame(&mut writer, &frame);
+        // Write whatever else is queued before flushing.
+        while let (Ok(_), Ok(frame)) = (&result, rx.try_recv()) {
+            result = write_frame(&mut writer, &frame);
+        }
+        if let Err(err) = result.and_then(|_| writer.flush()) {
+            tracing::error!("Failed to record websocket frame: {}", err);
+            return;
+        }
+    }
+}
+
+fn write_frame(writer: &mut impl Write, frame: &RecordedFrame) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, frame)?;
+    writer.write_all(b"\n")
+}
+
+/// Plays back a file written by a [`FrameRecorder`], the frames are parsed just like frames
+/// received from a websocket.
+#[derive(Debug, Clone)]
+pub struct FrameReplayer {
+    pub path: PathBuf,
+    /// Wait between frames for as long as there was between receiving them.
+    pub paced: bool,
+    /// Only play back the frames of this websocket.
+    pub websocket_id: Option<Uuid>,
+}
+
+impl FrameReplayer {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            paced: false,
+            websocket_id: None,
+        }
+    }
+
+    pub fn with_paced(mut self, paced: bool) -> Self {
+        self.paced = paced;
+        self
+    }
+
+    pub fn with_websocket_id(mut self, websocket_id: Uuid) -> Self {
+        self.websocket_id = Some(websocket_id);
+        self
+    }
+
+    /// Stream of the recorded messages, shaped like [`Stream::stream`](crate::spot::ws::stream::Stream::stream).
+    /// Replies to requests are left out, and lines that are not a recorded frame are skipped.
+    pub fn stream<'a>(self) -> BoxStream<'a, Arc<Message>> {
+        let stream = async_stream::stream! {
+            let file = match tokio::fs::File::open(&self.path).await {
+                Ok(file) => file,
+                Err(err) => {
+                    tracing::error!("Failed to open recorded frames {}: {}", self.path.display(), err);
+                    return;
+                }
+            };
+            let mut lines = tokio::io::BufReader::new(file).lines();
+            let mut previous_received_at: Option<DateTime<Utc>> = None;
+            loop {
+                let line = match lines.next_line().await {
+                    Ok(Some(line)) => line,
+                    Ok(None) => break,
+                    Err(err) => {
+                        tracing::error!("Failed to read recorded frames: {}", err);
+                        break;
+                    }
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let frame = match serde_json::from_str::<RecordedFrame>(&line) {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        tracing::warn!("Skipping line that is not a recorded frame: {}", err);
+                        continue;
+                    }
+                };
+                if self.websocket_id.is_some_and(|websocket_id| websocket_id != frame.websocket_id) {
+                    continue;
+                }
+
+                if self.paced {
+                    if let Some(previous_received_at) = previous_received_at {
+                        let delay = (frame.received_at - previous_received_at)
+                            .to_std()
+                            .unwrap_or_default();
+                        tokio::time::sleep(delay).await;
+                    }
+                    previous_received_at = Some(frame.received_at);
+                }
+
+                let parsed_frame = match frame.data {
+                    RecordedFrameData::Text(text) => parse_text_frame(text),
+                    RecordedFrameData::Binary(data) => parse_binary_frame(data),
+                };
+                match parsed_frame {
+                    ParsedFrame::IdCode(_) => {}
+                    ParsedFrame::Message { message, .. } => yield Arc::new(message),
+                    ParsedFrame::Unknown(unknown_message) => {
+                        yield Arc::new(Message::Unknown(unknown_message))
+                    }
+                }
+            }
+        };
+        stream.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::ws::message::UnknownMessage;
+
+    #[tokio::test]
+    async fn replay_recorded_frames() {
+        let websocket_id = Uuid::new_v4();
+        let frames = [
+            RecordedFrameData::Text(r#"{"id":0,"code":0,"msg":"PONG"}"#.to_string()),
+            RecordedFrameData::Text(r#"{"d":{"e":"spot@public.kline.v3.api","k":{"t":1695680400,"o":"26288.47","c":"26289.11","h":"26289.12","l":"26288.46","v":"1.579991","a":"41535.11","T":1695680460,"i":"Min1"}},"c":"spot@public.kline.v3.api@BTCUSDT@Min1","t":1695680458622,"s":"BTCUSDT"}"#.to_string()),
+            RecordedFrameData::Binary(vec![0xff, 0x00]),
+        ];
+        let path = std::env::temp_dir().join(format!("mexc-rs-frames-{}.jsonl", Uuid::new_v4()));
+        let mut contents = String::new();
+        for data in frames {
+            let frame = RecordedFrame {
+                received_at: Utc::now(),
+                websocket_id,
+                data,
+            };
+            contents.push_str(&serde_json::to_string(&frame).unwrap());
+            contents.push('\n');
+        }
+        std::fs::write(&path, contents).unwrap();
+
+        let messages = FrameReplayer::new(&path).stream().collect::<Vec<_>>().await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(messages[0].as_ref(), Message::Kline(_)));
+        assert!(matches!(
+            messages[1].as_ref(),
+            Message::Unknown(UnknownMessage::Binary(data)) if data == &vec![0xff, 0x00]
+        ));
+    }
+
+    #[test]
+    fn count_dropped_frames() {
+        // Nothing drains the queue, like a writer that can't keep up.
+        let (tx, _rx) = mpsc::sync_channel(1);
+        let frame_recorder = FrameRecorder {
+            tx,
+            dropped_frames: Arc::new(AtomicU64::new(0)),
+        };
+        let cloned_frame_recorder = frame_recorder.clone();
+        for _ in 0..3 {
+            frame_recorder.record(Uuid::new_v4(), RecordedFrameData::Binary(vec![]));
+        }
+
+        assert_eq!(cloned_frame_recorder.dropped_frames(), 2);
+    }
+}
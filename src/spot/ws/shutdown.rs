@@ -0,0 +1,104 @@
+use crate::spot::ws::connection_event::ConnectionEventKind;
+use crate::spot::ws::listen_key::delete_listen_key;
+use crate::spot::ws::MexcSpotWebsocketClient;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+pub struct ShutdownParams {
+    /// Delete the listen keys this client created for the private websockets, instead of letting
+    /// them expire. Reused listen keys are left alone, other processes might still use them.
+    pub delete_listen_keys: bool,
+}
+
+impl ShutdownParams {
+    pub fn new(delete_listen_keys: bool) -> Self {
+        Self { delete_listen_keys }
+    }
+
+    pub fn with_delete_listen_keys(mut self, delete_listen_keys: bool) -> Self {
+        self.delete_listen_keys = delete_listen_keys;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShutdownOutput {
+    pub closed_websockets: usize,
+}
+
+#[async_trait]
+pub trait Shutdown {
+    /// Closes all websockets and returns once all of their tasks have exited. Subscribing
+    /// afterwards fails.
+    async fn shutdown(self: Arc<Self>, params: ShutdownParams) -> ShutdownOutput;
+}
+
+#[async_trait]
+impl Shutdown for MexcSpotWebsocketClient {
+    async fn shutdown(self: Arc<Self>, params: ShutdownParams) -> ShutdownOutput {
+        let (websockets, listen_keys) = {
+            let mut inner = self.inner.write().await;
+            // Cancelled while holding the lock, so no websocket can be created afterwards.
+            self.shutdown_token.cancel();
+            (
+                std::mem::take(&mut inner.websockets),
+                std::mem::take(&mut inner.listen_keys),
+            )
+        };
+
+        for websocket_entry in websockets.iter() {
+            let topics = websocket_entry.topics.read().await.clone();
+            self.emit_connection_event(websocket_entry.id, topics, ConnectionEventKind::Closed);
+        }
+
+        // Tasks that are exiting might spawn new ones, e.g. while reconnecting.
+        loop {
+            let tasks = std::mem::take(&mut *self.tasks.lock().expect("Tasks lock poisoned"));
+            if tasks.is_empty() {
+                break;
+            }
+            for task in tasks {
+                if let Err(err) = task.await {
+                    tracing::error!("Websocket task failed: {}", err);
+                }
+            }
+        }
+
+        if params.delete_listen_keys {
+            for (auth, managed_listen_key) in listen_keys.iter() {
+                if managed_listen_key.owned {
+                    delete_listen_key(&self, auth, &managed_listen_key.listen_key).await;
+                }
+            }
+        }
+
+        tracing::debug!("Websocket client shut down");
+
+        ShutdownOutput {
+            closed_websockets: websockets.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::ws::subscribe::{Subscribe, SubscribeError, SubscribeParams};
+    use crate::spot::ws::topic::{DealsTopic, Topic};
+
+    #[tokio::test]
+    async fn subscribe_after_shutdown() {
+        let ws_client = MexcSpotWebsocketClient::default().into_arc();
+        let output = ws_client.clone().shutdown(ShutdownParams::default()).await;
+        assert_eq!(output.closed_websockets, 0);
+
+        let result = ws_client
+            .subscribe(
+                SubscribeParams::default()
+                    .with_topic(Topic::Deals(DealsTopic::new("BTCUSDT".to_string()))),
+            )
+            .await;
+        assert!(matches!(result, Err(SubscribeError::ClientShutDown)));
+    }
+}
@@ -2,20 +2,285 @@ use crate::spot::ws::message::Message;
 use crate::spot::ws::MexcSpotWebsocketClient;
 use futures::stream::BoxStream;
 use futures::StreamExt;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Clone)]
+pub enum StreamItem {
+    Message(Arc<Message>),
+    /// The consumer fell behind and the given amount of messages were skipped.
+    Lagged {
+        skipped: u64,
+    },
+}
+
+/// What a queued stream does when its queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueOverflow {
+    /// Drop the oldest queued message to make room, reported as [`StreamItem::Lagged`].
+    DropOldest,
+    /// Wait until the consumer made room. The websocket stops reading while waiting, which stalls
+    /// every topic on that connection and not just the ones the consumer is slow on. Closing the
+    /// websocket stops the wait and drops the message.
+    Backpressure,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueuedStreamParams {
+    pub capacity: usize,
+    pub overflow: QueueOverflow,
+}
+
+impl Default for QueuedStreamParams {
+    fn default() -> Self {
+        Self::new(1024, QueueOverflow::DropOldest)
+    }
+}
+
+impl QueuedStreamParams {
+    pub fn new(capacity: usize, overflow: QueueOverflow) -> Self {
+        Self { capacity, overflow }
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: QueueOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
 
 pub trait Stream {
+    /// Stream of all messages, messages missed because the consumer fell behind are skipped.
     fn stream<'a>(self: Arc<Self>) -> BoxStream<'a, Arc<Message>>;
+
+    /// Stream of all messages that reports when messages were skipped.
+    fn stream_with_lag<'a>(self: Arc<Self>) -> BoxStream<'a, StreamItem>;
+
+    /// Stream of all messages with a queue of its own, instead of the shared broadcast channel.
+    fn queued_stream<'a>(self: Arc<Self>, params: QueuedStreamParams) -> BoxStream<'a, StreamItem>;
 }
 
 impl Stream for MexcSpotWebsocketClient {
     fn stream<'a>(self: Arc<Self>) -> BoxStream<'a, Arc<Message>> {
+        self.stream_with_lag()
+            .filter_map(|item| async move {
+                match item {
+                    StreamItem::Message(message) => Some(message),
+                    StreamItem::Lagged { skipped } => {
+                        tracing::warn!("Stream lagged behind, skipped {} messages", skipped);
+                        None
+                    }
+                }
+            })
+            .boxed()
+    }
+
+    fn stream_with_lag<'a>(self: Arc<Self>) -> BoxStream<'a, StreamItem> {
         let mut rx = self.broadcast_tx.subscribe();
         let stream = async_stream::stream! {
+            loop {
+                match rx.recv().await {
+                    Ok(message) => yield StreamItem::Message(message),
+                    Err(RecvError::Lagged(skipped)) => yield StreamItem::Lagged { skipped },
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        };
+        stream.boxed()
+    }
+
+    fn queued_stream<'a>(self: Arc<Self>, params: QueuedStreamParams) -> BoxStream<'a, StreamItem> {
+        let (tx, rx) = async_channel::bounded(params.capacity.max(1));
+        let consumer = Arc::new(QueuedConsumer {
+            tx,
+            rx: rx.clone(),
+            overflow: params.overflow,
+            skipped: AtomicU64::new(0),
+        });
+        self.queued_consumers
+            .lock()
+            .expect("Queued consumers lock poisoned")
+            .push(consumer.clone());
+
+        let guard = QueuedConsumerGuard(consumer);
+        let stream = async_stream::stream! {
+            let guard = guard;
             while let Ok(message) = rx.recv().await {
-                yield message;
+                let skipped = guard.0.skipped.swap(0, Ordering::Relaxed);
+                if skipped > 0 {
+                    yield StreamItem::Lagged { skipped };
+                }
+                yield StreamItem::Message(message);
             }
         };
         stream.boxed()
     }
 }
+
+#[derive(Debug)]
+pub(crate) struct QueuedConsumer {
+    tx: async_channel::Sender<Arc<Message>>,
+    /// Used to drop the oldest message when the queue is full.
+    rx: async_channel::Receiver<Arc<Message>>,
+    overflow: QueueOverflow,
+    skipped: AtomicU64,
+}
+
+impl QueuedConsumer {
+    /// Returns false when the consumer is gone.
+    async fn deliver(&self, message: Arc<Message>) -> bool {
+        match self.overflow {
+            QueueOverflow::Backpressure => self.tx.send(message).await.is_ok(),
+            QueueOverflow::DropOldest => {
+                let mut message = message;
+                loop {
+                    match self.tx.try_send(message) {
+                        Ok(_) => return true,
+                        Err(async_channel::TrySendError::Closed(_)) => return false,
+                        Err(async_channel::TrySendError::Full(returned_message)) => {
+                            if self.rx.try_recv().is_ok() {
+                                self.skipped.fetch_add(1, Ordering::Relaxed);
+                            }
+                            message = returned_message;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Closes the queue once the stream is dropped, so the consumer gets unregistered.
+struct QueuedConsumerGuard(Arc<QueuedConsumer>);
+
+impl Drop for QueuedConsumerGuard {
+    fn drop(&mut self) {
+        self.0.rx.close();
+    }
+}
+
+impl MexcSpotWebsocketClient {
+    /// Stops delivering once the cancellation token of the websocket that received the message
+    /// is cancelled, so a consumer applying backpressure can't keep the websocket from closing.
+    pub(crate) async fn deliver_to_queued_consumers(
+        &self,
+        message: Arc<Message>,
+        cancellation_token: &CancellationToken,
+    ) {
+        let consumers = self
+            .queued_consumers
+            .lock()
+            .expect("Queued consumers lock poisoned")
+            .clone();
+        if consumers.is_empty() {
+            return;
+        }
+
+        let mut has_closed_consumers = false;
+        for consumer in consumers.iter() {
+            let delivered = tokio::select! {
+                _ = cancellation_token.cancelled() => return,
+                delivered = consumer.deliver(message.clone()) => delivered,
+            };
+            if !delivered {
+                has_closed_consumers = true;
+            }
+        }
+        if has_closed_consumers {
+            self.queued_consumers
+                .lock()
+                .expect("Queued consumers lock poisoned")
+                .retain(|consumer| !consumer.tx.is_closed());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::ws::message::deals::SpotDealsMessage;
+
+    fn message() -> Arc<Message> {
+        Arc::new(Message::Deals(SpotDealsMessage { deals: vec![] }))
+    }
+
+    #[tokio::test]
+    async fn stream_reports_lag() {
+        let ws_client = MexcSpotWebsocketClient::builder()
+            .with_broadcast_capacity(2)
+            .build()
+            .into_arc();
+        let mut stream = ws_client.clone().stream_with_lag();
+        for _ in 0..5 {
+            ws_client.broadcast_tx.send(message()).unwrap();
+        }
+        assert!(matches!(
+            stream.next().await,
+            Some(StreamItem::Lagged { skipped: 3 })
+        ));
+        assert!(matches!(stream.next().await, Some(StreamItem::Message(_))));
+    }
+
+    #[tokio::test]
+    async fn queued_stream_drops_oldest() {
+        let ws_client = MexcSpotWebsocketClient::default().into_arc();
+        let mut stream = ws_client
+            .clone()
+            .queued_stream(QueuedStreamParams::default().with_capacity(2));
+        for _ in 0..5 {
+            ws_client
+                .deliver_to_queued_consumers(message(), &CancellationToken::new())
+                .await;
+        }
+        assert!(matches!(
+            stream.next().await,
+            Some(StreamItem::Lagged { skipped: 3 })
+        ));
+        assert!(matches!(stream.next().await, Some(StreamItem::Message(_))));
+        assert!(matches!(stream.next().await, Some(StreamItem::Message(_))));
+
+        drop(stream);
+        ws_client
+            .deliver_to_queued_consumers(message(), &CancellationToken::new())
+            .await;
+        assert!(ws_client.queued_consumers.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelling_stops_backpressure() {
+        let ws_client = MexcSpotWebsocketClient::default().into_arc();
+        let _stream = ws_client.clone().queued_stream(
+            QueuedStreamParams::default()
+                .with_capacity(1)
+                .with_overflow(QueueOverflow::Backpressure),
+        );
+        let cancellation_token = CancellationToken::new();
+        ws_client
+            .deliver_to_queued_consumers(message(), &cancellation_token)
+            .await;
+
+        let delivery = tokio::spawn({
+            let ws_client = ws_client.clone();
+            let cancellation_token = cancellation_token.clone();
+            async move {
+                ws_client
+                    .deliver_to_queued_consumers(message(), &cancellation_token)
+                    .await;
+            }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!delivery.is_finished());
+
+        cancellation_token.cancel();
+        tokio::time::timeout(std::time::Duration::from_secs(1), delivery)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -3,22 +3,31 @@ use crate::spot::ws::acquire_websocket::{
     AcquireWebsocketForTopicsError, AcquireWebsocketsForTopics, AcquireWebsocketsForTopicsParams,
 };
 use crate::spot::ws::auth::WebsocketAuth;
+use crate::spot::ws::connection_event::ConnectionEventKind;
+use crate::spot::ws::listen_key::{delete_listen_key, take_unused_listen_key};
+use crate::spot::ws::message::RawIdCodeMessage;
 use crate::spot::ws::topic::Topic;
-use crate::spot::ws::{MexcSpotWebsocketClient, SendableMessage};
+use crate::spot::ws::{MexcSpotWebsocketClient, SendableMessage, WebsocketEntry};
 use async_channel::SendError;
 use async_trait::async_trait;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+/// How long to wait for the server to acknowledge a subscription request.
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug)]
 pub struct SubscribeParams {
     pub auth: Option<WebsocketAuth>,
     pub topics: Vec<Topic>,
-    // pub wait_for_confirmation: bool,
+    pub wait_for_confirmation: bool,
 }
 
 impl Default for SubscribeParams {
     fn default() -> Self {
-        Self::new(None, Vec::new() /*, true*/)
+        Self::new(None, Vec::new(), true)
     }
 }
 
@@ -26,12 +35,12 @@ impl SubscribeParams {
     pub fn new(
         auth: Option<WebsocketAuth>,
         topics: Vec<Topic>,
-        // wait_for_confirmation: bool,
+        wait_for_confirmation: bool,
     ) -> Self {
         Self {
             auth,
             topics,
-            // wait_for_confirmation,
+            wait_for_confirmation,
         }
     }
 
@@ -50,14 +59,28 @@ impl SubscribeParams {
         self
     }
 
-    // pub fn with_wait_for_confirmation(mut self, wait_for_confirmation: bool) -> Self {
-    //     self.wait_for_confirmation = wait_for_confirmation;
-    //     self
-    // }
+    pub fn with_wait_for_confirmation(mut self, wait_for_confirmation: bool) -> Self {
+        self.wait_for_confirmation = wait_for_confirmation;
+        self
+    }
+}
+
+/// The topics are only sorted into accepted, rejected and unconfirmed when the subscription
+/// waited for confirmation, otherwise all lists are empty.
+#[derive(Debug, Clone, Default)]
+pub struct SubscribeOutput {
+    pub accepted_topics: Vec<Topic>,
+    pub rejected_topics: Vec<RejectedTopic>,
+    /// Topics for which the server did not reply in time.
+    pub unconfirmed_topics: Vec<Topic>,
 }
 
 #[derive(Debug, Clone)]
-pub struct SubscribeOutput {}
+pub struct RejectedTopic {
+    pub topic: Topic,
+    /// The message the server replied with, e.g. `Not Subscribed successfully! [...]. Reason: Blocked!`
+    pub message: String,
+}
 
 #[derive(Debug, thiserror::Error)]
 pub enum SubscribeError {
@@ -80,6 +103,9 @@ pub enum SubscribeError {
 
     #[error("Failed to send message through channel: {0}")]
     SendError(#[from] SendError<SendableMessage>),
+
+    #[error("Client was shut down")]
+    ClientShutDown,
 }
 
 #[async_trait]
@@ -120,9 +146,16 @@ impl Subscribe for MexcSpotWebsocketClient {
                 AcquireWebsocketForTopicsError::CouldNotCreateDataStream(err) => {
                     return Err(SubscribeError::CouldNotCreateDataStream(err));
                 }
+                AcquireWebsocketForTopicsError::ClientShutDown => {
+                    return Err(SubscribeError::ClientShutDown);
+                }
             },
         };
 
+        // Subscribe before sending anything so no reply can slip through.
+        let mut id_code_rx = self.id_code_tx.subscribe();
+
+        let mut pending_confirmations = vec![];
         for acquired_ws in acquire_output.websockets.into_iter() {
             let params = acquired_ws
                 .for_topics
@@ -131,18 +164,308 @@ impl Subscribe for MexcSpotWebsocketClient {
                 .collect::<Vec<String>>();
             let sendable_message = SendableMessage::Subscription(params);
 
+            // The topics were already added to the websocket when acquiring it.
             let tx = acquired_ws.websocket_entry.message_tx.read().await;
             tx.send(sendable_message).await?;
 
-            let mut topics = acquired_ws.websocket_entry.topics.write().await;
-            let topics_websocket_entry_does_not_have = acquired_ws
-                .for_topics
-                .into_iter()
-                .filter(|topic| !topics.contains(topic))
-                .collect::<Vec<Topic>>();
-            topics.extend(topics_websocket_entry_does_not_have);
+            pending_confirmations.extend(acquired_ws.for_topics.into_iter().map(|topic| {
+                PendingConfirmation {
+                    websocket_entry: acquired_ws.websocket_entry.clone(),
+                    topic_subscription_string: topic.to_topic_subscription_string(),
+                    topic,
+                }
+            }));
+        }
+
+        if !params.wait_for_confirmation {
+            return Ok(SubscribeOutput::default());
+        }
+
+        let mut output = SubscribeOutput::default();
+        let mut rejected_websockets = vec![];
+        let _ = tokio::time::timeout(
+            CONFIRMATION_TIMEOUT,
+            wait_for_confirmations(
+                &mut id_code_rx,
+                &mut pending_confirmations,
+                &mut output,
+                &mut rejected_websockets,
+            ),
+        )
+        .await;
+        output.unconfirmed_topics = pending_confirmations
+            .into_iter()
+            .map(|pending| pending.topic)
+            .collect();
+        close_websockets_without_topics(&self, rejected_websockets).await;
+
+        Ok(output)
+    }
+}
+
+/// Closes the websockets that only had rejected topics, such as the ones that were created for
+/// the subscription. Done the same way as unsubscribing from the last topic of a websocket.
+async fn close_websockets_without_topics(
+    this: &MexcSpotWebsocketClient,
+    rejected_websockets: Vec<(Arc<WebsocketEntry>, Vec<Topic>)>,
+) {
+    let mut closed_websockets = vec![];
+    let mut unused_listen_keys = vec![];
+    {
+        let mut inner = this.inner.write().await;
+        for (websocket_entry, rejected_topics) in rejected_websockets {
+            // Topics might have been added in the meantime.
+            if !websocket_entry.topics.read().await.is_empty() {
+                continue;
+            }
+            let Some(index) = inner
+                .websockets
+                .iter()
+                .position(|entry| entry.id == websocket_entry.id)
+            else {
+                continue;
+            };
+            inner.websockets.remove(index);
+            if let Some(auth) = &websocket_entry.auth {
+                if let Some(listen_key) = take_unused_listen_key(&mut inner, auth) {
+                    unused_listen_keys.push((auth.clone(), listen_key));
+                }
+            }
+            closed_websockets.push((websocket_entry, rejected_topics));
+        }
+    }
+
+    for (closed_websocket, rejected_topics) in closed_websockets {
+        tracing::debug!(
+            "Closing websocket with id {} because all of its topics were rejected",
+            closed_websocket.id
+        );
+        closed_websocket.cancellation_token.read().await.cancel();
+        this.emit_connection_event(
+            closed_websocket.id,
+            rejected_topics,
+            ConnectionEventKind::Closed,
+        );
+    }
+    for (auth, listen_key) in unused_listen_keys {
+        delete_listen_key(this, &auth, &listen_key).await;
+    }
+}
+
+#[derive(Debug)]
+struct PendingConfirmation {
+    websocket_entry: Arc<WebsocketEntry>,
+    topic: Topic,
+    topic_subscription_string: String,
+}
+
+async fn wait_for_confirmations(
+    id_code_rx: &mut tokio::sync::broadcast::Receiver<(Uuid, Arc<RawIdCodeMessage>)>,
+    pending_confirmations: &mut Vec<PendingConfirmation>,
+    output: &mut SubscribeOutput,
+    rejected_websockets: &mut Vec<(Arc<WebsocketEntry>, Vec<Topic>)>,
+) {
+    while !pending_confirmations.is_empty() {
+        let (websocket_id, id_code_message) = match id_code_rx.recv().await {
+            Ok(x) => x,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("Missed {} replies while waiting for confirmation", skipped);
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+        let confirmation = SubscriptionConfirmation::from(id_code_message.as_ref());
+        if confirmation.is_error() && !confirmation.mentions_topics() {
+            // Replies don't tell which request they belong to, so the topics are left
+            // unconfirmed rather than rejecting topics this reply might not be about.
+            tracing::warn!(
+                "Received error reply while waiting for confirmation: {}",
+                id_code_message.message
+            );
+            continue;
         }
 
-        Ok(SubscribeOutput {})
+        let mut index = 0;
+        while index < pending_confirmations.len() {
+            let pending = &pending_confirmations[index];
+            if pending.websocket_entry.id != websocket_id {
+                index += 1;
+                continue;
+            }
+
+            let topic_str = pending.topic_subscription_string.as_str();
+            if confirmation.rejected.iter().any(|t| t == topic_str) {
+                let pending = pending_confirmations.remove(index);
+                // Rejected topics would otherwise be resubscribed on reconnect and take up space.
+                pending
+                    .websocket_entry
+                    .topics
+                    .write()
+                    .await
+                    .retain(|topic| topic != &pending.topic);
+                match rejected_websockets
+                    .iter_mut()
+                    .find(|(entry, _)| entry.id == websocket_id)
+                {
+                    Some((_, topics)) => topics.push(pending.topic.clone()),
+                    None => rejected_websockets
+                        .push((pending.websocket_entry.clone(), vec![pending.topic.clone()])),
+                }
+                output.rejected_topics.push(RejectedTopic {
+                    topic: pending.topic,
+                    message: id_code_message.message.clone(),
+                });
+            } else if confirmation.accepted.iter().any(|t| t == topic_str) {
+                let pending = pending_confirmations.remove(index);
+                output.accepted_topics.push(pending.topic);
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
+
+/// Parsed reply to a subscription request.
+///
+/// The server confirms with the topics joined by commas as message, and rejects with a message
+/// such as `Not Subscribed successfully! [topic,topic].  Reason： Blocked! `, both with code 0.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct SubscriptionConfirmation {
+    pub code: i32,
+    pub accepted: Vec<String>,
+    pub rejected: Vec<String>,
+}
+
+impl SubscriptionConfirmation {
+    fn is_error(&self) -> bool {
+        self.code != 0
+    }
+
+    fn mentions_topics(&self) -> bool {
+        !self.accepted.is_empty() || !self.rejected.is_empty()
+    }
+}
+
+impl From<&RawIdCodeMessage> for SubscriptionConfirmation {
+    fn from(value: &RawIdCodeMessage) -> Self {
+        let message = value.message.trim();
+        if message.starts_with("Not Subscribed successfully") {
+            let rejected = match (message.find('['), message.find(']')) {
+                (Some(start), Some(end)) if start < end => split_topics(&message[start + 1..end]),
+                _ => vec![],
+            };
+            return Self {
+                code: value.code,
+                accepted: vec![],
+                rejected,
+            };
+        }
+
+        let accepted = if value.code == 0 {
+            split_topics(message)
+        } else {
+            vec![]
+        };
+        Self {
+            code: value.code,
+            accepted,
+            rejected: vec![],
+        }
+    }
+}
+
+fn split_topics(topics: &str) -> Vec<String> {
+    topics
+        .split(',')
+        .map(|topic| topic.trim())
+        .filter(|topic| !topic.is_empty())
+        .map(|topic| topic.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::ws::health::WebsocketMetrics;
+    use crate::spot::ws::topic::DealsTopic;
+    use tokio::sync::RwLock;
+    use tokio_util::sync::CancellationToken;
+
+    fn id_code_message(json: &str) -> RawIdCodeMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn confirmation_accepted() {
+        let message = id_code_message(
+            r#"{"id":0,"code":0,"msg":"spot@public.deals.v3.api@BTCUSDT,spot@public.kline.v3.api@BTCUSDT@Min1"}"#,
+        );
+        let confirmation = SubscriptionConfirmation::from(&message);
+        assert_eq!(
+            confirmation.accepted,
+            vec![
+                "spot@public.deals.v3.api@BTCUSDT".to_string(),
+                "spot@public.kline.v3.api@BTCUSDT@Min1".to_string()
+            ]
+        );
+        assert!(confirmation.rejected.is_empty());
+    }
+
+    #[test]
+    fn confirmation_rejected() {
+        let message = id_code_message(
+            r#"{"id":0,"code":0,"msg":"Not Subscribed successfully! [spot@public.deals.v3.api@BTCUSDTX].  Reason： Blocked! "}"#,
+        );
+        let confirmation = SubscriptionConfirmation::from(&message);
+        assert!(confirmation.accepted.is_empty());
+        assert_eq!(
+            confirmation.rejected,
+            vec!["spot@public.deals.v3.api@BTCUSDTX".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn error_reply_leaves_topics_unconfirmed() {
+        let topic = Topic::Deals(DealsTopic::new("BTCUSDT".to_string()));
+        let websocket_entry = Arc::new(WebsocketEntry {
+            id: Uuid::new_v4(),
+            auth: None,
+            topics: Arc::new(RwLock::new(vec![topic.clone()])),
+            message_tx: Arc::new(RwLock::new(async_channel::unbounded().0)),
+            cancellation_token: Arc::new(RwLock::new(CancellationToken::new())),
+            metrics: Arc::new(WebsocketMetrics::new()),
+        });
+        let mut pending_confirmations = vec![PendingConfirmation {
+            websocket_entry: websocket_entry.clone(),
+            topic_subscription_string: topic.to_topic_subscription_string(),
+            topic: topic.clone(),
+        }];
+        let (id_code_tx, mut id_code_rx) = tokio::sync::broadcast::channel(8);
+        id_code_tx
+            .send((
+                websocket_entry.id,
+                Arc::new(id_code_message(
+                    r#"{"id":0,"code":1,"msg":"Invalid request"}"#,
+                )),
+            ))
+            .unwrap();
+
+        let mut output = SubscribeOutput::default();
+        let mut rejected_websockets = vec![];
+        let _ = tokio::time::timeout(
+            Duration::from_millis(50),
+            wait_for_confirmations(
+                &mut id_code_rx,
+                &mut pending_confirmations,
+                &mut output,
+                &mut rejected_websockets,
+            ),
+        )
+        .await;
+
+        assert!(output.rejected_topics.is_empty());
+        assert!(rejected_websockets.is_empty());
+        assert_eq!(pending_confirmations.len(), 1);
+        assert_eq!(*websocket_entry.topics.read().await, vec![topic]);
     }
 }
@@ -8,9 +8,44 @@ pub enum Topic {
     Deals(DealsTopic),
     Kline(KlineTopic),
     Depth(DepthTopic),
+    BookTicker(BookTickerTopic),
+    BookTickerBatch(BookTickerBatchTopic),
+    LimitDepth(LimitDepthTopic),
+    IncreaseDepth(IncreaseDepthTopic),
+    IncreaseDepthBatch(IncreaseDepthBatchTopic),
+    MiniTicker(MiniTickerTopic),
+    MiniTickers(MiniTickersTopic),
+}
+
+/// Kind of data of a topic, topics of the same class are expected to update at a similar rate.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum TopicClass {
+    Private,
+    Deals,
+    Kline,
+    Depth,
+    Ticker,
 }
 
 impl Topic {
+    pub fn class(&self) -> TopicClass {
+        match self {
+            Topic::AccountDeals => TopicClass::Private,
+            Topic::AccountOrders => TopicClass::Private,
+            Topic::AccountUpdate => TopicClass::Private,
+            Topic::Deals(_) => TopicClass::Deals,
+            Topic::Kline(_) => TopicClass::Kline,
+            Topic::Depth(_) => TopicClass::Depth,
+            Topic::BookTicker(_) => TopicClass::Ticker,
+            Topic::BookTickerBatch(_) => TopicClass::Ticker,
+            Topic::LimitDepth(_) => TopicClass::Depth,
+            Topic::IncreaseDepth(_) => TopicClass::Depth,
+            Topic::IncreaseDepthBatch(_) => TopicClass::Depth,
+            Topic::MiniTicker(_) => TopicClass::Ticker,
+            Topic::MiniTickers(_) => TopicClass::Ticker,
+        }
+    }
+
     pub fn requires_auth(&self) -> bool {
         match self {
             Topic::AccountDeals => true,
@@ -19,6 +54,13 @@ impl Topic {
             Topic::Deals(_) => false,
             Topic::Kline(_) => false,
             Topic::Depth(_) => false,
+            Topic::BookTicker(_) => false,
+            Topic::BookTickerBatch(_) => false,
+            Topic::LimitDepth(_) => false,
+            Topic::IncreaseDepth(_) => false,
+            Topic::IncreaseDepthBatch(_) => false,
+            Topic::MiniTicker(_) => false,
+            Topic::MiniTickers(_) => false,
         }
     }
 
@@ -41,6 +83,37 @@ impl Topic {
                 symbol = depth_topic.symbol,
                 freq = depth_topic.frequency.to_api_str()
             ),
+            Topic::BookTicker(book_ticker_topic) => format!(
+                "spot@public.aggre.bookTicker.v3.api.pb@{freq}@{symbol}",
+                symbol = book_ticker_topic.symbol,
+                freq = book_ticker_topic.frequency.to_api_str()
+            ),
+            Topic::BookTickerBatch(book_ticker_batch_topic) => format!(
+                "spot@public.bookTicker.batch.v3.api.pb@{symbol}",
+                symbol = book_ticker_batch_topic.symbol
+            ),
+            Topic::LimitDepth(limit_depth_topic) => format!(
+                "spot@public.limit.depth.v3.api.pb@{symbol}@{levels}",
+                symbol = limit_depth_topic.symbol,
+                levels = limit_depth_topic.levels.to_api_str()
+            ),
+            Topic::IncreaseDepth(increase_depth_topic) => format!(
+                "spot@public.increase.depth.v3.api.pb@{symbol}",
+                symbol = increase_depth_topic.symbol
+            ),
+            Topic::IncreaseDepthBatch(increase_depth_batch_topic) => format!(
+                "spot@public.increase.depth.batch.v3.api.pb@{symbol}",
+                symbol = increase_depth_batch_topic.symbol
+            ),
+            Topic::MiniTicker(mini_ticker_topic) => format!(
+                "spot@public.miniTicker.v3.api.pb@{symbol}@{timezone}",
+                symbol = mini_ticker_topic.symbol,
+                timezone = mini_ticker_topic.timezone
+            ),
+            Topic::MiniTickers(mini_tickers_topic) => format!(
+                "spot@public.miniTickers.v3.api.pb@{timezone}",
+                timezone = mini_tickers_topic.timezone
+            ),
         }
     }
 }
@@ -97,3 +170,162 @@ impl DepthTopic {
         }
     }
 }
+
+/// Best bid and ask of a symbol, pushed at most once per frequency.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct BookTickerTopic {
+    pub symbol: String,
+    pub frequency: DepthTopicFrequency,
+}
+
+impl BookTickerTopic {
+    pub fn new(symbol: String) -> Self {
+        Self {
+            symbol,
+            frequency: DepthTopicFrequency::Freq100ms,
+        }
+    }
+}
+
+/// Best bid and ask of a symbol, pushed in batches.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct BookTickerBatchTopic {
+    pub symbol: String,
+}
+
+impl BookTickerBatchTopic {
+    pub fn new(symbol: String) -> Self {
+        Self { symbol }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum LimitDepthLevels {
+    Levels5,
+    Levels10,
+    Levels20,
+}
+
+impl LimitDepthLevels {
+    pub fn to_api_str(&self) -> &'static str {
+        match self {
+            LimitDepthLevels::Levels5 => "5",
+            LimitDepthLevels::Levels10 => "10",
+            LimitDepthLevels::Levels20 => "20",
+        }
+    }
+}
+
+/// Top levels of the order book of a symbol.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct LimitDepthTopic {
+    pub symbol: String,
+    pub levels: LimitDepthLevels,
+}
+
+impl LimitDepthTopic {
+    pub fn new(symbol: String, levels: LimitDepthLevels) -> Self {
+        Self { symbol, levels }
+    }
+}
+
+/// Every change of the order book of a symbol, with a version per change.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct IncreaseDepthTopic {
+    pub symbol: String,
+}
+
+impl IncreaseDepthTopic {
+    pub fn new(symbol: String) -> Self {
+        Self { symbol }
+    }
+}
+
+/// Every change of the order book of a symbol, pushed in batches.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct IncreaseDepthBatchTopic {
+    pub symbol: String,
+}
+
+impl IncreaseDepthBatchTopic {
+    pub fn new(symbol: String) -> Self {
+        Self { symbol }
+    }
+}
+
+/// Mini ticker of a symbol.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct MiniTickerTopic {
+    pub symbol: String,
+    /// Timezone the price change is calculated in, e.g. `24H`, `UTC+0` or `UTC+8`
+    pub timezone: String,
+}
+
+impl MiniTickerTopic {
+    pub fn new(symbol: String) -> Self {
+        Self {
+            symbol,
+            timezone: "UTC+0".to_string(),
+        }
+    }
+
+    pub fn with_timezone(mut self, timezone: String) -> Self {
+        self.timezone = timezone;
+        self
+    }
+}
+
+/// Mini tickers of all symbols.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct MiniTickersTopic {
+    /// Timezone the price change is calculated in, e.g. `24H`, `UTC+0` or `UTC+8`
+    pub timezone: String,
+}
+
+impl Default for MiniTickersTopic {
+    fn default() -> Self {
+        Self::new("UTC+0".to_string())
+    }
+}
+
+impl MiniTickersTopic {
+    pub fn new(timezone: String) -> Self {
+        Self { timezone }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protobuf_topic_subscription_strings() {
+        let symbol = "BTCUSDT".to_string();
+        assert_eq!(
+            Topic::BookTicker(BookTickerTopic::new(symbol.clone())).to_topic_subscription_string(),
+            "spot@public.aggre.bookTicker.v3.api.pb@100ms@BTCUSDT"
+        );
+        assert_eq!(
+            Topic::LimitDepth(LimitDepthTopic::new(
+                symbol.clone(),
+                LimitDepthLevels::Levels20
+            ))
+            .to_topic_subscription_string(),
+            "spot@public.limit.depth.v3.api.pb@BTCUSDT@20"
+        );
+        assert_eq!(
+            Topic::IncreaseDepthBatch(IncreaseDepthBatchTopic::new(symbol.clone()))
+                .to_topic_subscription_string(),
+            "spot@public.increase.depth.batch.v3.api.pb@BTCUSDT"
+        );
+        assert_eq!(
+            Topic::MiniTicker(MiniTickerTopic::new(symbol).with_timezone("UTC+8".to_string()))
+                .to_topic_subscription_string(),
+            "spot@public.miniTicker.v3.api.pb@BTCUSDT@UTC+8"
+        );
+        assert_eq!(
+            Topic::MiniTickers(MiniTickersTopic::default()).to_topic_subscription_string(),
+            "spot@public.miniTickers.v3.api.pb@UTC+0"
+        );
+    }
+}
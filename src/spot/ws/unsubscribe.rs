@@ -1 +1,166 @@
+use crate::spot::ws::auth::WebsocketAuth;
+use crate::spot::ws::connection_event::ConnectionEventKind;
+use crate::spot::ws::listen_key::{delete_listen_key, take_unused_listen_key};
+use crate::spot::ws::topic::Topic;
+use crate::spot::ws::{MexcSpotWebsocketClient, SendableMessage};
+use async_channel::SendError;
+use async_trait::async_trait;
+use std::sync::Arc;
 
+#[derive(Debug)]
+pub struct UnsubscribeParams {
+    pub auth: Option<WebsocketAuth>,
+    pub topics: Vec<Topic>,
+}
+
+impl Default for UnsubscribeParams {
+    fn default() -> Self {
+        Self::new(None, Vec::new())
+    }
+}
+
+impl UnsubscribeParams {
+    pub fn new(auth: Option<WebsocketAuth>, topics: Vec<Topic>) -> Self {
+        Self { auth, topics }
+    }
+
+    pub fn with_auth(mut self, auth: WebsocketAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_topic(mut self, topic: Topic) -> Self {
+        self.topics.push(topic);
+        self
+    }
+
+    pub fn with_topics(mut self, topics: Vec<Topic>) -> Self {
+        self.topics.extend(topics);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnsubscribeOutput {}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UnsubscribeError {
+    #[error("Requested topics require authentication")]
+    RequestedTopicsRequireAuthentication,
+
+    #[error("Failed to send message through channel: {0}")]
+    SendError(#[from] SendError<SendableMessage>),
+}
+
+#[async_trait]
+pub trait Unsubscribe {
+    async fn unsubscribe(
+        self: Arc<Self>,
+        params: UnsubscribeParams,
+    ) -> Result<UnsubscribeOutput, UnsubscribeError>;
+}
+
+#[async_trait]
+impl Unsubscribe for MexcSpotWebsocketClient {
+    async fn unsubscribe(
+        self: Arc<Self>,
+        params: UnsubscribeParams,
+    ) -> Result<UnsubscribeOutput, UnsubscribeError> {
+        let (private_topics, public_topics) = params
+            .topics
+            .into_iter()
+            .partition::<Vec<_>, _>(|topic| topic.requires_auth());
+
+        if params.auth.is_none() && !private_topics.is_empty() {
+            return Err(UnsubscribeError::RequestedTopicsRequireAuthentication);
+        }
+
+        let mut unsubscriptions = vec![];
+        let mut closed_websockets = vec![];
+        let mut unused_listen_keys = vec![];
+        {
+            let mut inner = self.inner.write().await;
+
+            for websocket_entry in inner.websockets.iter() {
+                let mut topics = websocket_entry.topics.write().await;
+                // Private topics are bound to the listen key of the websocket, so only the
+                // websockets of the given auth can facilitate them.
+                let is_same_auth = websocket_entry.auth.is_some()
+                    && websocket_entry.auth.as_ref() == params.auth.as_ref();
+                let topics_to_remove = topics
+                    .iter()
+                    .filter(|&topic| {
+                        public_topics.contains(topic)
+                            || (is_same_auth && private_topics.contains(topic))
+                    })
+                    .cloned()
+                    .collect::<Vec<Topic>>();
+                if topics_to_remove.is_empty() {
+                    continue;
+                }
+
+                topics.retain(|topic| !topics_to_remove.contains(topic));
+                if topics.is_empty() {
+                    // No need to unsubscribe, the connection is going to be closed anyway.
+                    closed_websockets.push((websocket_entry.clone(), topics_to_remove));
+                    continue;
+                }
+
+                let params = topics_to_remove
+                    .iter()
+                    .map(|topic| topic.to_topic_subscription_string())
+                    .collect::<Vec<String>>();
+                unsubscriptions.push((
+                    websocket_entry.clone(),
+                    SendableMessage::Unsubscription(params),
+                ));
+            }
+
+            inner.websockets.retain(|websocket_entry| {
+                closed_websockets
+                    .iter()
+                    .all(|(closed_websocket, _)| closed_websocket.id != websocket_entry.id)
+            });
+
+            for (closed_websocket, _) in closed_websockets.iter() {
+                let Some(auth) = &closed_websocket.auth else {
+                    continue;
+                };
+                if let Some(listen_key) = take_unused_listen_key(&mut inner, auth) {
+                    unused_listen_keys.push((auth.clone(), listen_key));
+                }
+            }
+        }
+
+        // Sent without holding the lock. The topics are already removed, so a failed send is
+        // reported once everything else is done rather than leaving the rest undone.
+        let mut send_result = Ok(());
+        for (websocket_entry, sendable_message) in unsubscriptions {
+            let tx = websocket_entry.message_tx.read().await.clone();
+            if let Err(err) = tx.send(sendable_message).await {
+                if send_result.is_ok() {
+                    send_result = Err(err);
+                }
+            }
+        }
+
+        for (closed_websocket, removed_topics) in closed_websockets.into_iter() {
+            tracing::debug!(
+                "Closing websocket with id {} because it has no topics left",
+                closed_websocket.id
+            );
+            closed_websocket.cancellation_token.read().await.cancel();
+            self.emit_connection_event(
+                closed_websocket.id,
+                removed_topics,
+                ConnectionEventKind::Closed,
+            );
+        }
+        for (auth, listen_key) in unused_listen_keys {
+            delete_listen_key(&self, &auth, &listen_key).await;
+        }
+
+        send_result?;
+        Ok(UnsubscribeOutput {})
+    }
+}
@@ -0,0 +1,153 @@
+use crate::spot::ws::acquire_websocket::{cancel_websocket, handle_disconnect};
+use crate::spot::ws::connection_event::DisconnectCause;
+use crate::spot::ws::health::WebsocketMetrics;
+use crate::spot::ws::topic::{Topic, TopicClass};
+use crate::spot::ws::MexcSpotWebsocketClient;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Determines how long a websocket may go without receiving any frame, including replies to
+/// pings, before it is considered stale and reconnected.
+#[derive(Debug, Clone)]
+pub struct WatchdogPolicy {
+    /// Window for topic classes without a window of their own, `None` disables the watchdog
+    /// for them.
+    pub default_window: Option<Duration>,
+    pub windows: HashMap<TopicClass, Duration>,
+}
+
+impl Default for WatchdogPolicy {
+    /// Allows for a few missed pings with the default ping interval.
+    fn default() -> Self {
+        Self::for_ping_interval(Duration::from_secs(30))
+    }
+}
+
+impl WatchdogPolicy {
+    pub fn new(default_window: Option<Duration>) -> Self {
+        Self {
+            default_window,
+            windows: HashMap::new(),
+        }
+    }
+
+    /// Default window of three ping intervals, so a quiet websocket is only considered stale
+    /// after the replies to two pings in a row went missing.
+    pub fn for_ping_interval(ping_interval: Duration) -> Self {
+        Self::new(Some(ping_interval * 3))
+    }
+
+    pub fn disabled() -> Self {
+        Self::new(None)
+    }
+
+    pub fn with_default_window(mut self, default_window: Duration) -> Self {
+        self.default_window = Some(default_window);
+        self
+    }
+
+    pub fn with_window(mut self, topic_class: TopicClass, window: Duration) -> Self {
+        self.windows.insert(topic_class, window);
+        self
+    }
+
+    /// Shortest window of the topics, `None` when none of them are watched.
+    pub fn window_for(&self, topics: &[Topic]) -> Option<Duration> {
+        if topics.is_empty() {
+            return self.default_window;
+        }
+
+        topics
+            .iter()
+            .filter_map(|topic| {
+                self.windows
+                    .get(&topic.class())
+                    .copied()
+                    .or(self.default_window)
+            })
+            .min()
+    }
+}
+
+/// Reconnects the websocket once it did not receive anything within the window of its topics.
+pub(crate) fn spawn_websocket_watchdog_task(
+    this: Arc<MexcSpotWebsocketClient>,
+    cancellation_token: CancellationToken,
+    websocket_id: Uuid,
+    metrics: Arc<WebsocketMetrics>,
+) {
+    this.clone().spawn_task(async move {
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    break;
+                }
+                _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+            }
+
+            let topics = this.websocket_topics(websocket_id).await;
+            let Some(window) = this.config.watchdog_policy.window_for(&topics) else {
+                continue;
+            };
+            let idle_time = metrics.idle_time();
+            if idle_time < window {
+                continue;
+            }
+
+            tracing::warn!(
+                "Websocket with id {} did not receive anything for {:?}, reconnecting",
+                websocket_id,
+                idle_time
+            );
+            if cancel_websocket(&cancellation_token) {
+                handle_disconnect(
+                    this.clone(),
+                    websocket_id,
+                    DisconnectCause::Stale(idle_time),
+                )
+                .await;
+            }
+            break;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::ws::topic::{DealsTopic, LimitDepthLevels, LimitDepthTopic};
+
+    #[test]
+    fn window_for_topics() {
+        let policy = WatchdogPolicy::new(Some(Duration::from_secs(90)))
+            .with_window(TopicClass::Depth, Duration::from_secs(10));
+        let deals = Topic::Deals(DealsTopic::new("BTCUSDT".to_string()));
+        let depth = Topic::LimitDepth(LimitDepthTopic::new(
+            "BTCUSDT".to_string(),
+            LimitDepthLevels::Levels5,
+        ));
+
+        assert_eq!(policy.window_for(&[]), Some(Duration::from_secs(90)));
+        assert_eq!(
+            policy.window_for(std::slice::from_ref(&deals)),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            policy.window_for(&[deals.clone(), depth.clone()]),
+            Some(Duration::from_secs(10))
+        );
+
+        let policy =
+            WatchdogPolicy::disabled().with_window(TopicClass::Depth, Duration::from_secs(10));
+        assert_eq!(policy.window_for(std::slice::from_ref(&deals)), None);
+        assert_eq!(
+            policy.window_for(&[deals, depth]),
+            Some(Duration::from_secs(10))
+        );
+    }
+}